@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+use josekit::jwk::KeyPair;
+use josekit::jws::{JwsSigner, PS256};
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+
+const ITERATIONS: u32 = 1000;
+
+fn measure<F: FnMut()>(name: &str, mut f: F) -> Duration {
+    for _ in 0..10 {
+        f();
+    }
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "{:<10} {:>10.3} us/iter",
+        name,
+        elapsed.as_secs_f64() * 1_000_000.0 / ITERATIONS as f64
+    );
+    elapsed
+}
+
+fn main() {
+    let message = b"eyJhbGciOiJQUzI1NiJ9.eyJzdWIiOiJqb3Nla2l0In0";
+
+    let key_pair = PS256.generate_key_pair(2048).unwrap();
+    let private_key = PKey::private_key_from_der(&key_pair.to_der_private_key()).unwrap();
+    let signer = PS256
+        .signer_from_der(&key_pair.to_der_private_key())
+        .unwrap();
+
+    let naive = measure("naive", || {
+        let mut signer = Signer::new(MessageDigest::sha256(), &private_key).unwrap();
+        signer.update(message).unwrap();
+        signer.sign_to_vec().unwrap();
+    });
+
+    let pooled = measure("pooled", || {
+        signer.sign(message).unwrap();
+    });
+
+    println!(
+        "pooled/naive: {:.3}",
+        pooled.as_secs_f64() / naive.as_secs_f64()
+    );
+}
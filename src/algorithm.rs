@@ -0,0 +1,354 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+
+use crate::JoseError;
+
+/// Represents a name of JWS and JWE algorithms supported by this library.
+///
+/// The "none" algorithm of unsecured JWTs is not included,
+/// so parsing it fails like an unknown name.
+///
+/// This enum is non-exhaustive because the `legacy-algorithms` feature adds
+/// the `Rs1` variant.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+#[non_exhaustive]
+pub enum Algorithm {
+    /// HMAC using SHA-256
+    Hs256,
+    /// HMAC using SHA-384
+    Hs384,
+    /// HMAC using SHA-512
+    Hs512,
+    /// RSASSA-PKCS1-v1_5 using SHA-1
+    #[cfg(feature = "legacy-algorithms")]
+    Rs1,
+    /// RSASSA-PKCS1-v1_5 using SHA-256
+    Rs256,
+    /// RSASSA-PKCS1-v1_5 using SHA-384
+    Rs384,
+    /// RSASSA-PKCS1-v1_5 using SHA-512
+    Rs512,
+    /// RSASSA-PSS using SHA-256 and MGF1 with SHA-256
+    Ps256,
+    /// RSASSA-PSS using SHA-384 and MGF1 with SHA-384
+    Ps384,
+    /// RSASSA-PSS using SHA-512 and MGF1 with SHA-512
+    Ps512,
+    /// ECDSA using P-256 and SHA-256
+    Es256,
+    /// ECDSA using P-384 and SHA-384
+    Es384,
+    /// ECDSA using P-521 and SHA-512
+    Es512,
+    /// ECDSA using secp256k1 curve and SHA-256
+    Es256k,
+    /// EdDSA signature algorithms
+    Eddsa,
+    /// Direct use of a shared symmetric key as the CEK
+    Dir,
+    /// Elliptic Curve Diffie-Hellman Ephemeral Static key agreement using Concat KDF
+    EcdhEs,
+    /// ECDH-ES using Concat KDF and CEK wrapped with "A128KW"
+    EcdhEsA128kw,
+    /// ECDH-ES using Concat KDF and CEK wrapped with "A192KW"
+    EcdhEsA192kw,
+    /// ECDH-ES using Concat KDF and CEK wrapped with "A256KW"
+    EcdhEsA256kw,
+    /// AES Key Wrap with default initial value using 128-bit key
+    A128kw,
+    /// AES Key Wrap with default initial value using 192-bit key
+    A192kw,
+    /// AES Key Wrap with default initial value using 256-bit key
+    A256kw,
+    /// Key wrapping with AES GCM using 128-bit key
+    A128gcmkw,
+    /// Key wrapping with AES GCM using 192-bit key
+    A192gcmkw,
+    /// Key wrapping with AES GCM using 256-bit key
+    A256gcmkw,
+    /// PBES2 with HMAC SHA-256 and "A128KW" wrapping
+    Pbes2Hs256A128kw,
+    /// PBES2 with HMAC SHA-384 and "A192KW" wrapping
+    Pbes2Hs384A192kw,
+    /// PBES2 with HMAC SHA-512 and "A256KW" wrapping
+    Pbes2Hs512A256kw,
+    /// RSAES-PKCS1-v1_5
+    Rsa1_5,
+    /// RSAES OAEP using default parameters
+    RsaOaep,
+    /// RSAES OAEP using SHA-256 and MGF1 with SHA-256
+    RsaOaep256,
+    /// RSAES OAEP using SHA-384 and MGF1 with SHA-384
+    RsaOaep384,
+    /// RSAES OAEP using SHA-512 and MGF1 with SHA-512
+    RsaOaep512,
+    /// AES_128_CBC_HMAC_SHA_256 authenticated encryption algorithm
+    A128cbcHs256,
+    /// AES_192_CBC_HMAC_SHA_384 authenticated encryption algorithm
+    A192cbcHs384,
+    /// AES_256_CBC_HMAC_SHA_512 authenticated encryption algorithm
+    A256cbcHs512,
+    /// AES GCM using 128-bit key
+    A128gcm,
+    /// AES GCM using 192-bit key
+    A192gcm,
+    /// AES GCM using 256-bit key
+    A256gcm,
+    /// ChaCha20-Poly1305 using 256-bit key and 96-bit nonce
+    C20p,
+    /// XChaCha20-Poly1305 using 256-bit key and 192-bit nonce
+    Xc20p,
+}
+
+impl Algorithm {
+    /// Return the name of this algorithm that is used as an alg or enc header claim value.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Hs256 => "HS256",
+            Self::Hs384 => "HS384",
+            Self::Hs512 => "HS512",
+            #[cfg(feature = "legacy-algorithms")]
+            Self::Rs1 => "RS1",
+            Self::Rs256 => "RS256",
+            Self::Rs384 => "RS384",
+            Self::Rs512 => "RS512",
+            Self::Ps256 => "PS256",
+            Self::Ps384 => "PS384",
+            Self::Ps512 => "PS512",
+            Self::Es256 => "ES256",
+            Self::Es384 => "ES384",
+            Self::Es512 => "ES512",
+            Self::Es256k => "ES256K",
+            Self::Eddsa => "EdDSA",
+            Self::Dir => "dir",
+            Self::EcdhEs => "ECDH-ES",
+            Self::EcdhEsA128kw => "ECDH-ES+A128KW",
+            Self::EcdhEsA192kw => "ECDH-ES+A192KW",
+            Self::EcdhEsA256kw => "ECDH-ES+A256KW",
+            Self::A128kw => "A128KW",
+            Self::A192kw => "A192KW",
+            Self::A256kw => "A256KW",
+            Self::A128gcmkw => "A128GCMKW",
+            Self::A192gcmkw => "A192GCMKW",
+            Self::A256gcmkw => "A256GCMKW",
+            Self::Pbes2Hs256A128kw => "PBES2-HS256+A128KW",
+            Self::Pbes2Hs384A192kw => "PBES2-HS384+A192KW",
+            Self::Pbes2Hs512A256kw => "PBES2-HS512+A256KW",
+            Self::Rsa1_5 => "RSA1_5",
+            Self::RsaOaep => "RSA-OAEP",
+            Self::RsaOaep256 => "RSA-OAEP-256",
+            Self::RsaOaep384 => "RSA-OAEP-384",
+            Self::RsaOaep512 => "RSA-OAEP-512",
+            Self::A128cbcHs256 => "A128CBC-HS256",
+            Self::A192cbcHs384 => "A192CBC-HS384",
+            Self::A256cbcHs512 => "A256CBC-HS512",
+            Self::A128gcm => "A128GCM",
+            Self::A192gcm => "A192GCM",
+            Self::A256gcm => "A256GCM",
+            Self::C20p => "C20P",
+            Self::Xc20p => "XC20P",
+        }
+    }
+
+    /// Return whether this algorithm is a JWS signature or MAC algorithm (alg of JWS).
+    pub fn is_signature(&self) -> bool {
+        match self {
+            Self::Hs256 | Self::Hs384 | Self::Hs512 => true,
+            #[cfg(feature = "legacy-algorithms")]
+            Self::Rs1 => true,
+            Self::Rs256 | Self::Rs384 | Self::Rs512 => true,
+            Self::Ps256 | Self::Ps384 | Self::Ps512 => true,
+            Self::Es256 | Self::Es384 | Self::Es512 | Self::Es256k => true,
+            Self::Eddsa => true,
+            _ => false,
+        }
+    }
+
+    /// Return whether this algorithm is a JWE key management algorithm (alg of JWE).
+    pub fn is_key_management(&self) -> bool {
+        !self.is_signature() && !self.is_content_encryption()
+    }
+
+    /// Return whether this algorithm is a JWE content encryption algorithm (enc of JWE).
+    pub fn is_content_encryption(&self) -> bool {
+        matches!(
+            self,
+            Self::A128cbcHs256
+                | Self::A192cbcHs384
+                | Self::A256cbcHs512
+                | Self::A128gcm
+                | Self::A192gcm
+                | Self::A256gcm
+                | Self::C20p
+                | Self::Xc20p
+        )
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = JoseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let algorithm = match value {
+            "HS256" => Self::Hs256,
+            "HS384" => Self::Hs384,
+            "HS512" => Self::Hs512,
+            #[cfg(feature = "legacy-algorithms")]
+            "RS1" => Self::Rs1,
+            "RS256" => Self::Rs256,
+            "RS384" => Self::Rs384,
+            "RS512" => Self::Rs512,
+            "PS256" => Self::Ps256,
+            "PS384" => Self::Ps384,
+            "PS512" => Self::Ps512,
+            "ES256" => Self::Es256,
+            "ES384" => Self::Es384,
+            "ES512" => Self::Es512,
+            "ES256K" => Self::Es256k,
+            "EdDSA" => Self::Eddsa,
+            "dir" => Self::Dir,
+            "ECDH-ES" => Self::EcdhEs,
+            "ECDH-ES+A128KW" => Self::EcdhEsA128kw,
+            "ECDH-ES+A192KW" => Self::EcdhEsA192kw,
+            "ECDH-ES+A256KW" => Self::EcdhEsA256kw,
+            "A128KW" => Self::A128kw,
+            "A192KW" => Self::A192kw,
+            "A256KW" => Self::A256kw,
+            "A128GCMKW" => Self::A128gcmkw,
+            "A192GCMKW" => Self::A192gcmkw,
+            "A256GCMKW" => Self::A256gcmkw,
+            "PBES2-HS256+A128KW" => Self::Pbes2Hs256A128kw,
+            "PBES2-HS384+A192KW" => Self::Pbes2Hs384A192kw,
+            "PBES2-HS512+A256KW" => Self::Pbes2Hs512A256kw,
+            "RSA1_5" => Self::Rsa1_5,
+            "RSA-OAEP" => Self::RsaOaep,
+            "RSA-OAEP-256" => Self::RsaOaep256,
+            "RSA-OAEP-384" => Self::RsaOaep384,
+            "RSA-OAEP-512" => Self::RsaOaep512,
+            "A128CBC-HS256" => Self::A128cbcHs256,
+            "A192CBC-HS384" => Self::A192cbcHs384,
+            "A256CBC-HS512" => Self::A256cbcHs512,
+            "A128GCM" => Self::A128gcm,
+            "A192GCM" => Self::A192gcm,
+            "A256GCM" => Self::A256gcm,
+            "C20P" => Self::C20p,
+            "XC20P" => Self::Xc20p,
+            val => {
+                return Err(JoseError::UnsupportedAlgorithm(anyhow!(
+                    "Unknown algorithm: {}",
+                    val
+                )))
+            }
+        };
+        Ok(algorithm)
+    }
+}
+
+impl Display for Algorithm {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        fmt.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::Algorithm;
+    use crate::jwe::enc::{
+        A128CBC_HS256, A128GCM, A192CBC_HS384, A192GCM, A256CBC_HS512, A256GCM, C20P, XC20P,
+    };
+    use crate::jwe::{self, JweAlgorithm, JweContentEncryption};
+    use crate::jws::{self, JwsAlgorithm};
+    use crate::JoseError;
+
+    #[test]
+    fn test_algorithm_from_str_and_display() -> Result<()> {
+        let signatures: Vec<&dyn JwsAlgorithm> = vec![
+            &jws::HS256,
+            &jws::HS384,
+            &jws::HS512,
+            #[cfg(feature = "legacy-algorithms")]
+            &jws::RS1,
+            &jws::RS256,
+            &jws::RS384,
+            &jws::RS512,
+            &jws::PS256,
+            &jws::PS384,
+            &jws::PS512,
+            &jws::ES256,
+            &jws::ES384,
+            &jws::ES512,
+            &jws::ES256K,
+            &jws::EdDSA,
+        ];
+        for alg in signatures {
+            let algorithm: Algorithm = alg.name().parse()?;
+            assert_eq!(algorithm.to_string(), alg.name());
+            assert!(algorithm.is_signature(), "{}", algorithm);
+            assert!(!algorithm.is_key_management(), "{}", algorithm);
+            assert!(!algorithm.is_content_encryption(), "{}", algorithm);
+        }
+
+        let key_managements: Vec<&dyn JweAlgorithm> = vec![
+            &jwe::Dir,
+            &jwe::ECDH_ES,
+            &jwe::ECDH_ES_A128KW,
+            &jwe::ECDH_ES_A192KW,
+            &jwe::ECDH_ES_A256KW,
+            &jwe::A128KW,
+            &jwe::A192KW,
+            &jwe::A256KW,
+            &jwe::A128GCMKW,
+            &jwe::A192GCMKW,
+            &jwe::A256GCMKW,
+            &jwe::PBES2_HS256_A128KW,
+            &jwe::PBES2_HS384_A192KW,
+            &jwe::PBES2_HS512_A256KW,
+            #[allow(deprecated)]
+            &jwe::RSA1_5,
+            &jwe::RSA_OAEP,
+            &jwe::RSA_OAEP_256,
+            &jwe::RSA_OAEP_384,
+            &jwe::RSA_OAEP_512,
+        ];
+        for alg in key_managements {
+            let algorithm: Algorithm = alg.name().parse()?;
+            assert_eq!(algorithm.to_string(), alg.name());
+            assert!(!algorithm.is_signature(), "{}", algorithm);
+            assert!(algorithm.is_key_management(), "{}", algorithm);
+            assert!(!algorithm.is_content_encryption(), "{}", algorithm);
+        }
+
+        let content_encryptions: Vec<&dyn JweContentEncryption> = vec![
+            &A128CBC_HS256,
+            &A192CBC_HS384,
+            &A256CBC_HS512,
+            &A128GCM,
+            &A192GCM,
+            &A256GCM,
+            &C20P,
+            &XC20P,
+        ];
+        for enc in content_encryptions {
+            let algorithm: Algorithm = enc.name().parse()?;
+            assert_eq!(algorithm.to_string(), enc.name());
+            assert!(!algorithm.is_signature(), "{}", algorithm);
+            assert!(!algorithm.is_key_management(), "{}", algorithm);
+            assert!(algorithm.is_content_encryption(), "{}", algorithm);
+        }
+
+        for name in &["none", "hs256", "RSA-OAEP-128", ""] {
+            let err = name.parse::<Algorithm>().unwrap_err();
+            assert!(
+                matches!(err, JoseError::UnsupportedAlgorithm(_)),
+                "{}",
+                name
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+use anyhow::bail;
+
+use crate::util;
+use crate::{JoseError, Value};
+
+/// Represents the kind of a compact serialization that is guessed by its segment count.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum CompactKind {
+    /// JWS compact serialization of three segments
+    Jws,
+    /// JWE compact serialization of five segments
+    Jwe,
+}
+
+/// Split the input that is formatted by compact serialization into the raw base64url segments.
+///
+/// This function only checks the structure. Neither the segments are decoded
+/// nor the signature or the encryption is verified. Empty segments are allowed
+/// (e.g. the encrypted key of "dir" or a detached payload). Whitespace including
+/// a trailing newline is rejected, so the input must be trimmed by the caller.
+///
+/// The payload segment of a JWS whose protected header has "b64": false
+/// (RFC 7797) is not base64url encoded, so its characters are not checked.
+///
+/// # Arguments
+///
+/// * `input` - a compact serialization string.
+pub fn split_compact(input: &str) -> Result<(Vec<&str>, CompactKind), JoseError> {
+    (|| -> anyhow::Result<(Vec<&str>, CompactKind)> {
+        let segments: Vec<&str> = input.split('.').collect();
+        let kind = match segments.len() {
+            3 => CompactKind::Jws,
+            5 => CompactKind::Jwe,
+            val => bail!(
+                "The compact serialization must be three or five segments separated by period: {}",
+                val
+            ),
+        };
+
+        let unencoded_payload = kind == CompactKind::Jws && is_unencoded_payload(segments[0]);
+
+        let mut pos = 0;
+        for (i, segment) in segments.iter().enumerate() {
+            if !(i == 1 && unencoded_payload) {
+                if let Some((offset, c)) = segment
+                    .char_indices()
+                    .find(|(_, c)| !c.is_ascii_alphanumeric() && *c != '-' && *c != '_')
+                {
+                    if c.is_whitespace() {
+                        bail!(
+                            "The compact serialization must not contain whitespace: position {}",
+                            pos + offset
+                        );
+                    }
+                    bail!(
+                        "The segment {} contains a non-base64url character: {:?}",
+                        i,
+                        c
+                    );
+                }
+            }
+            pos += segment.len() + 1;
+        }
+        if segments[0].is_empty() {
+            bail!("The header segment must not be empty.");
+        }
+
+        Ok((segments, kind))
+    })()
+    .map_err(JoseError::InvalidJwtFormat)
+}
+
+/// Return whether the header segment has "b64": false (RFC 7797).
+fn is_unencoded_payload(header: &str) -> bool {
+    let header = match util::decode_base64url_segment(header) {
+        Ok(val) => val,
+        Err(_) => return false,
+    };
+    match util::parse_json_object(&header) {
+        Ok(map) => map.get("b64") == Some(&Value::Bool(false)),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use anyhow::Result;
+
+    use crate::{split_compact, CompactKind};
+
+    #[test]
+    fn test_split_compact() -> Result<()> {
+        let data = String::from_utf8(load_file("jwt/RS256.jwt")?)?;
+        let (segments, kind) = split_compact(&data)?;
+        assert_eq!(kind, CompactKind::Jws);
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments.join("."), data);
+
+        let data = String::from_utf8(load_file("jwt/A128KW_A256GCM.jwt")?)?;
+        let (segments, kind) = split_compact(&data)?;
+        assert_eq!(kind, CompactKind::Jwe);
+        assert_eq!(segments.len(), 5);
+        assert_eq!(segments.join("."), data);
+
+        let (segments, kind) = split_compact("eyJhbGciOiJkaXIifQ..aXY.Y2lwaGVy.dGFn")?;
+        assert_eq!(kind, CompactKind::Jwe);
+        assert_eq!(segments[1], "");
+
+        let (segments, kind) = split_compact("eyJhbGciOiJub25lIn0.cGF5bG9hZA.")?;
+        assert_eq!(kind, CompactKind::Jws);
+        assert_eq!(segments[2], "");
+
+        for input in &[
+            "",
+            "a.b",
+            "a.b.c.d",
+            "a.b.c.d.e.f",
+            ".b.c",
+            "a.b+.c",
+            "a.b.c=",
+            "a.b.c\n",
+            " a.b.c",
+            "a.b .c",
+        ] {
+            assert!(split_compact(input).is_err(), "{:?}", input);
+        }
+        split_compact("a.b.c\n".trim())?;
+
+        // RFC 7797 Section 4.2: an unencoded payload is not base64url.
+        let (segments, kind) = split_compact(
+            "eyJhbGciOiJIUzI1NiIsImI2NCI6ZmFsc2UsImNyaXQiOlsiYjY0Il19.$ 02.\
+             A5dxf2s96_n5FLueVuW1Z_vh161FwXZC4YLPff6dmDY",
+        )?;
+        assert_eq!(kind, CompactKind::Jws);
+        assert_eq!(segments[1], "$ 02");
+        assert!(split_compact("eyJhbGciOiJIUzI1NiJ9.$.sig").is_err());
+
+        Ok(())
+    }
+
+    fn load_file(path: &str) -> Result<Vec<u8>> {
+        let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        pb.push("data");
+        pb.push(path);
+
+        let data = fs::read(&pb)?;
+        Ok(data)
+    }
+}
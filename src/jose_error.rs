@@ -1,3 +1,5 @@
+use std::fmt::{self, Display};
+
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -5,6 +7,9 @@ pub enum JoseError {
     #[error("Unsupported signature algorithm: {0}")]
     UnsupportedSignatureAlgorithm(#[source] anyhow::Error),
 
+    #[error("Unsupported algorithm: {0}")]
+    UnsupportedAlgorithm(#[source] anyhow::Error),
+
     #[error("Invalid JWT format: {0}")]
     InvalidJwtFormat(#[source] anyhow::Error),
 
@@ -28,4 +33,295 @@ pub enum JoseError {
 
     #[error("Invalid signature: {0}")]
     InvalidSignature(#[source] anyhow::Error),
+
+    #[error("Invalid passphrase: {0}")]
+    InvalidPassphrase(#[source] anyhow::Error),
+
+    #[error("Random generation failed: {0}")]
+    RandomGenerationFailed(#[source] anyhow::Error),
+}
+
+impl JoseError {
+    /// Return the same kind of error that is wrapped by a context message.
+    pub(crate) fn with_context(self, context: &'static str) -> JoseError {
+        match self {
+            JoseError::UnsupportedSignatureAlgorithm(err) => {
+                JoseError::UnsupportedSignatureAlgorithm(err.context(context))
+            }
+            JoseError::UnsupportedAlgorithm(err) => {
+                JoseError::UnsupportedAlgorithm(err.context(context))
+            }
+            JoseError::InvalidJwtFormat(err) => JoseError::InvalidJwtFormat(err.context(context)),
+            JoseError::InvalidJwkFormat(err) => JoseError::InvalidJwkFormat(err.context(context)),
+            JoseError::InvalidJwsFormat(err) => JoseError::InvalidJwsFormat(err.context(context)),
+            JoseError::InvalidJweFormat(err) => JoseError::InvalidJweFormat(err.context(context)),
+            JoseError::InvalidKeyFormat(err) => JoseError::InvalidKeyFormat(err.context(context)),
+            JoseError::InvalidJson(err) => JoseError::InvalidJson(err.context(context)),
+            JoseError::InvalidClaim(err) => JoseError::InvalidClaim(err.context(context)),
+            JoseError::InvalidSignature(err) => JoseError::InvalidSignature(err.context(context)),
+            JoseError::InvalidPassphrase(err) => JoseError::InvalidPassphrase(err.context(context)),
+            JoseError::RandomGenerationFailed(err) => {
+                JoseError::RandomGenerationFailed(err.context(context))
+            }
+        }
+    }
+
+    /// Return the JWK parameter error that caused this error if it exists.
+    pub fn jwk_parameter_error(&self) -> Option<&JwkParameterError> {
+        let err = match self {
+            JoseError::UnsupportedSignatureAlgorithm(err) => err,
+            JoseError::UnsupportedAlgorithm(err) => err,
+            JoseError::InvalidJwtFormat(err) => err,
+            JoseError::InvalidJwkFormat(err) => err,
+            JoseError::InvalidJwsFormat(err) => err,
+            JoseError::InvalidJweFormat(err) => err,
+            JoseError::InvalidKeyFormat(err) => err,
+            JoseError::InvalidJson(err) => err,
+            JoseError::InvalidClaim(err) => err,
+            JoseError::InvalidSignature(err) => err,
+            JoseError::InvalidPassphrase(err) => err,
+            JoseError::RandomGenerationFailed(err) => err,
+        };
+
+        if let Some(val) = err.downcast_ref::<JwkParameterError>() {
+            Some(val)
+        } else if let Some(val) = err.downcast_ref::<JoseError>() {
+            val.jwk_parameter_error()
+        } else {
+            None
+        }
+    }
+}
+
+/// Represents a failure kind of a JWK parameter.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum JwkParameterErrorKind {
+    /// The parameter is required but missing.
+    Missing,
+    /// The parameter has a wrong JSON type.
+    WrongType,
+    /// The parameter cannot be decoded.
+    DecodeError,
+    /// The parameter is decoded but has an invalid value (e.g. a wrong length).
+    InvalidValue,
+}
+
+/// Represents an error of a specified JWK parameter.
+#[derive(Debug)]
+pub struct JwkParameterError {
+    parameter: String,
+    kind: JwkParameterErrorKind,
+    cause: Option<anyhow::Error>,
+}
+
+impl JwkParameterError {
+    /// Return a error for a missing parameter.
+    ///
+    /// # Arguments
+    ///
+    /// * `parameter` - a parameter name
+    pub fn missing(parameter: impl Into<String>) -> Self {
+        Self {
+            parameter: parameter.into(),
+            kind: JwkParameterErrorKind::Missing,
+            cause: None,
+        }
+    }
+
+    /// Return a error for a parameter that has a wrong type.
+    ///
+    /// # Arguments
+    ///
+    /// * `parameter` - a parameter name
+    pub fn wrong_type(parameter: impl Into<String>) -> Self {
+        Self {
+            parameter: parameter.into(),
+            kind: JwkParameterErrorKind::WrongType,
+            cause: None,
+        }
+    }
+
+    /// Return a error for a parameter that cannot be decoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `parameter` - a parameter name
+    /// * `cause` - a cause of the decode error
+    pub fn decode_error(parameter: impl Into<String>, cause: impl Into<anyhow::Error>) -> Self {
+        Self {
+            parameter: parameter.into(),
+            kind: JwkParameterErrorKind::DecodeError,
+            cause: Some(cause.into()),
+        }
+    }
+
+    /// Return a error for a parameter that has an invalid value.
+    ///
+    /// # Arguments
+    ///
+    /// * `parameter` - a parameter name
+    /// * `cause` - a cause of the invalid value
+    pub fn invalid_value(parameter: impl Into<String>, cause: impl Into<anyhow::Error>) -> Self {
+        Self {
+            parameter: parameter.into(),
+            kind: JwkParameterErrorKind::InvalidValue,
+            cause: Some(cause.into()),
+        }
+    }
+
+    /// Return the name of the failing parameter.
+    pub fn parameter(&self) -> &str {
+        &self.parameter
+    }
+
+    /// Return the failure kind.
+    pub fn kind(&self) -> JwkParameterErrorKind {
+        self.kind
+    }
+}
+
+impl Display for JwkParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            JwkParameterErrorKind::Missing => {
+                write!(f, "A parameter {} is required.", self.parameter)
+            }
+            JwkParameterErrorKind::WrongType => {
+                write!(f, "A parameter {} must be a string.", self.parameter)
+            }
+            JwkParameterErrorKind::DecodeError => match &self.cause {
+                Some(cause) => write!(
+                    f,
+                    "A parameter {} cannot be decoded: {}",
+                    self.parameter, cause
+                ),
+                None => write!(f, "A parameter {} cannot be decoded.", self.parameter),
+            },
+            JwkParameterErrorKind::InvalidValue => match &self.cause {
+                Some(cause) => write!(f, "A parameter {} is invalid: {}", self.parameter, cause),
+                None => write!(f, "A parameter {} is invalid.", self.parameter),
+            },
+        }
+    }
+}
+
+impl std::error::Error for JwkParameterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.cause.as_ref().map(|err| err.as_ref())
+    }
+}
+
+/// Extension for adding context of a processed token to a JoseError result.
+pub trait JoseErrorContext<T> {
+    /// Return the result that the error is converted into an anyhow error
+    /// with the context (token kind, algorithm and key ID).
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - a token kind (e.g. JWS, JWE, JWT)
+    /// * `algorithm` - a algorithm name
+    /// * `key_id` - a key ID
+    fn with_jose_context(
+        self,
+        kind: &str,
+        algorithm: &str,
+        key_id: Option<&str>,
+    ) -> anyhow::Result<T>;
+}
+
+impl<T> JoseErrorContext<T> for Result<T, JoseError> {
+    fn with_jose_context(
+        self,
+        kind: &str,
+        algorithm: &str,
+        key_id: Option<&str>,
+    ) -> anyhow::Result<T> {
+        self.map_err(|err| {
+            let context = match key_id {
+                Some(val) => format!(
+                    "Failed to process {} (alg: {}, kid: {})",
+                    kind, algorithm, val
+                ),
+                None => format!("Failed to process {} (alg: {})", kind, algorithm),
+            };
+            anyhow::Error::new(err).context(context)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use anyhow::Result;
+
+    use crate::jwk::Jwk;
+    use crate::jws::{JwsSigner, JwsVerifier, ES256, HS256, PS256, RS256};
+    use crate::{JoseErrorContext, JwkParameterErrorKind, Value};
+
+    #[test]
+    fn test_with_jose_context() -> Result<()> {
+        let signer = HS256.signer_from_bytes(b"0123456789ABCDEF0123456789ABCDEF")?;
+        let signature = signer.sign(b"abcde12345")?;
+
+        let verifier = HS256.verifier_from_bytes(b"FEDCBA9876543210FEDCBA9876543210")?;
+        let err = verifier
+            .verify(b"abcde12345", &signature)
+            .with_jose_context("JWS", verifier.algorithm().name(), Some("key-1"))
+            .unwrap_err();
+
+        assert_eq!(
+            format!("{}", err),
+            "Failed to process JWS (alg: HS256, kid: key-1)"
+        );
+        assert!(format!("{:#}", err).contains("HS256"));
+        assert!(format!("{:#}", err).contains("Invalid signature"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_parameter_error() -> Result<()> {
+        let mut jwk = Jwk::from_bytes(&load_file("jwk/RSA_public.jwk")?)?;
+        jwk.set_parameter("e", None)?;
+        let err = RS256.verifier_from_jwk(&jwk).unwrap_err();
+        let param_err = err.jwk_parameter_error().unwrap();
+        assert_eq!(param_err.parameter(), "e");
+        assert_eq!(param_err.kind(), JwkParameterErrorKind::Missing);
+        assert_eq!(format!("{}", param_err), "A parameter e is required.");
+
+        let mut jwk = Jwk::from_bytes(&load_file("jwk/RSA_private.jwk")?)?;
+        jwk.set_parameter("e", None)?;
+        let err = PS256.signer_from_jwk(&jwk).unwrap_err();
+        let param_err = err.jwk_parameter_error().unwrap();
+        assert_eq!(param_err.parameter(), "e");
+        assert_eq!(param_err.kind(), JwkParameterErrorKind::Missing);
+
+        jwk.set_parameter("e", Some(Value::Number(65537.into())))?;
+        let err = PS256.signer_from_jwk(&jwk).unwrap_err();
+        let param_err = err.jwk_parameter_error().unwrap();
+        assert_eq!(param_err.kind(), JwkParameterErrorKind::WrongType);
+
+        let mut jwk = Jwk::from_bytes(&load_file("jwk/EC_P-256_public.jwk")?)?;
+        jwk.set_parameter("y", None)?;
+        let err = ES256.verifier_from_jwk(&jwk).unwrap_err();
+        let param_err = err.jwk_parameter_error().unwrap();
+        assert_eq!(param_err.parameter(), "y");
+        assert_eq!(param_err.kind(), JwkParameterErrorKind::Missing);
+
+        let err = HS256.verifier_from_bytes(b"").unwrap_err();
+        assert!(err.jwk_parameter_error().is_none());
+
+        Ok(())
+    }
+
+    fn load_file(path: &str) -> Result<Vec<u8>> {
+        let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        pb.push("data");
+        pb.push(path);
+
+        let data = fs::read(&pb)?;
+        Ok(data)
+    }
 }
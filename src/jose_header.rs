@@ -1,4 +1,5 @@
-use crate::Value;
+use crate::jwt;
+use crate::{JoseError, Value};
 
 pub trait JoseHeader: Send + Sync {
     // Return claim count.
@@ -12,6 +13,39 @@ pub trait JoseHeader: Send + Sync {
     fn claim(&self, key: &str) -> Option<&Value>;
 
     fn box_clone(&self) -> Box<dyn JoseHeader>;
+
+    /// Return the value for algorithm header claim (alg).
+    fn algorithm(&self) -> Option<&str> {
+        claim_as_str(self, "alg")
+    }
+
+    /// Return the value for key ID header claim (kid).
+    fn key_id(&self) -> Option<&str> {
+        claim_as_str(self, "kid")
+    }
+
+    /// Return the value for token type header claim (typ).
+    fn token_type(&self) -> Option<&str> {
+        claim_as_str(self, "typ")
+    }
+
+    /// Return the value for content type header claim (cty).
+    fn content_type(&self) -> Option<&str> {
+        claim_as_str(self, "cty")
+    }
+
+    /// Return the value for content encryption header claim (enc).
+    /// This claim is only used in JWE.
+    fn content_encryption(&self) -> Option<&str> {
+        claim_as_str(self, "enc")
+    }
+}
+
+fn claim_as_str<'a, T: JoseHeader + ?Sized>(header: &'a T, key: &str) -> Option<&'a str> {
+    match header.claim(key) {
+        Some(Value::String(val)) => Some(val),
+        _ => None,
+    }
 }
 
 impl Clone for Box<dyn JoseHeader> {
@@ -19,3 +53,60 @@ impl Clone for Box<dyn JoseHeader> {
         self.box_clone()
     }
 }
+
+/// Return the Jose header decoded from a JWS or JWE compact serialization.
+///
+/// # Arguments
+///
+/// * `input` - a JWS or JWE compact serialization.
+pub fn decode_header(input: impl AsRef<[u8]>) -> Result<Box<dyn JoseHeader>, JoseError> {
+    jwt::decode_header(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use anyhow::Result;
+
+    use crate::decode_header;
+
+    #[test]
+    fn test_decode_jws_header() -> Result<()> {
+        let data = load_file("jwt/RS256.jwt")?;
+        let header = decode_header(&data)?;
+        assert_eq!(header.algorithm(), Some("RS256"));
+        assert_eq!(header.key_id(), None);
+        assert_eq!(header.content_encryption(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_jwe_header() -> Result<()> {
+        let data = load_file("jwt/A128KW_A256GCM.jwt")?;
+        let header = decode_header(&data)?;
+        assert_eq!(header.algorithm(), Some("A128KW"));
+        assert_eq!(header.content_encryption(), Some("A256GCM"));
+        assert_eq!(header.token_type(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_invalid_header() -> Result<()> {
+        assert!(decode_header("a.b").is_err());
+
+        Ok(())
+    }
+
+    fn load_file(path: &str) -> Result<Vec<u8>> {
+        let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        pb.push("data");
+        pb.push(path);
+
+        let data = fs::read(&pb)?;
+        Ok(data)
+    }
+}
@@ -19,7 +19,7 @@ pub use crate::jwe::jwe_algorithm::JweDecrypter;
 pub use crate::jwe::jwe_algorithm::JweEncrypter;
 pub use crate::jwe::jwe_compression::JweCompression;
 pub use crate::jwe::jwe_content_encryption::JweContentEncryption;
-pub use crate::jwe::jwe_context::JweContext;
+pub use crate::jwe::jwe_context::{JweContext, PayloadWithAad};
 pub use crate::jwe::jwe_header::JweHeader;
 pub use crate::jwe::jwe_header_set::JweHeaderSet;
 
@@ -194,6 +194,20 @@ pub fn deserialize_compact(
     DEFAULT_CONTEXT.deserialize_compact(input, decrypter)
 }
 
+/// Deserialize the input that is formatted by compact serialization
+/// and decrypt it by the first decrypter that succeeds.
+///
+/// # Arguments
+///
+/// * `input` - The input data.
+/// * `decrypters` - The candidate JWE decrypters.
+pub fn decrypt_with_keys(
+    input: &str,
+    decrypters: &[&dyn JweDecrypter],
+) -> Result<(Vec<u8>, JweHeader), JoseError> {
+    DEFAULT_CONTEXT.decrypt_with_keys(input, decrypters)
+}
+
 /// Deserialize the input that is formatted by compact serialization.
 ///
 /// # Arguments
@@ -212,6 +226,9 @@ where
 
 /// Deserialize the input that is formatted by flattened json serialization.
 ///
+/// The returned header is merged from the protected header, the shared unprotected
+/// header and the unprotected header of the recipient that was decrypted.
+///
 /// # Arguments
 ///
 /// * `input` - The input data.
@@ -226,6 +243,9 @@ pub fn deserialize_json<'a>(
 
 /// Deserialize the input that is formatted by flattened json serialization.
 ///
+/// The returned header is merged from the protected header, the shared unprotected
+/// header and the unprotected header of the recipient that was decrypted.
+///
 /// # Arguments
 ///
 /// * `input` - The input data.
@@ -240,20 +260,94 @@ where
     DEFAULT_CONTEXT.deserialize_json_with_selector(input, selector)
 }
 
+/// Deserialize the input that is formatted by flattened json serialization
+/// and return the JWE additional authenticated data (aad) too.
+///
+/// # Arguments
+///
+/// * `input` - The input data.
+/// * `decrypter` - The JWE decrypter.
+pub fn deserialize_json_with_aad(
+    input: &str,
+    decrypter: &dyn JweDecrypter,
+) -> Result<PayloadWithAad, JoseError> {
+    DEFAULT_CONTEXT.deserialize_json_with_aad(input, decrypter)
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
     use std::path::PathBuf;
 
     use anyhow::Result;
+    use serde_json::json;
 
     use crate::jwe::{
-        self, Dir, JweAlgorithm, JweHeader, JweHeaderSet, ECDH_ES_A128KW, PBES2_HS256_A128KW,
-        RSA_OAEP,
+        self, Dir, JweAlgorithm, JweContext, JweHeader, JweHeaderSet, A128KW, A192KW,
+        ECDH_ES_A128KW, PBES2_HS256_A128KW, RSA_OAEP,
     };
     use crate::jwk::Jwk;
     use crate::util;
-    use crate::Value;
+    use crate::{Map, Value};
+
+    #[test]
+    fn test_jwe_compact_serialization_with_deflate() -> Result<()> {
+        let mut src_header = JweHeader::new();
+        src_header.set_content_encryption("A128GCM");
+        src_header.set_compression("DEF");
+        let src_payload = "{\"test\":\"payload\"}".repeat(1000);
+
+        let key = util::random_bytes(16);
+        let encrypter = Dir.encrypter_from_bytes(&key)?;
+        let jwe = jwe::serialize_compact(src_payload.as_bytes(), &src_header, &encrypter)?;
+        assert!(jwe.len() < src_payload.len());
+
+        let decrypter = Dir.decrypter_from_bytes(&key)?;
+        let (dst_payload, dst_header) = jwe::deserialize_compact(&jwe, &decrypter)?;
+        assert_eq!(dst_header.compression(), Some("DEF"));
+        assert_eq!(src_payload.as_bytes(), dst_payload.as_slice());
+
+        let mut context = JweContext::new();
+        context.set_max_decompressed_len(Some(src_payload.len()));
+        context.deserialize_compact(&jwe, &decrypter)?;
+
+        context.set_max_decompressed_len(Some(src_payload.len() - 1));
+        assert!(context.deserialize_compact(&jwe, &decrypter).is_err());
+
+        context.set_max_decompressed_len(Some(usize::MAX));
+        context.deserialize_compact(&jwe, &decrypter)?;
+
+        context.set_max_decompressed_len(None);
+        context.deserialize_compact(&jwe, &decrypter)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_max_len() -> Result<()> {
+        let mut header = JweHeader::new();
+        header.set_content_encryption("A128GCM");
+        let payload = vec![b'a'; 1000];
+
+        let key = util::random_bytes(16);
+        let encrypter = Dir.encrypter_from_bytes(&key)?;
+        let decrypter = Dir.decrypter_from_bytes(&key)?;
+        let jwe = jwe::serialize_compact(&payload, &header, &encrypter)?;
+
+        let mut context = JweContext::new();
+        context.set_max_input_len(Some(jwe.len()));
+        context.deserialize_compact(&jwe, &decrypter)?;
+        context.set_max_input_len(Some(jwe.len() - 1));
+        assert!(context.deserialize_compact(&jwe, &decrypter).is_err());
+
+        context.set_max_input_len(None);
+        context.set_max_payload_len(Some(payload.len()));
+        context.deserialize_compact(&jwe, &decrypter)?;
+        context.set_max_payload_len(Some(payload.len() - 1));
+        assert!(context.deserialize_compact(&jwe, &decrypter).is_err());
+
+        Ok(())
+    }
 
     #[test]
     fn test_jwe_compact_serialization() -> Result<()> {
@@ -262,12 +356,15 @@ mod tests {
             "A192CBC-HS384",
             "A256CBC-HS512",
             "A128GCM",
+            "A192GCM",
             "A256GCM",
-            "A256GCM",
+            "C20P",
+            "XC20P",
         ] {
             let mut src_header = JweHeader::new();
             src_header.set_content_encryption(enc);
             src_header.set_token_type("JWT");
+            src_header.set_content_type("example");
             let src_payload = b"test payload!";
 
             //println!("{}", enc);
@@ -280,6 +377,7 @@ mod tests {
                 "A128GCM" => util::random_bytes(16),
                 "A192GCM" => util::random_bytes(24),
                 "A256GCM" => util::random_bytes(32),
+                "C20P" | "XC20P" => util::random_bytes(32),
                 _ => unreachable!(),
             };
             let encrypter = alg.encrypter_from_bytes(&key)?;
@@ -290,6 +388,7 @@ mod tests {
 
             src_header.set_claim("alg", Some(Value::String(alg.name().to_string())))?;
             assert_eq!(src_header, dst_header);
+            assert_eq!(dst_header.content_type(), Some("example"));
             assert_eq!(src_payload.to_vec(), dst_payload);
         }
 
@@ -334,6 +433,90 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_jwe_json_serialization_with_aad() -> Result<()> {
+        let key = util::random_bytes(16);
+        let encrypter = Dir.encrypter_from_bytes(&key)?;
+        let decrypter = Dir.decrypter_from_bytes(&key)?;
+
+        let src_payload = b"test payload!";
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("A128GCM", true);
+
+        let json = jwe::serialize_flattened_json(
+            src_payload,
+            Some(&src_header),
+            None,
+            Some(b"metadata"),
+            &encrypter,
+        )?;
+        let (dst_payload, _, aad) = jwe::deserialize_json_with_aad(&json, &decrypter)?;
+        assert_eq!(dst_payload, src_payload);
+        assert_eq!(aad, Some(b"metadata".to_vec()));
+
+        let mut map: Map<String, Value> = serde_json::from_str(&json)?;
+        map.insert(
+            "aad".to_string(),
+            Value::String(util::encode_base64url(b"metadatb")),
+        );
+        let tampered = serde_json::to_string(&map)?;
+        assert!(jwe::deserialize_json(&tampered, &decrypter).is_err());
+        map.remove("aad");
+        let tampered = serde_json::to_string(&map)?;
+        assert!(jwe::deserialize_json(&tampered, &decrypter).is_err());
+
+        // Without a JWE AAD, the additional authenticated data is the protected header
+        // alone, so the same fields form a valid compact serialization.
+        let json =
+            jwe::serialize_flattened_json(src_payload, Some(&src_header), None, None, &encrypter)?;
+        let (_, _, aad) = jwe::deserialize_json_with_aad(&json, &decrypter)?;
+        assert_eq!(aad, None);
+
+        let map: Map<String, Value> = serde_json::from_str(&json)?;
+        let field = |name: &str| match map.get(name) {
+            Some(Value::String(val)) => val.clone(),
+            _ => String::new(),
+        };
+        let compact = format!(
+            "{}..{}.{}.{}",
+            field("protected"),
+            field("iv"),
+            field("ciphertext"),
+            field("tag")
+        );
+        let (dst_payload, _) = jwe::deserialize_compact(&compact, &decrypter)?;
+        assert_eq!(dst_payload, src_payload);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_json_serialization_aad_interop() -> Result<()> {
+        // RFC 7516 Appendix A.5: the additional authenticated data has no trailing period.
+        let jwk = Jwk::from_bytes(br#"{"kty":"oct","k":"GawgguFyGrWKav7AX4VKUg"}"#)?;
+        let decrypter = A128KW.decrypter_from_jwk(&jwk)?;
+        let json = json!({
+            "protected": "eyJlbmMiOiJBMTI4Q0JDLUhTMjU2In0",
+            "unprotected": {"jku": "https://server.example.com/keys.jwks"},
+            "header": {"alg": "A128KW", "kid": "7"},
+            "encrypted_key": "6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ",
+            "iv": "AxY8DCtDaGlsbGljb3RoZQ",
+            "ciphertext": "KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY",
+            "tag": "Mz-VPPyU4RlcuYv1IwIvzw"
+        });
+        let (payload, _) = jwe::deserialize_json(&json.to_string(), &decrypter)?;
+        assert_eq!(payload, b"Live long and prosper.");
+
+        // Earlier versions appended a period even without a JWE AAD,
+        // so their JSON serialized JWEs without an aad no longer decrypt.
+        let jwk = Jwk::from_bytes(load_file("jwk/oct_128bit_private.jwk")?)?;
+        let decrypter = A128KW.decrypter_from_jwk(&jwk)?;
+        let legacy = String::from_utf8(load_file("jwe/A128KW_A128GCM_legacy_aad.json")?)?;
+        assert!(jwe::deserialize_json(&legacy, &decrypter).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_jwe_general_json_serialization() -> Result<()> {
         let public_key_1 = load_file("pem/RSA_2048bit_public.pem")?;
@@ -347,6 +530,7 @@ mod tests {
         let mut src_header = JweHeaderSet::new();
         src_header.set_content_encryption("A128CBC-HS256", true);
         src_header.set_token_type("JWT-1", false);
+        src_header.set_content_type("JWT", true);
 
         let mut src_rheader_1 = JweHeader::new();
         src_rheader_1.set_key_id("xxx-1");
@@ -354,6 +538,7 @@ mod tests {
 
         let mut src_rheader_2 = JweHeader::new();
         src_rheader_2.set_key_id("xxx-2");
+        src_rheader_2.set_claim("x-recipient", Some(json!("2")))?;
         let encrypter_2 = ECDH_ES_A128KW.encrypter_from_der(&public_key_2)?;
 
         let mut src_rheader_3 = JweHeader::new();
@@ -377,12 +562,188 @@ mod tests {
 
         assert_eq!(dst_header.algorithm(), Some("ECDH-ES+A128KW"));
         assert_eq!(src_header.token_type(), dst_header.token_type());
+        assert_eq!(src_header.content_type(), dst_header.content_type());
         assert_eq!(src_rheader_2.key_id(), dst_header.key_id());
+        assert_eq!(dst_header.claim("x-recipient"), Some(&json!("2")));
         assert_eq!(src_payload.to_vec(), dst_payload);
 
         Ok(())
     }
 
+    #[test]
+    fn test_jwe_compact_segment_count() -> Result<()> {
+        let key = util::random_bytes(16);
+        let encrypter = Dir.encrypter_from_bytes(&key)?;
+        let decrypter = Dir.decrypter_from_bytes(&key)?;
+
+        let mut header = JweHeader::new();
+        header.set_content_encryption("A128GCM");
+        let jwt = jwe::serialize_compact(b"test payload!", &header, &encrypter)?;
+        jwe::deserialize_compact(&jwt, &decrypter)?;
+
+        let parts: Vec<&str> = jwt.split('.').collect();
+        for input in &[parts[..4].join("."), format!("{}.{}", jwt, parts[4])] {
+            let err = jwe::deserialize_compact(input, &decrypter).unwrap_err();
+            assert!(format!("{}", err).contains("five parts"), "{}", err);
+        }
+
+        let input = format!(".{}", parts[1..].join("."));
+        let err = jwe::deserialize_compact(&input, &decrypter).unwrap_err();
+        assert!(format!("{}", err).contains("header part"), "{}", err);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_general_json_serialization_to_multiple_rsa_recipients() -> Result<()> {
+        let key_pair_1 = RSA_OAEP.generate_key_pair(2048)?;
+        let key_pair_2 = RSA_OAEP.generate_key_pair(2048)?;
+
+        let src_payload = b"broadcast payload!";
+
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("A256GCM", true);
+
+        let mut src_rheader_1 = JweHeader::new();
+        src_rheader_1.set_key_id("recipient-1");
+        let encrypter_1 = RSA_OAEP.encrypter_from_der(key_pair_1.to_der_public_key())?;
+
+        let mut src_rheader_2 = JweHeader::new();
+        src_rheader_2.set_key_id("recipient-2");
+        let encrypter_2 = RSA_OAEP.encrypter_from_der(key_pair_2.to_der_public_key())?;
+
+        let json = jwe::serialize_general_json(
+            src_payload,
+            Some(&src_header),
+            &[
+                (Some(&src_rheader_1), &encrypter_1),
+                (Some(&src_rheader_2), &encrypter_2),
+            ],
+            None,
+        )?;
+
+        let map: Map<String, Value> = serde_json::from_str(&json)?;
+        match map.get("recipients") {
+            Some(Value::Array(vals)) => assert_eq!(vals.len(), 2),
+            _ => unreachable!(),
+        }
+
+        for (key_pair, key_id) in &[(&key_pair_1, "recipient-1"), (&key_pair_2, "recipient-2")] {
+            let mut decrypter = RSA_OAEP.decrypter_from_der(key_pair.to_der_private_key())?;
+            decrypter.set_key_id(*key_id);
+            let (dst_payload, dst_header) = jwe::deserialize_json(&json, &decrypter)?;
+            assert_eq!(dst_header.key_id(), Some(*key_id));
+            assert_eq!(src_payload.to_vec(), dst_payload);
+        }
+
+        let key_pair_3 = RSA_OAEP.generate_key_pair(2048)?;
+        let decrypter = RSA_OAEP.decrypter_from_der(key_pair_3.to_der_private_key())?;
+        assert!(jwe::deserialize_json(&json, &decrypter).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_a192gcm_key_length() -> Result<()> {
+        let src_payload = b"test payload!";
+        let mut header = JweHeader::new();
+        header.set_content_encryption("A192GCM");
+
+        let key = util::random_bytes(16);
+        let encrypter = Dir.encrypter_from_bytes(&key)?;
+        let err = jwe::serialize_compact(src_payload, &header, &encrypter).unwrap_err();
+        assert!(format!("{}", err).contains("24"), "{}", err);
+
+        let key = util::random_bytes(24);
+        let encrypter = Dir.encrypter_from_bytes(&key)?;
+        let jwt = jwe::serialize_compact(src_payload, &header, &encrypter)?;
+        let decrypter = Dir.decrypter_from_bytes(&key[..16])?;
+        assert!(jwe::deserialize_compact(&jwt, &decrypter).is_err());
+
+        // A key wrapped for A128GCM must not be used as a key of A192GCM.
+        let kek = util::random_bytes(24);
+        let encrypter = A192KW.encrypter_from_bytes(&kek)?;
+        let decrypter = A192KW.decrypter_from_bytes(&kek)?;
+        let jwt = jwe::serialize_compact(src_payload, &header, &encrypter)?;
+        let (dst_payload, dst_header) = jwe::deserialize_compact(&jwt, &decrypter)?;
+        assert_eq!(src_payload.to_vec(), dst_payload);
+        assert_eq!(dst_header.content_encryption(), Some("A192GCM"));
+
+        header.set_content_encryption("A128GCM");
+        let jwt = jwe::serialize_compact(src_payload, &header, &encrypter)?;
+        let mut parts: Vec<String> = jwt.split('.').map(|val| val.to_string()).collect();
+        let mut header_map =
+            util::parse_json_object(&base64::decode_config(&parts[0], base64::URL_SAFE_NO_PAD)?)?;
+        header_map.insert("enc".to_string(), Value::String("A192GCM".to_string()));
+        parts[0] = base64::encode_config(serde_json::to_vec(&header_map)?, base64::URL_SAFE_NO_PAD);
+        let err = jwe::deserialize_compact(&parts.join("."), &decrypter).unwrap_err();
+        assert!(format!("{}", err).contains("key size"), "{}", err);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_aes_gcm_iv_length() -> Result<()> {
+        let key = util::random_bytes(32);
+        let encrypter = Dir.encrypter_from_bytes(&key)?;
+        let decrypter = Dir.decrypter_from_bytes(&key)?;
+
+        let mut header = JweHeader::new();
+        header.set_content_encryption("A256GCM");
+        let jwt = jwe::serialize_compact(b"test payload!", &header, &encrypter)?;
+        let parts: Vec<&str> = jwt.split('.').collect();
+        let iv = base64::decode_config(parts[2], base64::URL_SAFE_NO_PAD)?;
+        assert_eq!(iv.len(), 12);
+
+        let jwt2 = jwe::serialize_compact(b"test payload!", &header, &encrypter)?;
+        assert_ne!(parts[2], jwt2.split('.').nth(2).unwrap());
+
+        let short_iv = base64::encode_config(&iv[..8], base64::URL_SAFE_NO_PAD);
+        let input = format!(
+            "{}.{}.{}.{}.{}",
+            parts[0], parts[1], short_iv, parts[3], parts[4]
+        );
+        let err = jwe::deserialize_compact(&input, &decrypter).unwrap_err();
+        assert!(
+            format!("{}", err).contains("initialization vector"),
+            "{}",
+            err
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_decrypt_with_keys() -> Result<()> {
+        let key_pair_1 = RSA_OAEP.generate_key_pair(2048)?;
+        let key_pair_2 = RSA_OAEP.generate_key_pair(2048)?;
+        let decrypter_1 = RSA_OAEP.decrypter_from_der(key_pair_1.to_der_private_key())?;
+        let decrypter_2 = RSA_OAEP.decrypter_from_der(key_pair_2.to_der_private_key())?;
+
+        let src_payload = b"test payload!";
+        let mut src_header = JweHeader::new();
+        src_header.set_content_encryption("A128GCM");
+        let encrypter = RSA_OAEP.encrypter_from_der(key_pair_2.to_der_public_key())?;
+        let jwt = jwe::serialize_compact(src_payload, &src_header, &encrypter)?;
+
+        let (dst_payload, dst_header) =
+            jwe::decrypt_with_keys(&jwt, &[&decrypter_1, &decrypter_2])?;
+        assert_eq!(src_payload.to_vec(), dst_payload);
+        assert_eq!(dst_header.algorithm(), Some("RSA-OAEP"));
+
+        let key = util::random_bytes(16);
+        let dir_decrypter = Dir.decrypter_from_bytes(&key)?;
+        let err = jwe::decrypt_with_keys(&jwt, &[&dir_decrypter, &decrypter_1]).unwrap_err();
+        let message = format!("{}", err);
+        assert!(message.contains("None of the 2 decrypters"), "{}", message);
+        assert!(message.contains("0: "), "{}", message);
+        assert!(message.contains("1: failed to decrypt"), "{}", message);
+
+        assert!(jwe::decrypt_with_keys(&jwt, &[]).is_err());
+
+        Ok(())
+    }
+
     fn load_file(path: &str) -> Result<Vec<u8>> {
         let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         pb.push("data");
@@ -1,5 +1,5 @@
 use std::borrow::Cow;
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 use std::ops::Deref;
 
 use anyhow::bail;
@@ -195,13 +195,23 @@ impl Deref for AesgcmkwJweAlgorithm {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct AesgcmkwJweEncrypter {
     algorithm: AesgcmkwJweAlgorithm,
     private_key: Vec<u8>,
     key_id: Option<String>,
 }
 
+impl Debug for AesgcmkwJweEncrypter {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("AesgcmkwJweEncrypter")
+            .field("algorithm", &self.algorithm)
+            .field("private_key", &format_args!("[REDACTED]"))
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
 impl AesgcmkwJweEncrypter {
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
@@ -240,7 +250,7 @@ impl JweEncrypter for AesgcmkwJweEncrypter {
         out_header: &mut JweHeader,
     ) -> Result<Option<Vec<u8>>, JoseError> {
         (|| -> anyhow::Result<Option<Vec<u8>>> {
-            let iv = util::random_bytes(32);
+            let iv = util::try_random_bytes(12)?;
 
             let cipher = self.algorithm.cipher();
             let mut tag = [0; 16];
@@ -274,13 +284,23 @@ impl Deref for AesgcmkwJweEncrypter {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct AesgcmkwJweDecrypter {
     algorithm: AesgcmkwJweAlgorithm,
     private_key: Vec<u8>,
     key_id: Option<String>,
 }
 
+impl Debug for AesgcmkwJweDecrypter {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("AesgcmkwJweDecrypter")
+            .field("algorithm", &self.algorithm)
+            .field("private_key", &format_args!("[REDACTED]"))
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
 impl AesgcmkwJweDecrypter {
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
@@ -327,6 +347,17 @@ impl JweDecrypter for AesgcmkwJweDecrypter {
                 None => bail!("The tag header claim is required."),
             };
 
+            // Earlier versions wrapped keys with a 256-bit IV, so longer IVs are still accepted.
+            if iv.len() < 12 {
+                bail!(
+                    "The iv header claim must be at least 96 bits: {} bytes",
+                    iv.len()
+                );
+            }
+            if tag.len() != 16 {
+                bail!("The tag header claim must be 128 bits: {} bytes", tag.len());
+            }
+
             let cipher = self.algorithm.cipher();
             let key = symm::decrypt_aead(
                 cipher,
@@ -359,6 +390,7 @@ impl Deref for AesgcmkwJweDecrypter {
 mod tests {
     use anyhow::Result;
     use base64;
+    use openssl::symm;
     use serde_json::json;
 
     use super::AesgcmkwJweAlgorithm;
@@ -366,6 +398,7 @@ mod tests {
     use crate::jwe::JweHeader;
     use crate::jwk::Jwk;
     use crate::util;
+    use crate::Value;
 
     #[test]
     fn encrypt_and_decrypt_aes_gcm() -> Result<()> {
@@ -402,4 +435,93 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn reject_tampered_aes_gcm_wrapped_key() -> Result<()> {
+        let enc = AescbcHmacJweEncryption::A128cbcHs256;
+        let alg = AesgcmkwJweAlgorithm::A256gcmkw;
+        let key = util::random_bytes(alg.key_len());
+
+        let header = JweHeader::new();
+        let encrypter = alg.encrypter_from_bytes(&key)?;
+        let src_key = util::random_bytes(enc.key_len());
+        let mut out_header = header.clone();
+        let encrypted_key = encrypter.encrypt(&src_key, &header, &mut out_header)?;
+        let encrypted_key = encrypted_key.as_deref();
+
+        let iv = match out_header.claim("iv") {
+            Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
+            _ => unreachable!(),
+        };
+        assert_eq!(iv.len(), 12);
+        let tag = match out_header.claim("tag") {
+            Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
+            _ => unreachable!(),
+        };
+        assert_eq!(tag.len(), 16);
+
+        let decrypter = alg.decrypter_from_bytes(&key)?;
+        decrypter.decrypt(encrypted_key, &enc, &out_header)?;
+
+        let mut tampered_tag = tag.clone();
+        tampered_tag[0] ^= 0x01;
+        let mut tampered_header = out_header.clone();
+        tampered_header.set_claim("tag", Some(json!(encode(&tampered_tag))))?;
+        assert!(decrypter
+            .decrypt(encrypted_key, &enc, &tampered_header)
+            .is_err());
+
+        let mut short_header = out_header.clone();
+        short_header.set_claim("tag", Some(json!(encode(&tag[..12]))))?;
+        assert!(decrypter
+            .decrypt(encrypted_key, &enc, &short_header)
+            .is_err());
+
+        let mut short_header = out_header.clone();
+        short_header.set_claim("iv", Some(json!(encode(&iv[..8]))))?;
+        assert!(decrypter
+            .decrypt(encrypted_key, &enc, &short_header)
+            .is_err());
+
+        let mut missing_header = out_header.clone();
+        missing_header.set_claim("iv", None)?;
+        assert!(decrypter
+            .decrypt(encrypted_key, &enc, &missing_header)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn decrypt_aes_gcm_wrapped_key_with_long_iv() -> Result<()> {
+        let enc = AescbcHmacJweEncryption::A128cbcHs256;
+        let alg = AesgcmkwJweAlgorithm::A128gcmkw;
+        let key = util::random_bytes(alg.key_len());
+        let src_key = util::random_bytes(enc.key_len());
+
+        // A wrapped key with the 256-bit IV that earlier versions produced.
+        let iv = util::random_bytes(32);
+        let mut tag = [0; 16];
+        let encrypted_key = symm::encrypt_aead(
+            symm::Cipher::aes_128_gcm(),
+            &key,
+            Some(&iv),
+            b"",
+            &src_key,
+            &mut tag,
+        )?;
+        let mut header = JweHeader::new();
+        header.set_claim("iv", Some(json!(encode(&iv))))?;
+        header.set_claim("tag", Some(json!(encode(&tag))))?;
+
+        let decrypter = alg.decrypter_from_bytes(&key)?;
+        let dst_key = decrypter.decrypt(Some(&encrypted_key), &enc, &header)?;
+        assert_eq!(&src_key as &[u8], &dst_key as &[u8]);
+
+        Ok(())
+    }
+
+    fn encode(input: &[u8]) -> String {
+        base64::encode_config(input, base64::URL_SAFE_NO_PAD)
+    }
 }
@@ -1,5 +1,5 @@
 use std::borrow::Cow;
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 use std::ops::Deref;
 
 use anyhow::bail;
@@ -186,13 +186,23 @@ impl Deref for AeskwJweAlgorithm {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct AeskwJweEncrypter {
     algorithm: AeskwJweAlgorithm,
     private_key: Vec<u8>,
     key_id: Option<String>,
 }
 
+impl Debug for AeskwJweEncrypter {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("AeskwJweEncrypter")
+            .field("algorithm", &self.algorithm)
+            .field("private_key", &format_args!("[REDACTED]"))
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
 impl AeskwJweEncrypter {
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
@@ -264,13 +274,23 @@ impl Deref for AeskwJweEncrypter {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct AeskwJweDecrypter {
     algorithm: AeskwJweAlgorithm,
     private_key: Vec<u8>,
     key_id: Option<String>,
 }
 
+impl Debug for AeskwJweDecrypter {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("AeskwJweDecrypter")
+            .field("algorithm", &self.algorithm)
+            .field("private_key", &format_args!("[REDACTED]"))
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
 impl AeskwJweDecrypter {
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
@@ -310,6 +330,13 @@ impl JweDecrypter for AeskwJweDecrypter {
                 Err(_) => bail!("Failed to set decrypt key."),
             };
 
+            if encrypted_key.len() < 24 || encrypted_key.len() % 8 != 0 {
+                bail!(
+                    "The wrapped key length must be a multiple of 8 and 24 or more: {}",
+                    encrypted_key.len()
+                );
+            }
+
             let mut key = vec![0; encrypted_key.len() - 8];
             match aes::unwrap_key(&aes, None, &mut key, encrypted_key) {
                 Ok(val) => {
@@ -317,7 +344,7 @@ impl JweDecrypter for AeskwJweDecrypter {
                         key.truncate(val);
                     }
                 }
-                Err(_) => bail!("Failed to unwrap key."),
+                Err(_) => bail!("The integrity check value of the wrapped key is mismatched."),
             };
 
             Ok(Cow::Owned(key))
@@ -385,4 +412,75 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn wrap_and_unwrap_rfc3394_test_vectors() -> Result<()> {
+        let enc = AescbcHmacJweEncryption::A128cbcHs256;
+        let kek =
+            util::decode_hex("000102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F");
+        let key_data =
+            util::decode_hex("00112233445566778899AABBCCDDEEFF000102030405060708090A0B0C0D0E0F");
+
+        // RFC 3394 4.1 - 4.6
+        for &(alg, key_data_len, expected) in &[
+            (
+                AeskwJweAlgorithm::A128kw,
+                16,
+                "1FA68B0A8112B447AEF34BD8FB5A7B829D3E862371D2CFE5",
+            ),
+            (
+                AeskwJweAlgorithm::A192kw,
+                16,
+                "96778B25AE6CA435F92B5B97C050AED2468AB8A17AD84E5D",
+            ),
+            (
+                AeskwJweAlgorithm::A256kw,
+                16,
+                "64E8C3F9CE0F5BA263E9777905818A2A93C8191E7D6E8AE7",
+            ),
+            (
+                AeskwJweAlgorithm::A192kw,
+                24,
+                "031D33264E15D33268F24EC260743EDCE1C6C7DDEE725A936BA814915C6762D2",
+            ),
+            (
+                AeskwJweAlgorithm::A256kw,
+                24,
+                "A8F9BC1612C68B3FF6E6F4FBE30E71E4769C8B80A32CB8958CD5D17D6B254DA1",
+            ),
+            (
+                AeskwJweAlgorithm::A256kw,
+                32,
+                "28C9F404C4B810F4CBCCB35CFB87F8263F5786E2D80ED326CBC7F0E71A99F43BFB988B9B7A02DD21",
+            ),
+        ] {
+            let kek = &kek[0..alg.key_len()];
+            let key_data = &key_data[0..key_data_len];
+            let expected = util::decode_hex(expected);
+
+            let header = JweHeader::new();
+            let encrypter = alg.encrypter_from_bytes(kek)?;
+            let encrypted_key = encrypter.encrypt(key_data, &header, &mut header.clone())?;
+            assert_eq!(encrypted_key, Some(expected.clone()));
+
+            let decrypter = alg.decrypter_from_bytes(kek)?;
+            let decrypted_key = decrypter.decrypt(Some(&expected), &enc, &header)?;
+            assert_eq!(&decrypted_key as &[u8], key_data);
+
+            let mut tampered = expected.clone();
+            tampered[0] ^= 0x01;
+            assert!(decrypter.decrypt(Some(&tampered), &enc, &header).is_err());
+            assert!(decrypter
+                .decrypt(Some(&expected[..16]), &enc, &header)
+                .is_err());
+            assert!(decrypter
+                .decrypt(Some(&expected[1..]), &enc, &header)
+                .is_err());
+
+            assert!(alg.encrypter_from_bytes(&kek[1..]).is_err());
+            assert!(alg.decrypter_from_bytes(&kek[1..]).is_err());
+        }
+
+        Ok(())
+    }
 }
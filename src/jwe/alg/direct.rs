@@ -1,5 +1,5 @@
 use std::borrow::Cow;
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 use std::ops::Deref;
 
 use anyhow::bail;
@@ -141,13 +141,23 @@ impl Deref for DirectJweAlgorithm {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct DirectJweEncrypter {
     algorithm: DirectJweAlgorithm,
     cencryption_key: Vec<u8>,
     key_id: Option<String>,
 }
 
+impl Debug for DirectJweEncrypter {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("DirectJweEncrypter")
+            .field("algorithm", &self.algorithm)
+            .field("cencryption_key", &format_args!("[REDACTED]"))
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
 impl DirectJweEncrypter {
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
@@ -213,13 +223,23 @@ impl Deref for DirectJweEncrypter {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct DirectJweDecrypter {
     algorithm: DirectJweAlgorithm,
     cencryption_key: Vec<u8>,
     key_id: Option<String>,
 }
 
+impl Debug for DirectJweDecrypter {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("DirectJweDecrypter")
+            .field("algorithm", &self.algorithm)
+            .field("cencryption_key", &format_args!("[REDACTED]"))
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
 impl DirectJweDecrypter {
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
@@ -245,7 +265,7 @@ impl JweDecrypter for DirectJweDecrypter {
     fn decrypt(
         &self,
         encrypted_key: Option<&[u8]>,
-        _cencryption: &dyn JweContentEncryption,
+        cencryption: &dyn JweContentEncryption,
         _header: &JweHeader,
     ) -> Result<Cow<[u8]>, JoseError> {
         (|| -> anyhow::Result<Cow<[u8]>> {
@@ -253,6 +273,15 @@ impl JweDecrypter for DirectJweDecrypter {
                 bail!("The encrypted_key must not exist.");
             }
 
+            let actual_len = self.cencryption_key.len();
+            if cencryption.key_len() != actual_len {
+                bail!(
+                    "The key size is expected to be {}: {}",
+                    cencryption.key_len(),
+                    actual_len
+                );
+            }
+
             Ok(Cow::Borrowed(&self.cencryption_key))
         })()
         .map_err(|err| JoseError::InvalidJweFormat(err))
@@ -278,8 +307,9 @@ mod tests {
 
     use super::DirectJweAlgorithm;
     use crate::jwe::enc::aescbc_hmac::AescbcHmacJweEncryption;
-    use crate::jwe::JweHeader;
+    use crate::jwe::{self, JweHeader, JweHeaderSet};
     use crate::jwk::Jwk;
+    use crate::util;
 
     #[test]
     fn encrypt_and_decrypt_direct() -> Result<()> {
@@ -314,4 +344,57 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn encrypt_and_decrypt_direct_round_trip() -> Result<()> {
+        let payload = b"direct encryption payload";
+
+        for &(enc, key_len) in &[("A128GCM", 16), ("A192GCM", 24), ("A256CBC-HS512", 48)] {
+            let key = util::random_bytes(key_len);
+
+            let mut header = JweHeader::new();
+            header.set_content_encryption(enc);
+
+            let encrypter = DirectJweAlgorithm::Dir.encrypter_from_bytes(&key)?;
+            let decrypter = DirectJweAlgorithm::Dir.decrypter_from_bytes(&key)?;
+
+            let jwt = jwe::serialize_compact(payload, &header, &encrypter)?;
+            assert_eq!(jwt.split('.').nth(1), Some(""));
+            let (dst_payload, _) = jwe::deserialize_compact(&jwt, &decrypter)?;
+            assert_eq!(payload.to_vec(), dst_payload);
+
+            let mut header_set = JweHeaderSet::new();
+            header_set.set_content_encryption(enc, true);
+            let json =
+                jwe::serialize_flattened_json(payload, Some(&header_set), None, None, &encrypter)?;
+            assert!(!json.contains("encrypted_key"));
+            let (dst_payload, _) = jwe::deserialize_json(&json, &decrypter)?;
+            assert_eq!(payload.to_vec(), dst_payload);
+
+            let wrong_len = DirectJweAlgorithm::Dir.decrypter_from_bytes(&key[1..])?;
+            assert!(jwe::deserialize_compact(&jwt, &wrong_len).is_err());
+            let wrong_len = DirectJweAlgorithm::Dir.encrypter_from_bytes(&key[1..])?;
+            assert!(jwe::serialize_compact(payload, &header, &wrong_len).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_encrypted_key_for_direct() -> Result<()> {
+        let key = util::random_bytes(16);
+        let mut header = JweHeader::new();
+        header.set_content_encryption("A128GCM");
+
+        let encrypter = DirectJweAlgorithm::Dir.encrypter_from_bytes(&key)?;
+        let decrypter = DirectJweAlgorithm::Dir.decrypter_from_bytes(&key)?;
+
+        let jwt = jwe::serialize_compact(b"payload", &header, &encrypter)?;
+        let mut parts: Vec<&str> = jwt.split('.').collect();
+        parts[1] = "AAAAAAAAAAAAAAAAAAAAAA";
+        let jwt = parts.join(".");
+        assert!(jwe::deserialize_compact(&jwt, &decrypter).is_err());
+
+        Ok(())
+    }
 }
@@ -13,7 +13,7 @@ use crate::jwk::alg::{
     ec::{EcCurve, EcKeyPair},
     ecx::{EcxCurve, EcxKeyPair},
 };
-use crate::jwk::Jwk;
+use crate::jwk::{Jwk, KeyPair};
 use crate::util;
 use crate::util::der::{DerReader, DerType};
 use crate::util::oid::{
@@ -81,6 +81,18 @@ impl EcdhEsJweAlgorithm {
         Ok(key_pair)
     }
 
+    /// Generate EC key pair for ECDH as a JWK that is usable as a recipient static key.
+    ///
+    /// The JWK has the alg parameter, use "enc" and key_ops ["deriveKey"].
+    /// Publish the public key of it by `Jwk::to_public`.
+    pub fn generate_ec_jwk_key_pair(&self, curve: EcCurve) -> Result<Jwk, JoseError> {
+        let key_pair = self.generate_ec_key_pair(curve)?;
+        let mut jwk = key_pair.to_jwk_key_pair();
+        jwk.set_key_use("enc");
+        jwk.set_key_operations(vec!["deriveKey"]);
+        Ok(jwk)
+    }
+
     /// Create a EC key pair for ECDH from a private key that is a DER encoded PKCS#8 PrivateKeyInfo or ECPrivateKey.
     ///
     /// # Arguments
@@ -244,6 +256,9 @@ impl EcdhEsJweAlgorithm {
                         let curve = match val.as_str() {
                             "X25519" => EcxCurve::X25519,
                             "X448" => EcxCurve::X448,
+                            "Ed25519" | "Ed448" => {
+                                bail!("The EdDSA curve {} cannot be used for key agreement.", val)
+                            }
                             val => bail!("OKP key doesn't support the curve algorithm: {}", val),
                         };
                         let x = match jwk.parameter("x") {
@@ -397,6 +412,9 @@ impl EcdhEsJweAlgorithm {
                         let curve = match val.as_str() {
                             "X25519" => EcxCurve::X25519,
                             "X448" => EcxCurve::X448,
+                            "Ed25519" | "Ed448" => {
+                                bail!("The EdDSA curve {} cannot be used for key agreement.", val)
+                            }
                             val => bail!("OKP key doesn't support the curve algorithm: {}", val),
                         };
                         match jwk.curve() {
@@ -440,6 +458,7 @@ impl EcdhEsJweAlgorithm {
     fn detect_pkcs8(input: &[u8], is_public: bool) -> Option<EcdhEsKeyType> {
         let key_type;
         let mut reader = DerReader::from_reader(input);
+        reader.set_strict(true);
 
         match reader.next() {
             Ok(Some(DerType::Sequence)) => {}
@@ -450,7 +469,7 @@ impl EcdhEsJweAlgorithm {
             if !is_public {
                 // Version
                 match reader.next() {
-                    Ok(Some(DerType::Integer)) => match reader.to_u8() {
+                    Ok(Some(DerType::Integer)) => match reader.to_u32() {
                         Ok(val) => {
                             if val != 0 {
                                 return None;
@@ -1014,10 +1033,11 @@ mod tests {
     use super::{EcdhEsJweAlgorithm, EcdhEsKeyType};
     use crate::jwe::enc::aescbc_hmac::AescbcHmacJweEncryption;
     use crate::jwe::enc::aesgcm::AesgcmJweEncryption;
-    use crate::jwe::JweHeader;
+    use crate::jwe::{self, JweHeader};
     use crate::jwk::alg::{ec::EcCurve, ecx::EcxCurve};
-    use crate::jwk::Jwk;
+    use crate::jwk::{Jwk, KeyPair};
     use crate::util;
+    use crate::Value;
 
     #[test]
     fn encrypt_and_decrypt_ecdh_es_with_pkcs8_der() -> Result<()> {
@@ -1207,6 +1227,94 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn encrypt_and_decrypt_ecdh_es_with_generated_jwk() -> Result<()> {
+        let enc = AesgcmJweEncryption::A256gcm;
+
+        for alg in &[
+            EcdhEsJweAlgorithm::EcdhEs,
+            EcdhEsJweAlgorithm::EcdhEsA128kw,
+            EcdhEsJweAlgorithm::EcdhEsA256kw,
+        ] {
+            for curve in &[EcCurve::P256, EcCurve::P384, EcCurve::P521] {
+                let private_key = alg.generate_ec_jwk_key_pair(*curve)?;
+                assert_eq!(private_key.algorithm(), Some(alg.name()));
+                assert_eq!(private_key.key_use(), Some("enc"));
+                assert_eq!(private_key.key_operations(), Some(vec!["deriveKey"]));
+                assert_eq!(private_key.curve(), Some(curve.name()));
+
+                let public_key = private_key.to_public();
+                assert_eq!(public_key.key_use(), Some("enc"));
+
+                let mut header = JweHeader::new();
+                header.set_content_encryption(enc.name());
+
+                let encrypter = alg.encrypter_from_jwk(&public_key)?;
+                let mut out_header = header.clone();
+                let src_key = match encrypter.compute_content_encryption_key(
+                    &enc,
+                    &header,
+                    &mut out_header,
+                )? {
+                    Some(val) => val,
+                    None => Cow::Owned(util::random_bytes(enc.key_len())),
+                };
+                let encrypted_key = encrypter.encrypt(&src_key, &header, &mut out_header)?;
+                assert!(out_header.claim("epk").is_some());
+
+                out_header.set_algorithm(alg.name());
+                let decrypter = alg.decrypter_from_jwk(&private_key)?;
+                let dst_key = decrypter.decrypt(encrypted_key.as_deref(), &enc, &out_header)?;
+
+                assert_eq!(&src_key, &dst_key);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn encrypt_and_decrypt_ecdh_es_with_okp_jwk() -> Result<()> {
+        let alg = EcdhEsJweAlgorithm::EcdhEs;
+
+        for curve in &[EcxCurve::X25519, EcxCurve::X448] {
+            let private_key = alg.generate_ecx_key_pair(*curve)?.to_jwk_key_pair();
+            let public_key = private_key.to_public_key()?;
+
+            let mut header = JweHeader::new();
+            header.set_content_encryption("A256GCM");
+            let encrypter = alg.encrypter_from_jwk(&public_key)?;
+            let jwe = jwe::serialize_compact(b"payload", &header, &encrypter)?;
+
+            let decrypter = alg.decrypter_from_jwk(&private_key)?;
+            let (payload, header) = jwe::deserialize_compact(&jwe, &decrypter)?;
+            assert_eq!(payload, b"payload");
+            match header.claim("epk") {
+                Some(Value::Object(epk)) => {
+                    assert_eq!(epk.get("kty"), Some(&Value::String("OKP".to_string())));
+                    assert_eq!(
+                        epk.get("crv"),
+                        Some(&Value::String(curve.name().to_string()))
+                    );
+                    assert!(epk.get("d").is_none());
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        for path in &["jwk/OKP_Ed25519_private.jwk", "jwk/OKP_Ed448_private.jwk"] {
+            let private_key = Jwk::from_bytes(&load_file(path)?)?;
+            let err = alg.decrypter_from_jwk(&private_key).unwrap_err();
+            assert!(err.to_string().contains("key agreement"), "{}", err);
+            let err = alg
+                .encrypter_from_jwk(&private_key.to_public_key()?)
+                .unwrap_err();
+            assert!(err.to_string().contains("key agreement"), "{}", err);
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn encrypt_and_decrypt_ecdh_es_with_jwk() -> Result<()> {
         let enc = AescbcHmacJweEncryption::A128cbcHs256;
@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 use std::convert::TryFrom;
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 use std::ops::Deref;
 
 use anyhow::bail;
@@ -193,7 +193,7 @@ impl Deref for Pbes2HmacAeskwJweAlgorithm {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Pbes2HmacAeskwJweEncrypter {
     algorithm: Pbes2HmacAeskwJweAlgorithm,
     private_key: Vec<u8>,
@@ -202,6 +202,18 @@ pub struct Pbes2HmacAeskwJweEncrypter {
     key_id: Option<String>,
 }
 
+impl Debug for Pbes2HmacAeskwJweEncrypter {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("Pbes2HmacAeskwJweEncrypter")
+            .field("algorithm", &self.algorithm)
+            .field("private_key", &format_args!("[REDACTED]"))
+            .field("salt_len", &self.salt_len)
+            .field("iter_count", &self.iter_count)
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
 impl Pbes2HmacAeskwJweEncrypter {
     pub fn set_salt_len(&mut self, salt_len: usize) {
         if salt_len < 8 {
@@ -264,7 +276,7 @@ impl JweEncrypter for Pbes2HmacAeskwJweEncrypter {
                 }
                 Some(_) => bail!("The p2s header claim must be string."),
                 None => {
-                    let p2s = util::random_bytes(self.salt_len);
+                    let p2s = util::try_random_bytes(self.salt_len)?;
                     let p2s_b64 = base64::encode_config(&p2s, base64::URL_SAFE_NO_PAD);
                     out_header.set_claim("p2s", Some(Value::String(p2s_b64)))?;
                     p2s
@@ -325,13 +337,23 @@ impl Deref for Pbes2HmacAeskwJweEncrypter {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Pbes2HmacAeskwJweDecrypter {
     algorithm: Pbes2HmacAeskwJweAlgorithm,
     private_key: Vec<u8>,
     key_id: Option<String>,
 }
 
+impl Debug for Pbes2HmacAeskwJweDecrypter {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("Pbes2HmacAeskwJweDecrypter")
+            .field("algorithm", &self.algorithm)
+            .field("private_key", &format_args!("[REDACTED]"))
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
 impl Pbes2HmacAeskwJweDecrypter {
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
@@ -191,6 +191,8 @@ impl RsaesJweAlgorithm {
                 None => bail!("A parameter e is required."),
             };
 
+            RsaKeyPair::check_jwk_lengths(&n, &e, &[])?;
+
             let mut builder = DerBuilder::new();
             builder.begin(DerType::Sequence);
             {
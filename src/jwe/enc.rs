@@ -1,5 +1,10 @@
 pub mod aescbc_hmac;
 pub mod aesgcm;
+pub mod chacha20_poly1305;
+
+use anyhow::bail;
+
+use crate::jwe::JweContentEncryption;
 
 use crate::jwe::enc::aescbc_hmac::AescbcHmacJweEncryption;
 pub use AescbcHmacJweEncryption::A128cbcHs256 as A128CBC_HS256;
@@ -10,3 +15,44 @@ use crate::jwe::enc::aesgcm::AesgcmJweEncryption;
 pub use AesgcmJweEncryption::A128gcm as A128GCM;
 pub use AesgcmJweEncryption::A192gcm as A192GCM;
 pub use AesgcmJweEncryption::A256gcm as A256GCM;
+
+use crate::jwe::enc::chacha20_poly1305::ChaCha20Poly1305JweEncryption;
+pub use ChaCha20Poly1305JweEncryption::C20p as C20P;
+pub use ChaCha20Poly1305JweEncryption::Xc20p as XC20P;
+
+/// Check the lengths of a content encryption key and an initialization vector
+/// for an encryption, and return the initialization vector.
+///
+/// # Arguments
+///
+/// * `enc` - a content encryption
+/// * `key` - a content encryption key
+/// * `iv` - an initialization vector
+pub(crate) fn check_key_and_iv<'a>(
+    enc: &dyn JweContentEncryption,
+    key: &[u8],
+    iv: Option<&'a [u8]>,
+) -> anyhow::Result<&'a [u8]> {
+    let expected_len = enc.key_len();
+    if key.len() != expected_len {
+        bail!(
+            "The length of content encryption key must be {}: {}",
+            expected_len,
+            key.len()
+        );
+    }
+
+    let iv = match iv {
+        Some(val) => val,
+        None => bail!("An initialization vector is required."),
+    };
+    let expected_len = enc.iv_len();
+    if iv.len() != expected_len {
+        bail!(
+            "The length of initialization vector must be {}: {}",
+            expected_len,
+            iv.len()
+        );
+    }
+    Ok(iv)
+}
@@ -4,7 +4,7 @@ use std::ops::Deref;
 use anyhow::bail;
 use openssl::symm::{self, Cipher};
 
-use crate::jwe::JweContentEncryption;
+use crate::jwe::{enc, JweContentEncryption};
 use crate::JoseError;
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -56,18 +56,14 @@ impl JweContentEncryption for AesgcmJweEncryption {
         aad: &[u8],
     ) -> Result<(Vec<u8>, Option<Vec<u8>>), JoseError> {
         (|| -> anyhow::Result<(Vec<u8>, Option<Vec<u8>>)> {
-            let expected_len = self.key_len();
-            if key.len() != expected_len {
-                bail!(
-                    "The length of content encryption key must be {}: {}",
-                    expected_len,
-                    key.len()
-                );
-            }
+            // GCM derives the counter block from an IV of other lengths by GHASH,
+            // so only 96-bit IVs are accepted (RFC 7518 Section 5.3).
+            let iv = enc::check_key_and_iv(self, key, iv)?;
 
             let cipher = self.cipher();
             let mut tag = [0; 16];
-            let encrypted_message = symm::encrypt_aead(cipher, key, iv, aad, message, &mut tag)?;
+            let encrypted_message =
+                symm::encrypt_aead(cipher, key, Some(iv), aad, message, &mut tag)?;
             Ok((encrypted_message, Some(tag.to_vec())))
         })()
         .map_err(|err| JoseError::InvalidKeyFormat(err))
@@ -82,22 +78,16 @@ impl JweContentEncryption for AesgcmJweEncryption {
         tag: Option<&[u8]>,
     ) -> Result<Vec<u8>, JoseError> {
         (|| -> anyhow::Result<Vec<u8>> {
-            let expected_len = self.key_len();
-            if key.len() != expected_len {
-                bail!(
-                    "The length of content encryption key must be {}: {}",
-                    expected_len,
-                    key.len()
-                );
-            }
+            let iv = enc::check_key_and_iv(self, key, iv)?;
 
             let tag = match tag {
-                Some(val) => val,
+                Some(val) if val.len() == 16 => val,
+                Some(val) => bail!("The length of tag must be 16: {}", val.len()),
                 None => bail!("A tag value is required."),
             };
 
             let cipher = self.cipher();
-            let message = symm::decrypt_aead(cipher, key, iv, aad, encrypted_message, tag)?;
+            let message = symm::decrypt_aead(cipher, key, Some(iv), aad, encrypted_message, tag)?;
             Ok(message)
         })()
         .map_err(|err| JoseError::InvalidJweFormat(err))
@@ -156,4 +146,35 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn reject_invalid_iv_and_tag_length_aes_gcm() -> Result<()> {
+        let message = b"abcde12345";
+        let aad = b"test";
+
+        let enc = AesgcmJweEncryption::A256gcm;
+        let key = util::random_bytes(enc.key_len());
+        let iv = util::random_bytes(enc.iv_len());
+        let (encrypted_message, tag) = enc.encrypt(&key, Some(&iv), message, aad)?;
+        let tag = tag.unwrap();
+
+        let short_iv = util::random_bytes(8);
+        let err = enc
+            .decrypt(&key, Some(&short_iv), &encrypted_message, aad, Some(&tag))
+            .unwrap_err();
+        assert!(
+            format!("{}", err).contains("initialization vector"),
+            "{}",
+            err
+        );
+        assert!(enc.encrypt(&key, Some(&short_iv), message, aad).is_err());
+        assert!(enc.encrypt(&key, None, message, aad).is_err());
+
+        let err = enc
+            .decrypt(&key, Some(&iv), &encrypted_message, aad, Some(&tag[..12]))
+            .unwrap_err();
+        assert!(format!("{}", err).contains("tag"), "{}", err);
+
+        Ok(())
+    }
 }
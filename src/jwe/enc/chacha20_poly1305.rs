@@ -0,0 +1,264 @@
+use std::fmt::Display;
+use std::ops::Deref;
+
+use anyhow::bail;
+use openssl::symm::{self, Cipher};
+
+use crate::jwe::{enc, JweContentEncryption};
+use crate::JoseError;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum ChaCha20Poly1305JweEncryption {
+    /// ChaCha20-Poly1305 using 256-bit key and 96-bit nonce
+    C20p,
+    /// XChaCha20-Poly1305 using 256-bit key and 192-bit nonce
+    Xc20p,
+}
+
+impl ChaCha20Poly1305JweEncryption {
+    /// Return the key and the 96-bit nonce for the ChaCha20-Poly1305 cipher.
+    ///
+    /// XChaCha20-Poly1305 derives a subkey from the first 128 bits of the nonce
+    /// by HChaCha20 and uses the rest of the nonce with 32 zero bits prefixed.
+    fn cipher_key_and_nonce(&self, key: &[u8], iv: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        match self {
+            Self::C20p => (key.to_vec(), iv.to_vec()),
+            Self::Xc20p => {
+                let subkey = hchacha20(key, &iv[0..16]);
+                let mut nonce = vec![0; 4];
+                nonce.extend_from_slice(&iv[16..]);
+                (subkey, nonce)
+            }
+        }
+    }
+}
+
+impl JweContentEncryption for ChaCha20Poly1305JweEncryption {
+    fn name(&self) -> &str {
+        match self {
+            Self::C20p => "C20P",
+            Self::Xc20p => "XC20P",
+        }
+    }
+
+    fn key_len(&self) -> usize {
+        32
+    }
+
+    fn iv_len(&self) -> usize {
+        match self {
+            Self::C20p => 12,
+            Self::Xc20p => 24,
+        }
+    }
+
+    fn encrypt(
+        &self,
+        key: &[u8],
+        iv: Option<&[u8]>,
+        message: &[u8],
+        aad: &[u8],
+    ) -> Result<(Vec<u8>, Option<Vec<u8>>), JoseError> {
+        (|| -> anyhow::Result<(Vec<u8>, Option<Vec<u8>>)> {
+            let iv = enc::check_key_and_iv(self, key, iv)?;
+            let (key, nonce) = self.cipher_key_and_nonce(key, iv);
+
+            let cipher = Cipher::chacha20_poly1305();
+            let mut tag = [0; 16];
+            let encrypted_message =
+                symm::encrypt_aead(cipher, &key, Some(&nonce), aad, message, &mut tag)?;
+            Ok((encrypted_message, Some(tag.to_vec())))
+        })()
+        .map_err(JoseError::InvalidKeyFormat)
+    }
+
+    fn decrypt(
+        &self,
+        key: &[u8],
+        iv: Option<&[u8]>,
+        encrypted_message: &[u8],
+        aad: &[u8],
+        tag: Option<&[u8]>,
+    ) -> Result<Vec<u8>, JoseError> {
+        (|| -> anyhow::Result<Vec<u8>> {
+            let iv = enc::check_key_and_iv(self, key, iv)?;
+
+            let tag = match tag {
+                Some(val) if val.len() == 16 => val,
+                Some(val) => bail!("The length of tag must be 16: {}", val.len()),
+                None => bail!("A tag value is required."),
+            };
+
+            let (key, nonce) = self.cipher_key_and_nonce(key, iv);
+            let cipher = Cipher::chacha20_poly1305();
+            let message =
+                symm::decrypt_aead(cipher, &key, Some(&nonce), aad, encrypted_message, tag)?;
+            Ok(message)
+        })()
+        .map_err(JoseError::InvalidJweFormat)
+    }
+
+    fn box_clone(&self) -> Box<dyn JweContentEncryption> {
+        Box::new(*self)
+    }
+}
+
+impl Display for ChaCha20Poly1305JweEncryption {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        fmt.write_str(self.name())
+    }
+}
+
+impl Deref for ChaCha20Poly1305JweEncryption {
+    type Target = dyn JweContentEncryption;
+
+    fn deref(&self) -> &Self::Target {
+        self
+    }
+}
+
+/// Return the 256-bit subkey derived by HChaCha20 (draft-irtf-cfrg-xchacha Section 2.2).
+fn hchacha20(key: &[u8], nonce: &[u8]) -> Vec<u8> {
+    fn read_u32(input: &[u8]) -> u32 {
+        u32::from_le_bytes([input[0], input[1], input[2], input[3]])
+    }
+
+    fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+        state[a] = state[a].wrapping_add(state[b]);
+        state[d] = (state[d] ^ state[a]).rotate_left(16);
+        state[c] = state[c].wrapping_add(state[d]);
+        state[b] = (state[b] ^ state[c]).rotate_left(12);
+        state[a] = state[a].wrapping_add(state[b]);
+        state[d] = (state[d] ^ state[a]).rotate_left(8);
+        state[c] = state[c].wrapping_add(state[d]);
+        state[b] = (state[b] ^ state[c]).rotate_left(7);
+    }
+
+    let mut state = [0u32; 16];
+    state[0] = 0x61707865;
+    state[1] = 0x3320646e;
+    state[2] = 0x79622d32;
+    state[3] = 0x6b206574;
+    for i in 0..8 {
+        state[4 + i] = read_u32(&key[(i * 4)..]);
+    }
+    for i in 0..4 {
+        state[12 + i] = read_u32(&nonce[(i * 4)..]);
+    }
+
+    for _ in 0..10 {
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+
+    let mut subkey = Vec::with_capacity(32);
+    for i in (0..4).chain(12..16) {
+        subkey.extend_from_slice(&state[i].to_le_bytes());
+    }
+    subkey
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::{hchacha20, ChaCha20Poly1305JweEncryption};
+    use crate::util;
+
+    #[test]
+    fn encrypt_and_decrypt_chacha20_poly1305() -> Result<()> {
+        let message = b"abcde12345";
+        let aad = b"test";
+
+        for enc in [
+            ChaCha20Poly1305JweEncryption::C20p,
+            ChaCha20Poly1305JweEncryption::Xc20p,
+        ] {
+            let key = util::random_bytes(enc.key_len());
+            let iv = util::random_bytes(enc.iv_len());
+
+            let (encrypted_message, tag) = enc.encrypt(&key, Some(&iv), message, aad)?;
+            let decrypted_message = enc.decrypt(
+                &key,
+                Some(&iv),
+                &encrypted_message,
+                &aad[..],
+                tag.as_deref(),
+            )?;
+            assert_eq!(&message[..], &decrypted_message[..]);
+
+            let mut tampered_tag = tag.clone().unwrap();
+            tampered_tag[0] ^= 0x01;
+            assert!(enc
+                .decrypt(
+                    &key,
+                    Some(&iv),
+                    &encrypted_message,
+                    aad,
+                    Some(&tampered_tag)
+                )
+                .is_err());
+            assert!(enc
+                .decrypt(
+                    &key,
+                    Some(&iv),
+                    &encrypted_message,
+                    b"other",
+                    tag.as_deref()
+                )
+                .is_err());
+            assert!(enc
+                .decrypt(
+                    &key,
+                    Some(&iv[1..]),
+                    &encrypted_message,
+                    aad,
+                    tag.as_deref()
+                )
+                .is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn xchacha20_poly1305_test_vector() -> Result<()> {
+        // draft-irtf-cfrg-xchacha-03 Section 2.2.1
+        let key: Vec<u8> = (0x00..=0x1F).collect();
+        let nonce = util::decode_hex("000000090000004a0000000031415927");
+        assert_eq!(
+            hchacha20(&key, &nonce),
+            util::decode_hex("82413b4227b27bfed30e42508a877d73a0f9e4d58a74a853c12ec41326d3ecdc")
+        );
+
+        // draft-irtf-cfrg-xchacha-03 Appendix A.3.1
+        let enc = ChaCha20Poly1305JweEncryption::Xc20p;
+        let message = b"Ladies and Gentlemen of the class of '99: If I could offer you only one \
+            tip for the future, sunscreen would be it.";
+        let aad = util::decode_hex("50515253c0c1c2c3c4c5c6c7");
+        let key: Vec<u8> = (0x80..=0x9F).collect();
+        let iv: Vec<u8> = (0x40..=0x57).collect();
+        let (encrypted_message, tag) = enc.encrypt(&key, Some(&iv), message, &aad)?;
+        assert_eq!(
+            encrypted_message,
+            util::decode_hex(concat!(
+                "bd6d179d3e83d43b9576579493c0e939572a1700252bfaccbed2902c21396cbb",
+                "731c7f1b0b4aa6440bf3a82f4eda7e39ae64c6708c54c216cb96b72e1213b452",
+                "2f8c9ba40db5d945b11b69b982c1bb9e3f3fac2bc369488f76b2383565d3fff9",
+                "21f9664c97637da9768812f615c68b13b52e"
+            ))
+        );
+        assert_eq!(
+            tag,
+            Some(util::decode_hex("c0875924c1c7987947deafd8780acf49"))
+        );
+
+        Ok(())
+    }
+}
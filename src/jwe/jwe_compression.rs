@@ -11,6 +11,27 @@ pub trait JweCompression: Debug + Send + Sync {
 
     fn decompress(&self, message: &[u8]) -> Result<Vec<u8>, io::Error>;
 
+    /// Return the decompressed data or an error if it exceeds the maximum length.
+    ///
+    /// The default implementation checks the length after decompression.
+    /// An implementation should override it to stop decompression as soon as
+    /// the limit is exceeded.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The compressed data.
+    /// * `max_len` - The maximum length of the decompressed data.
+    fn decompress_with_limit(&self, message: &[u8], max_len: usize) -> Result<Vec<u8>, io::Error> {
+        let vec = self.decompress(message)?;
+        if vec.len() > max_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("The decompressed data exceeds {} bytes.", max_len),
+            ));
+        }
+        Ok(vec)
+    }
+
     fn box_clone(&self) -> Box<dyn JweCompression>;
 }
 
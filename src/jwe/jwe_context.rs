@@ -1,11 +1,14 @@
 use std::borrow::Cow;
+use std::cell::Cell;
 use std::cmp::Eq;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Debug;
 
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 
-use crate::jwe::enc::{A128CBC_HS256, A128GCM, A192CBC_HS384, A192GCM, A256CBC_HS512, A256GCM};
+use crate::jwe::enc::{
+    A128CBC_HS256, A128GCM, A192CBC_HS384, A192GCM, A256CBC_HS512, A256GCM, C20P, XC20P,
+};
 use crate::jwe::zip::Def;
 use crate::jwe::{
     JweCompression, JweContentEncryption, JweDecrypter, JweEncrypter, JweHeader, JweHeaderSet,
@@ -13,11 +16,26 @@ use crate::jwe::{
 use crate::util;
 use crate::{JoseError, JoseHeader, Map, Value};
 
+/// The default maximum length of a decompressed payload.
+const DEFAULT_MAX_DECOMPRESSED_LEN: usize = 10 * 1024 * 1024;
+
+/// The default maximum length of a serialized input.
+const DEFAULT_MAX_INPUT_LEN: usize = 8 * 1024 * 1024;
+
+/// The default maximum length of a decoded ciphertext.
+const DEFAULT_MAX_PAYLOAD_LEN: usize = 8 * 1024 * 1024;
+
+/// The decrypted payload, the merged header and the JWE additional authenticated data.
+pub type PayloadWithAad = (Vec<u8>, JweHeader, Option<Vec<u8>>);
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct JweContext {
     acceptable_criticals: BTreeSet<String>,
     compressions: BTreeMap<String, Box<dyn JweCompression>>,
     content_encryptions: BTreeMap<String, Box<dyn JweContentEncryption>>,
+    max_decompressed_len: Option<usize>,
+    max_input_len: Option<usize>,
+    max_payload_len: Option<usize>,
 }
 
 impl JweContext {
@@ -41,6 +59,8 @@ impl JweContext {
                     Box::new(A128GCM),
                     Box::new(A192GCM),
                     Box::new(A256GCM),
+                    Box::new(C20P),
+                    Box::new(XC20P),
                 ];
 
                 let mut map = BTreeMap::new();
@@ -49,6 +69,9 @@ impl JweContext {
                 }
                 map
             },
+            max_decompressed_len: Some(DEFAULT_MAX_DECOMPRESSED_LEN),
+            max_input_len: Some(DEFAULT_MAX_INPUT_LEN),
+            max_payload_len: Some(DEFAULT_MAX_PAYLOAD_LEN),
         }
     }
 
@@ -110,6 +133,55 @@ impl JweContext {
         self.compressions.remove(name);
     }
 
+    /// Return the maximum length of a decompressed payload.
+    /// The default value is 10 MiB.
+    pub fn max_decompressed_len(&self) -> Option<usize> {
+        self.max_decompressed_len
+    }
+
+    /// Set the maximum length of a decompressed payload.
+    /// If it is none, the length is not limited.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a maximum length in bytes
+    pub fn set_max_decompressed_len(&mut self, value: Option<usize>) {
+        self.max_decompressed_len = value;
+    }
+
+    /// Return the maximum length of a serialized input for a deserialization.
+    /// The default value is 8 MiB.
+    pub fn max_input_len(&self) -> Option<usize> {
+        self.max_input_len
+    }
+
+    /// Set the maximum length of a serialized input for a deserialization.
+    /// If it is none, the length is not limited.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a maximum length in bytes
+    pub fn set_max_input_len(&mut self, value: Option<usize>) {
+        self.max_input_len = value;
+    }
+
+    /// Return the maximum length of a decoded ciphertext for a deserialization.
+    /// The default value is 8 MiB.
+    pub fn max_payload_len(&self) -> Option<usize> {
+        self.max_payload_len
+    }
+
+    /// Set the maximum length of a decoded ciphertext for a deserialization.
+    /// The length is checked before the ciphertext is decoded and decrypted.
+    /// If it is none, the length is not limited.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a maximum length in bytes
+    pub fn set_max_payload_len(&mut self, value: Option<usize>) {
+        self.max_payload_len = value;
+    }
+
     /// Get a content encryption algorithm for enc header claim value.
     ///
     /// # Arguments
@@ -204,7 +276,7 @@ impl JweContext {
                 &mut out_header,
             )? {
                 Some(val) => val,
-                None => Cow::Owned(util::random_bytes(key_len)),
+                None => Cow::Owned(util::try_random_bytes(key_len)?),
             };
 
             let encrypted_key = encrypter.encrypt(&key, &header, &mut out_header)?;
@@ -229,7 +301,7 @@ impl JweContext {
 
             let iv_vec;
             let iv = if cencryption.iv_len() > 0 {
-                iv_vec = util::random_bytes(cencryption.iv_len());
+                iv_vec = util::try_random_bytes(cencryption.iv_len())?;
                 Some(iv_vec.as_slice())
             } else {
                 None
@@ -443,11 +515,11 @@ impl JweContext {
 
             let key = match &selected_key {
                 Some(val) => Cow::Borrowed(val.as_ref()),
-                None => Cow::Owned(util::random_bytes(cencryption.key_len())),
+                None => Cow::Owned(util::try_random_bytes(cencryption.key_len())?),
             };
 
             let iv = if cencryption.iv_len() > 0 {
-                Some(util::random_bytes(cencryption.iv_len()))
+                Some(util::try_random_bytes(cencryption.iv_len())?)
             } else {
                 None
             };
@@ -485,8 +557,9 @@ impl JweContext {
             if let Some(val) = &protected_b64 {
                 full_aad.push_str(&val);
             }
-            full_aad.push_str(".");
+            // RFC 7516 Section 5.1 Step 14: the period is only added with a JWE AAD.
             if let Some(val) = &aad_b64 {
+                full_aad.push_str(".");
                 full_aad.push_str(&val);
             }
 
@@ -542,12 +615,14 @@ impl JweContext {
                     writed = true;
                 }
 
-                json.push_str(if writed { "," } else { "{" });
-                json.push_str("\"encrypted_key\":\"");
                 if let Some(val) = encrypted_key {
+                    json.push_str(if writed { "," } else { "{" });
+                    json.push_str("\"encrypted_key\":\"");
                     base64::encode_config_buf(&val, base64::URL_SAFE_NO_PAD, &mut json);
+                    json.push('"');
+                    writed = true;
                 }
-                json.push_str("\"}");
+                json.push_str(if writed { "}" } else { "{}" });
             }
             json.push_str("]");
 
@@ -685,7 +760,7 @@ impl JweContext {
                 &mut protected,
             )? {
                 Some(val) => val,
-                None => Cow::Owned(util::random_bytes(cencryption.key_len())),
+                None => Cow::Owned(util::try_random_bytes(cencryption.key_len())?),
             };
 
             let encrypted_key = encrypter.encrypt(&key, &merged, &mut protected)?;
@@ -706,7 +781,7 @@ impl JweContext {
 
             let iv_vec;
             let iv = if cencryption.iv_len() > 0 {
-                iv_vec = util::random_bytes(cencryption.iv_len());
+                iv_vec = util::try_random_bytes(cencryption.iv_len())?;
                 Some(iv_vec.as_slice())
             } else {
                 None
@@ -738,8 +813,9 @@ impl JweContext {
             if let Some(val) = &protected_b64 {
                 full_aad.push_str(&val);
             }
-            full_aad.push_str(".");
+            // RFC 7516 Section 5.1 Step 14: the period is only added with a JWE AAD.
             if let Some(val) = &aad_b64 {
+                full_aad.push_str(".");
                 full_aad.push_str(&val);
             }
 
@@ -783,11 +859,11 @@ impl JweContext {
                 }
             }
 
-            json.push_str(",\"encrypted_key\":\"");
             if let Some(val) = encrypted_key {
+                json.push_str(",\"encrypted_key\":\"");
                 base64::encode_config_buf(&val, base64::URL_SAFE_NO_PAD, &mut json);
+                json.push_str("\"");
             }
-            json.push_str("\"");
 
             if let Some(val) = aad_b64 {
                 json.push_str(",\"aad\":\"");
@@ -849,6 +925,8 @@ impl JweContext {
     {
         (|| -> anyhow::Result<(Vec<u8>, JweHeader)> {
             let input = input.as_ref();
+            self.check_input_len(input.len())?;
+
             let indexies: Vec<usize> = input
                 .iter()
                 .enumerate()
@@ -857,17 +935,20 @@ impl JweContext {
                 .collect();
             if indexies.len() != 4 {
                 bail!(
-                    "The compact serialization form of JWE must be five parts separated by colon."
+                    "The compact form of JWE must be five parts separated by period: {}",
+                    indexies.len() + 1
                 );
             }
 
             let header_b64 = &input[0..indexies[0]];
+            if header_b64.is_empty() {
+                bail!("The JWE header part must not be empty.");
+            }
 
             let encrypted_key_b64 = &input[(indexies[0] + 1)..(indexies[1])];
             let encrypted_key_vec;
             let encrypted_key = if encrypted_key_b64.len() > 0 {
-                encrypted_key_vec =
-                    base64::decode_config(encrypted_key_b64, base64::URL_SAFE_NO_PAD)?;
+                encrypted_key_vec = util::decode_base64url_segment(encrypted_key_b64)?;
                 Some(encrypted_key_vec.as_slice())
             } else {
                 None
@@ -876,26 +957,27 @@ impl JweContext {
             let iv_b64 = &input[(indexies[1] + 1)..(indexies[2])];
             let iv_vec;
             let iv = if iv_b64.len() > 0 {
-                iv_vec = base64::decode_config(iv_b64, base64::URL_SAFE_NO_PAD)?;
+                iv_vec = util::decode_base64url_segment(iv_b64)?;
                 Some(iv_vec.as_slice())
             } else {
                 None
             };
 
             let ciphertext_b64 = &input[(indexies[2] + 1)..(indexies[3])];
-            let ciphertext = base64::decode_config(ciphertext_b64, base64::URL_SAFE_NO_PAD)?;
+            self.check_payload_len(ciphertext_b64.len())?;
+            let ciphertext = util::decode_base64url_segment(ciphertext_b64)?;
 
             let tag_b64 = &input[(indexies[3] + 1)..];
             let tag_vec;
             let tag = if tag_b64.len() > 0 {
-                tag_vec = base64::decode_config(tag_b64, base64::URL_SAFE_NO_PAD)?;
+                tag_vec = util::decode_base64url_segment(tag_b64)?;
                 Some(tag_vec.as_slice())
             } else {
                 None
             };
 
-            let header = base64::decode_config(header_b64, base64::URL_SAFE_NO_PAD)?;
-            let merged: Map<String, Value> = serde_json::from_slice(&header)?;
+            let header = util::decode_base64url_segment(header_b64)?;
+            let merged = util::parse_json_object(&header)?;
             let merged = JweHeader::from_map(merged)?;
 
             let decrypter = match selector(&merged)? {
@@ -952,7 +1034,7 @@ impl JweContext {
 
             let content = cencryption.decrypt(&key, iv, &ciphertext, header_b64, tag)?;
             let content = match compression {
-                Some(val) => val.decompress(&content)?,
+                Some(val) => self.decompress(val, &content)?,
                 None => content,
             };
 
@@ -964,8 +1046,61 @@ impl JweContext {
         })
     }
 
+    /// Deserialize the input that is formatted by compact serialization
+    /// and decrypt it by the first decrypter that succeeds.
+    ///
+    /// Decrypters whose algorithm or key ID doesn't match the header are skipped.
+    /// The decrypters are tried in order, and the errors are collected only until
+    /// one of them succeeds; the decrypters after it are not tried. A failure of the
+    /// key unwrapping and a failure of the content decryption are reported by the
+    /// same message, so that a caller can't tell which step failed. The elapsed time
+    /// depends on how many decrypters are tried before the one that succeeds.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input data.
+    /// * `decrypters` - The candidate JWE decrypters.
+    pub fn decrypt_with_keys(
+        &self,
+        input: impl AsRef<[u8]>,
+        decrypters: &[&dyn JweDecrypter],
+    ) -> Result<(Vec<u8>, JweHeader), JoseError> {
+        let input = input.as_ref();
+        let mut errors = Vec::new();
+        for (i, decrypter) in decrypters.iter().enumerate() {
+            let selected = Cell::new(false);
+            let result = self.deserialize_compact_with_selector(input, |header| {
+                if header.algorithm() != Some(decrypter.algorithm().name()) {
+                    return Ok(None);
+                }
+                if let Some(expected) = decrypter.key_id() {
+                    if header.key_id() != Some(expected) {
+                        return Ok(None);
+                    }
+                }
+                selected.set(true);
+                Ok(Some(*decrypter))
+            });
+
+            match result {
+                Ok(val) => return Ok(val),
+                Err(_) if selected.get() => errors.push(format!("{}: failed to decrypt", i)),
+                Err(err) => errors.push(format!("{}: {}", i, err)),
+            }
+        }
+
+        Err(JoseError::InvalidJweFormat(anyhow!(
+            "None of the {} decrypters could decrypt the JWE: [{}]",
+            decrypters.len(),
+            errors.join(", ")
+        )))
+    }
+
     /// Deserialize the input that is formatted by flattened json serialization.
     ///
+    /// The returned header is merged from the protected header, the shared unprotected
+    /// header and the unprotected header of the recipient that was decrypted.
+    ///
     /// # Arguments
     ///
     /// * `input` - The input data.
@@ -1000,6 +1135,9 @@ impl JweContext {
 
     /// Deserialize the input that is formatted by flattened json serialization.
     ///
+    /// The returned header is merged from the protected header, the shared unprotected
+    /// header and the unprotected header of the recipient that was decrypted.
+    ///
     /// # Arguments
     ///
     /// * `input` - The input data.
@@ -1012,9 +1150,49 @@ impl JweContext {
     where
         F: Fn(&JweHeader) -> Result<Option<&'a dyn JweDecrypter>, JoseError>,
     {
-        (|| -> anyhow::Result<(Vec<u8>, JweHeader)> {
+        self.deserialize_json_with_aad_and_selector(input, selector)
+            .map(|(content, header, _)| (content, header))
+    }
+
+    /// Deserialize the input that is formatted by flattened json serialization
+    /// and return the JWE additional authenticated data (aad) too.
+    ///
+    /// The aad is authenticated with the protected header by the content encryption,
+    /// so a modified aad causes a decryption error.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input data.
+    /// * `decrypter` - The JWE decrypter.
+    pub fn deserialize_json_with_aad(
+        &self,
+        input: impl AsRef<[u8]>,
+        decrypter: &dyn JweDecrypter,
+    ) -> Result<PayloadWithAad, JoseError> {
+        self.deserialize_json_with_aad_and_selector(input, |header| {
+            if header.algorithm() != Some(decrypter.algorithm().name()) {
+                return Ok(None);
+            }
+
+            match decrypter.key_id() {
+                Some(expected) if header.key_id() != Some(expected) => Ok(None),
+                _ => Ok(Some(decrypter)),
+            }
+        })
+    }
+
+    fn deserialize_json_with_aad_and_selector<'a, F>(
+        &self,
+        input: impl AsRef<[u8]>,
+        selector: F,
+    ) -> Result<PayloadWithAad, JoseError>
+    where
+        F: Fn(&JweHeader) -> Result<Option<&'a dyn JweDecrypter>, JoseError>,
+    {
+        (|| -> anyhow::Result<PayloadWithAad> {
             let input = input.as_ref();
-            let mut map: Map<String, Value> = serde_json::from_slice(input)?;
+            self.check_input_len(input.len())?;
+            let mut map = util::parse_json_object(input)?;
 
             let (protected, protected_b64) = match map.remove("protected") {
                 Some(Value::String(val)) => {
@@ -1022,7 +1200,7 @@ impl JweContext {
                         bail!("The protected field must be empty.");
                     }
                     let vec = base64::decode_config(&val, base64::URL_SAFE_NO_PAD)?;
-                    let json: Map<String, Value> = serde_json::from_slice(&vec)?;
+                    let json = util::parse_json_object(&vec)?;
                     (Some(json), Some(val))
                 }
                 Some(_) => bail!("The protected field must be a string."),
@@ -1067,6 +1245,7 @@ impl JweContext {
                     if val.len() == 0 {
                         bail!("The ciphertext field must be empty.");
                     }
+                    self.check_payload_len(val.len())?;
                     base64::decode_config(&val, base64::URL_SAFE_NO_PAD)?
                 }
                 Some(_) => bail!("The ciphertext field must be string."),
@@ -1115,7 +1294,7 @@ impl JweContext {
                 let encrypted_key = match recipient.get("encrypted_key") {
                     Some(Value::String(val)) => {
                         if val.len() == 0 {
-                            bail!("The encrypted_key field must be empty.");
+                            bail!("The encrypted_key field must not be empty.");
                         }
                         encrypted_key_vec = base64::decode_config(&val, base64::URL_SAFE_NO_PAD)?;
                         Some(encrypted_key_vec.as_slice())
@@ -1198,9 +1377,10 @@ impl JweContext {
                     Some(val) => val,
                     None => String::new(),
                 };
-                full_aad.push_str(".");
-                if let Some(val) = aad_b64 {
-                    full_aad.push_str(&val);
+                // RFC 7516 Section 5.2 Step 15: the period is only added with a JWE AAD.
+                if let Some(val) = &aad_b64 {
+                    full_aad.push_str(".");
+                    full_aad.push_str(val);
                 }
 
                 let key = decrypter.decrypt(encrypted_key, cencryption, &merged)?;
@@ -1215,11 +1395,16 @@ impl JweContext {
                 let content =
                     cencryption.decrypt(&key, iv, &ciphertext, full_aad.as_bytes(), tag)?;
                 let content = match compression {
-                    Some(val) => val.decompress(&content)?,
+                    Some(val) => self.decompress(val, &content)?,
                     None => content,
                 };
 
-                return Ok((content, merged));
+                let aad = match &aad_b64 {
+                    Some(val) => Some(base64::decode_config(val, base64::URL_SAFE_NO_PAD)?),
+                    None => None,
+                };
+
+                return Ok((content, merged, aad));
             }
 
             bail!("A recipient that matched the header claims is not found.");
@@ -1229,4 +1414,32 @@ impl JweContext {
             Err(err) => JoseError::InvalidJweFormat(err),
         })
     }
+
+    fn check_input_len(&self, len: usize) -> anyhow::Result<()> {
+        match self.max_input_len {
+            Some(max) if len > max => bail!("The input is too long: {} > {}", len, max),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the length of a ciphertext part before it is decoded.
+    /// A base64url text of n characters is decoded to at most n * 3 / 4 bytes.
+    fn check_payload_len(&self, encoded_len: usize) -> anyhow::Result<()> {
+        let len = encoded_len / 4 * 3 + encoded_len % 4 * 3 / 4;
+        match self.max_payload_len {
+            Some(max) if len > max => bail!("The ciphertext is too long: {} > {}", len, max),
+            _ => Ok(()),
+        }
+    }
+
+    fn decompress(
+        &self,
+        compression: &dyn JweCompression,
+        content: &[u8],
+    ) -> Result<Vec<u8>, std::io::Error> {
+        match self.max_decompressed_len {
+            Some(val) => compression.decompress_with_limit(content, val),
+            None => compression.decompress(content),
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use std::cmp::Eq;
-use std::convert::Into;
+use std::convert::{Into, TryFrom};
 use std::fmt::{Debug, Display};
 use std::ops::Deref;
 
@@ -7,7 +7,7 @@ use anyhow::bail;
 
 use crate::jwk::Jwk;
 use crate::util;
-use crate::{JoseError, JoseHeader, Map, Value};
+use crate::{JoseError, JoseHeader, Map, Number, Value};
 
 /// Represent JWE header claims
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -28,7 +28,7 @@ impl JweHeader {
     /// * `value` - The json style header claims
     pub fn from_bytes(value: &[u8]) -> Result<Self, JoseError> {
         let claims = (|| -> anyhow::Result<Map<String, Value>> {
-            let claims: Map<String, Value> = serde_json::from_slice(value)?;
+            let claims = util::parse_json_object(value)?;
             Ok(claims)
         })()
         .map_err(|err| JoseError::InvalidJson(err))?;
@@ -415,6 +415,49 @@ impl JweHeader {
         }
     }
 
+    /// Set a value for a PBES2 salt input header claim (p2s).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A PBES2 salt input
+    pub fn set_pbes2_salt_input(&mut self, value: impl AsRef<[u8]>) {
+        let key = "p2s";
+        let val = base64::encode_config(&value, base64::URL_SAFE_NO_PAD);
+        self.claims.insert(key.to_string(), Value::String(val));
+    }
+
+    /// Return the value for PBES2 salt input header claim (p2s).
+    pub fn pbes2_salt_input(&self) -> Option<Vec<u8>> {
+        match self.claims.get("p2s") {
+            Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD).ok(),
+            _ => None,
+        }
+    }
+
+    /// Set a value for a PBES2 count header claim (p2c).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A PBES2 iteration count
+    pub fn set_pbes2_count(&mut self, value: usize) {
+        let key = "p2c";
+        self.claims
+            .insert(key.to_string(), Value::Number(Number::from(value)));
+    }
+
+    /// Return the value for PBES2 count header claim (p2c).
+    ///
+    /// A count of 0 is not a valid iteration count, so it is returned as none.
+    pub fn pbes2_count(&self) -> Option<usize> {
+        match self.claims.get("p2c") {
+            Some(Value::Number(val)) => match val.as_u64() {
+                Some(val) if val > 0 => usize::try_from(val).ok(),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     /// Set a value for issuer header claim (iss).
     ///
     /// # Arguments
@@ -563,7 +606,7 @@ impl JweHeader {
                     }
                     _ => bail!("The JWE {} header claim must be a array.", key),
                 },
-                "x5t" | "x5t#S256" | "nonce" | "apu" | "apv" => match &value {
+                "x5t" | "x5t#S256" | "nonce" | "apu" | "apv" | "p2s" => match &value {
                     Value::String(val) => {
                         if !util::is_base64_url_safe_nopad(val) {
                             bail!("The JWE {} header claim must be a base64 string.", key);
@@ -571,6 +614,10 @@ impl JweHeader {
                     }
                     _ => bail!("The JWE {} header claim must be a string.", key),
                 },
+                "p2c" => match &value {
+                    Value::Number(val) if matches!(val.as_u64(), Some(val) if val > 0) => {}
+                    _ => bail!("The JWE {} header claim must be a positive number.", key),
+                },
                 "x5c" => match &value {
                     Value::Array(vals) => {
                         for val in vals {
@@ -711,4 +758,39 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_jwe_header_key_management_claims() -> Result<()> {
+        let mut header = JweHeader::new();
+        header.set_algorithm("PBES2-HS256+A128KW");
+        header.set_content_encryption("A128GCM");
+        header.set_compression("DEF");
+        header.set_key_id("kid");
+        header.set_agreement_partyuinfo(b"Alice");
+        header.set_agreement_partyvinfo(b"Bob");
+        header.set_pbes2_salt_input(b"saltsalt");
+        header.set_pbes2_count(4096);
+
+        let header = JweHeader::from_bytes(header.to_string().as_bytes())?;
+        assert_eq!(header.algorithm(), Some("PBES2-HS256+A128KW"));
+        assert_eq!(header.content_encryption(), Some("A128GCM"));
+        assert_eq!(header.compression(), Some("DEF"));
+        assert_eq!(header.key_id(), Some("kid"));
+        assert_eq!(header.agreement_partyuinfo(), Some(b"Alice".to_vec()));
+        assert_eq!(header.agreement_partyvinfo(), Some(b"Bob".to_vec()));
+        assert_eq!(header.pbes2_salt_input(), Some(b"saltsalt".to_vec()));
+        assert_eq!(header.pbes2_count(), Some(4096));
+        assert_eq!(header.claim("p2s"), Some(&json!("c2FsdHNhbHQ")));
+
+        assert!(JweHeader::from_bytes(br#"{"p2c":-1}"#).is_err());
+        assert!(JweHeader::from_bytes(br#"{"p2c":0}"#).is_err());
+        assert!(JweHeader::from_bytes(br#"{"p2c":1}"#).is_ok());
+
+        let mut header = JweHeader::new();
+        header.set_pbes2_count(0);
+        assert_eq!(header.pbes2_count(), None);
+        assert!(JweHeader::from_bytes(br#"{"p2s":"not base64!"}"#).is_err());
+
+        Ok(())
+    }
 }
@@ -35,6 +35,21 @@ impl JweCompression for DeflateJweCompression {
         Ok(vec)
     }
 
+    fn decompress_with_limit(&self, data: &[u8], max_len: usize) -> Result<Vec<u8>, io::Error> {
+        let decoder = DeflateDecoder::new(data);
+        let mut vec = Vec::new();
+        decoder
+            .take((max_len as u64).saturating_add(1))
+            .read_to_end(&mut vec)?;
+        if vec.len() > max_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("The decompressed data exceeds {} bytes.", max_len),
+            ));
+        }
+        Ok(vec)
+    }
+
     fn box_clone(&self) -> Box<dyn JweCompression> {
         Box::new(self.clone())
     }
@@ -3,7 +3,7 @@ use std::ops::Deref;
 
 use anyhow::bail;
 use openssl::bn::{BigNum, BigNumContext};
-use openssl::ec::{EcGroup, EcKey};
+use openssl::ec::{EcGroup, EcKey, EcPoint, PointConversionForm};
 use openssl::nid::Nid;
 use openssl::pkey::{PKey, Private};
 
@@ -14,7 +14,7 @@ use crate::util::oid::{
     ObjectIdentifier, OID_ID_EC_PUBLIC_KEY, OID_PRIME256V1, OID_SECP256K1, OID_SECP384R1,
     OID_SECP521R1,
 };
-use crate::{JoseError, Value};
+use crate::{JoseError, JwkParameterError, Value};
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum EcCurve {
@@ -52,13 +52,44 @@ impl EcCurve {
         }
     }
 
-    fn coordinate_size(&self) -> usize {
+    pub(crate) fn coordinate_size(&self) -> usize {
         match self {
             Self::P256 | Self::Secp256k1 => 32,
             Self::P384 => 48,
             Self::P521 => 66,
         }
     }
+
+    /// Return the uncompressed form of a compressed point on this curve.
+    ///
+    /// # Arguments
+    /// * `input` - A compressed point that is prefixed by 0x02 or 0x03.
+    pub(crate) fn decompress_point(&self, input: &[u8]) -> anyhow::Result<Vec<u8>> {
+        match input.first() {
+            Some(0x02) | Some(0x03) if input.len() == self.coordinate_size() + 1 => {}
+            _ => bail!("A compressed point of {} is invalid.", self.name()),
+        }
+
+        let group = EcGroup::from_curve_name(self.nid())?;
+        let mut ctx = BigNumContext::new()?;
+        let point = EcPoint::from_bytes(&group, input, &mut ctx)?;
+        let vec = point.to_bytes(&group, PointConversionForm::UNCOMPRESSED, &mut ctx)?;
+        Ok(vec)
+    }
+
+    /// Return the uncompressed form of the public point for a private scalar on this curve.
+    ///
+    /// # Arguments
+    /// * `d` - A big-endian private scalar.
+    pub(crate) fn public_point(&self, d: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let group = EcGroup::from_curve_name(self.nid())?;
+        let mut ctx = BigNumContext::new()?;
+        let d = BigNum::from_slice(d)?;
+        let mut point = EcPoint::new(&group)?;
+        point.mul_generator2(&group, &d, &mut ctx)?;
+        let vec = point.to_bytes(&group, PointConversionForm::UNCOMPRESSED, &mut ctx)?;
+        Ok(vec)
+    }
 }
 
 impl Display for EcCurve {
@@ -175,28 +206,31 @@ impl EcKeyPair {
                     "secp256k1" => EcCurve::Secp256k1,
                     _ => bail!("A Unknown curve: {}", val),
                 },
-                Some(_) => bail!("A parameter crv must be a string."),
-                None => bail!("A parameter crv is required."),
+                Some(_) => bail!(JwkParameterError::wrong_type("crv")),
+                None => bail!(JwkParameterError::missing("crv")),
             };
             let d = match jwk.parameter("d") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter d must be a string."),
-                None => bail!("A parameter d is required."),
+                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                    .map_err(|err| JwkParameterError::decode_error("d", err))?,
+                Some(_) => bail!(JwkParameterError::wrong_type("d")),
+                None => bail!(JwkParameterError::missing("d")),
             };
             let x = match jwk.parameter("x") {
                 Some(Value::String(val)) => {
-                    let x = base64::decode_config(val, base64::URL_SAFE_NO_PAD)?;
+                    let x = base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                        .map_err(|err| JwkParameterError::decode_error("x", err))?;
                     Some(x)
                 }
-                Some(_) => bail!("A parameter x must be a string."),
+                Some(_) => bail!(JwkParameterError::wrong_type("x")),
                 None => None,
             };
             let y = match jwk.parameter("y") {
                 Some(Value::String(val)) => {
-                    let y = base64::decode_config(val, base64::URL_SAFE_NO_PAD)?;
+                    let y = base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                        .map_err(|err| JwkParameterError::decode_error("y", err))?;
                     Some(y)
                 }
-                Some(_) => bail!("A parameter y must be a string."),
+                Some(_) => bail!(JwkParameterError::wrong_type("y")),
                 None => None,
             };
 
@@ -260,7 +294,15 @@ impl EcKeyPair {
     /// * `curve` - EC curve
     pub fn from_pem(input: impl AsRef<[u8]>, curve: Option<EcCurve>) -> Result<Self, JoseError> {
         (|| -> anyhow::Result<Self> {
-            let (alg, data) = util::parse_pem(input.as_ref())?;
+            let (params_curve, input) = Self::split_ec_parameters_pem(input.as_ref())?;
+            let curve = match (params_curve, curve) {
+                (Some(val), Some(val2)) if val != val2 => {
+                    bail!("The curve is mismatched: {}", val2)
+                }
+                (Some(val), _) => Some(val),
+                (None, val) => val,
+            };
+            let (alg, data) = util::parse_pem(input)?;
 
             let pkcs8_der_vec;
             let (pkcs8_der, curve) = match alg.as_str() {
@@ -361,6 +403,7 @@ impl EcKeyPair {
     pub(crate) fn detect_pkcs8(input: impl AsRef<[u8]>, is_public: bool) -> Option<EcCurve> {
         let curve;
         let mut reader = DerReader::from_reader(input.as_ref());
+        reader.set_strict(true);
 
         match reader.next() {
             Ok(Some(DerType::Sequence)) => {}
@@ -371,7 +414,7 @@ impl EcKeyPair {
             if !is_public {
                 // Version
                 match reader.next() {
-                    Ok(Some(DerType::Integer)) => match reader.to_u8() {
+                    Ok(Some(DerType::Integer)) => match reader.to_u32() {
                         Ok(val) if val == 0 => {}
                         _ => return None,
                     },
@@ -413,9 +456,45 @@ impl EcKeyPair {
         Some(curve)
     }
 
+    /// Split a leading "EC PARAMETERS" block that is written by "openssl ecparam -genkey"
+    /// and return the named curve of it with the remaining input.
+    fn split_ec_parameters_pem(input: &[u8]) -> anyhow::Result<(Option<EcCurve>, &[u8])> {
+        const END_LABEL: &[u8] = b"-----END EC PARAMETERS-----";
+
+        if !input.starts_with(b"-----BEGIN EC PARAMETERS-----") {
+            return Ok((None, input));
+        }
+
+        let end = match input.windows(END_LABEL.len()).position(|w| w == END_LABEL) {
+            Some(val) => val + END_LABEL.len(),
+            None => bail!("Mismatched the beginning and ending label."),
+        };
+
+        let (_, data) = util::parse_pem(&input[..end])?;
+        let mut reader = DerReader::from_reader(data.as_slice());
+        let curve = match reader.next() {
+            Ok(Some(DerType::ObjectIdentifier)) => match reader.to_object_identifier() {
+                Ok(val) if val == *OID_PRIME256V1 => EcCurve::P256,
+                Ok(val) if val == *OID_SECP384R1 => EcCurve::P384,
+                Ok(val) if val == *OID_SECP521R1 => EcCurve::P521,
+                Ok(val) if val == *OID_SECP256K1 => EcCurve::Secp256k1,
+                _ => bail!("The EC parameters is not a supported named curve."),
+            },
+            _ => bail!("The EC parameters must be a named curve."),
+        };
+
+        let rest = &input[end..];
+        let start = rest
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+        Ok((Some(curve), &rest[start..]))
+    }
+
     pub(crate) fn detect_ec_curve(input: impl AsRef<[u8]>) -> Option<EcCurve> {
         let curve;
         let mut reader = DerReader::from_reader(input.as_ref());
+        reader.set_strict(true);
 
         match reader.next() {
             Ok(Some(DerType::Sequence)) => {}
@@ -425,7 +504,7 @@ impl EcKeyPair {
         {
             // Version
             match reader.next() {
-                Ok(Some(DerType::Integer)) => match reader.to_u8() {
+                Ok(Some(DerType::Integer)) => match reader.to_u32() {
                     Ok(val) if val == 1 => {}
                     _ => return None,
                 },
@@ -504,6 +583,10 @@ impl KeyPair for EcKeyPair {
         }
     }
 
+    fn key_bits(&self) -> u32 {
+        self.private_key.bits()
+    }
+
     fn to_der_private_key(&self) -> Vec<u8> {
         self.private_key.private_key_to_der().unwrap()
     }
@@ -549,7 +632,12 @@ impl Deref for EcKeyPair {
 mod tests {
     use anyhow::Result;
 
+    use std::fs;
+    use std::path::PathBuf;
+
     use super::{EcCurve, EcKeyPair};
+    use crate::jwk::KeyPair;
+    use crate::jws::{ES256, ES384};
 
     #[test]
     fn test_ec_jwt() -> Result<()> {
@@ -575,4 +663,32 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_ec_sec1_pem_with_ec_parameters() -> Result<()> {
+        // Written by "openssl ecparam -name prime256v1 -genkey".
+        let private_pem = load_file("pem/EC_P-256_ecparam_private.pem")?;
+        let public_pem = load_file("pem/EC_P-256_ecparam_public.pem")?;
+
+        let key_pair = EcKeyPair::from_pem(&private_pem, None)?;
+        assert_eq!(key_pair.to_pem_public_key(), public_pem);
+
+        let signer = ES256.signer_from_pem(&private_pem)?;
+        let signature = signer.sign(b"message")?;
+        let verifier = ES256.verifier_from_pem(&public_pem)?;
+        verifier.verify(b"message", &signature)?;
+
+        assert!(ES384.signer_from_pem(&private_pem).is_err());
+
+        Ok(())
+    }
+
+    fn load_file(path: &str) -> Result<Vec<u8>> {
+        let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        pb.push("data");
+        pb.push(path);
+
+        let data = fs::read(&pb)?;
+        Ok(data)
+    }
 }
@@ -251,7 +251,7 @@ impl EcxKeyPair {
 
             match reader.next() {
                 Ok(Some(DerType::Integer)) => {
-                    if reader.to_u8().unwrap() != 0 {
+                    if reader.to_u32().unwrap() != 0 {
                         unreachable!("Invalid private key.");
                     }
                 }
@@ -342,6 +342,7 @@ impl EcxKeyPair {
     pub(crate) fn detect_pkcs8(input: impl AsRef<[u8]>, is_public: bool) -> Option<EcxCurve> {
         let curve;
         let mut reader = DerReader::from_reader(input.as_ref());
+        reader.set_strict(true);
 
         match reader.next() {
             Ok(Some(DerType::Sequence)) => {}
@@ -352,7 +353,7 @@ impl EcxKeyPair {
             if !is_public {
                 // Version
                 match reader.next() {
-                    Ok(Some(DerType::Integer)) => match reader.to_u8() {
+                    Ok(Some(DerType::Integer)) => match reader.to_u32() {
                         Ok(val) => {
                             if val != 0 {
                                 return None;
@@ -425,6 +426,10 @@ impl KeyPair for EcxKeyPair {
         }
     }
 
+    fn key_bits(&self) -> u32 {
+        self.private_key.bits()
+    }
+
     fn to_der_private_key(&self) -> Vec<u8> {
         self.private_key.private_key_to_der().unwrap()
     }
@@ -30,6 +30,37 @@ impl EdCurve {
             Self::Ed448 => &*OID_ED448,
         }
     }
+
+    /// Return the length in bytes of a private key (d) and a public key (x).
+    pub fn key_len(&self) -> usize {
+        match self {
+            Self::Ed25519 => 32,
+            Self::Ed448 => 57,
+        }
+    }
+
+    /// Return the curve of a OKP JWK crv parameter for EdDSA.
+    ///
+    /// The key agreement curves X25519 and X448 are rejected with a clear error.
+    ///
+    /// # Arguments
+    ///
+    /// * `jwk` - a OKP JWK
+    pub(crate) fn from_jwk(jwk: &Jwk) -> anyhow::Result<Self> {
+        match jwk.parameter("crv") {
+            Some(Value::String(val)) => match val.as_str() {
+                "Ed25519" => Ok(EdCurve::Ed25519),
+                "Ed448" => Ok(EdCurve::Ed448),
+                "X25519" | "X448" => bail!(
+                    "A parameter crv {} is for key agreement and cannot be used for EdDSA.",
+                    val
+                ),
+                _ => bail!("A parameter crv is unrecognized: {}", val),
+            },
+            Some(_) => bail!("A parameter crv must be a string."),
+            None => bail!("A parameter crv is required."),
+        }
+    }
 }
 
 impl Display for EdCurve {
@@ -170,26 +201,36 @@ impl EdKeyPair {
                 val if val == "OKP" => {}
                 val => bail!("A parameter kty must be OKP: {}", val),
             }
-            let curve = match jwk.parameter("crv") {
-                Some(Value::String(val)) => match val.as_str() {
-                    "Ed25519" => EdCurve::Ed25519,
-                    "Ed448" => EdCurve::Ed448,
-                    _ => bail!("A parameter crv is unrecognized: {}", val),
-                },
-                Some(_) => bail!("A parameter crv must be a string."),
-                None => bail!("A parameter crv is required."),
-            };
+            let curve = EdCurve::from_jwk(jwk)?;
             let d = match jwk.parameter("d") {
                 Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
                 Some(_) => bail!("A parameter d must be a string."),
                 None => bail!("A parameter d is required."),
             };
+            if d.len() != curve.key_len() {
+                bail!(
+                    "A parameter d of {} must be {} bytes: {}",
+                    curve,
+                    curve.key_len(),
+                    d.len()
+                );
+            }
 
             let mut builder = DerBuilder::new();
             builder.append_octed_string_from_bytes(&d);
 
             let pkcs8 = Self::to_pkcs8(&builder.build(), false, curve);
             let private_key = PKey::private_key_from_der(&pkcs8)?;
+            match jwk.parameter("x") {
+                Some(Value::String(val)) => {
+                    let x = base64::decode_config(val, base64::URL_SAFE_NO_PAD)?;
+                    if x != private_key.raw_public_key()? {
+                        bail!("A parameter x doesn't match the public key of the parameter d.");
+                    }
+                }
+                Some(_) => bail!("A parameter x must be a string."),
+                None => {}
+            }
             let algorithm = jwk.algorithm().map(|val| val.to_string());
             let key_id = jwk.key_id().map(|val| val.to_string());
 
@@ -250,7 +291,7 @@ impl EdKeyPair {
 
             match reader.next() {
                 Ok(Some(DerType::Integer)) => {
-                    if reader.to_u8().unwrap() != 0 {
+                    if reader.to_u32().unwrap() != 0 {
                         unreachable!("Invalid private key.");
                     }
                 }
@@ -341,6 +382,7 @@ impl EdKeyPair {
     pub(crate) fn detect_pkcs8(input: impl AsRef<[u8]>, is_public: bool) -> Option<EdCurve> {
         let curve;
         let mut reader = DerReader::from_reader(input.as_ref());
+        reader.set_strict(true);
 
         match reader.next() {
             Ok(Some(DerType::Sequence)) => {}
@@ -351,7 +393,7 @@ impl EdKeyPair {
             if !is_public {
                 // Version
                 match reader.next() {
-                    Ok(Some(DerType::Integer)) => match reader.to_u8() {
+                    Ok(Some(DerType::Integer)) => match reader.to_u32() {
                         Ok(val) => {
                             if val != 0 {
                                 return None;
@@ -424,6 +466,10 @@ impl KeyPair for EdKeyPair {
         }
     }
 
+    fn key_bits(&self) -> u32 {
+        self.private_key.bits()
+    }
+
     fn to_der_private_key(&self) -> Vec<u8> {
         self.private_key.private_key_to_der().unwrap()
     }
@@ -8,7 +8,16 @@ use crate::jwk::{alg::rsapss::RsaPssKeyPair, Jwk, KeyPair};
 use crate::util::der::{DerBuilder, DerReader, DerType};
 use crate::util::oid::OID_RSA_ENCRYPTION;
 use crate::util::{self, HashAlgorithm};
-use crate::{JoseError, Value};
+use crate::{JoseError, JwkParameterError, Value};
+
+/// The minimum length in bits of a RSA modulus that is accepted from a JWK.
+const MIN_JWK_MODULUS_BITS: usize = 512;
+
+/// The maximum length in bits of a RSA modulus that is accepted from a JWK.
+const MAX_JWK_MODULUS_BITS: usize = 16384;
+
+/// The maximum length in bytes of a RSA public exponent that is accepted from a JWK.
+const MAX_JWK_PUBLIC_EXPONENT_LEN: usize = 8;
 
 #[derive(Debug, Clone)]
 pub struct RsaKeyPair {
@@ -42,7 +51,7 @@ impl RsaKeyPair {
         self,
         hash: HashAlgorithm,
         mgf1_hash: HashAlgorithm,
-        salt_len: u8,
+        salt_len: u32,
     ) -> RsaPssKeyPair {
         RsaPssKeyPair::from_private_key(self.private_key, self.key_len, hash, mgf1_hash, salt_len)
     }
@@ -161,46 +170,67 @@ impl RsaKeyPair {
                 val => bail!("A parameter kty must be RSA: {}", val),
             }
             let n = match jwk.parameter("n") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter n must be a string."),
-                None => bail!("A parameter n is required."),
+                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                    .map_err(|err| JwkParameterError::decode_error("n", err))?,
+                Some(_) => bail!(JwkParameterError::wrong_type("n")),
+                None => bail!(JwkParameterError::missing("n")),
             };
             let e = match jwk.parameter("e") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter e must be a string."),
-                None => bail!("A parameter e is required."),
+                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                    .map_err(|err| JwkParameterError::decode_error("e", err))?,
+                Some(_) => bail!(JwkParameterError::wrong_type("e")),
+                None => bail!(JwkParameterError::missing("e")),
             };
             let d = match jwk.parameter("d") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter d must be a string."),
-                None => bail!("A parameter d is required."),
+                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                    .map_err(|err| JwkParameterError::decode_error("d", err))?,
+                Some(_) => bail!(JwkParameterError::wrong_type("d")),
+                None => bail!(JwkParameterError::missing("d")),
             };
             let p = match jwk.parameter("p") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter p must be a string."),
-                None => bail!("A parameter p is required."),
+                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                    .map_err(|err| JwkParameterError::decode_error("p", err))?,
+                Some(_) => bail!(JwkParameterError::wrong_type("p")),
+                None => bail!(JwkParameterError::missing("p")),
             };
             let q = match jwk.parameter("q") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter q must be a string."),
-                None => bail!("A parameter q is required."),
+                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                    .map_err(|err| JwkParameterError::decode_error("q", err))?,
+                Some(_) => bail!(JwkParameterError::wrong_type("q")),
+                None => bail!(JwkParameterError::missing("q")),
             };
             let dp = match jwk.parameter("dp") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter dp must be a string."),
-                None => bail!("A parameter dp is required."),
+                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                    .map_err(|err| JwkParameterError::decode_error("dp", err))?,
+                Some(_) => bail!(JwkParameterError::wrong_type("dp")),
+                None => bail!(JwkParameterError::missing("dp")),
             };
             let dq = match jwk.parameter("dq") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter dq must be a string."),
-                None => bail!("A parameter dq is required."),
+                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                    .map_err(|err| JwkParameterError::decode_error("dq", err))?,
+                Some(_) => bail!(JwkParameterError::wrong_type("dq")),
+                None => bail!(JwkParameterError::missing("dq")),
             };
             let qi = match jwk.parameter("qi") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter qi must be a string."),
-                None => bail!("A parameter qi is required."),
+                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                    .map_err(|err| JwkParameterError::decode_error("qi", err))?,
+                Some(_) => bail!(JwkParameterError::wrong_type("qi")),
+                None => bail!(JwkParameterError::missing("qi")),
             };
 
+            RsaKeyPair::check_jwk_lengths(
+                &n,
+                &e,
+                &[
+                    ("d", &d),
+                    ("p", &p),
+                    ("q", &q),
+                    ("dp", &dp),
+                    ("dq", &dq),
+                    ("qi", &qi),
+                ],
+            )?;
+
             let mut builder = DerBuilder::new();
             builder.begin(DerType::Sequence);
             {
@@ -233,6 +263,76 @@ impl RsaKeyPair {
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
+    /// Check the lengths of the decoded parameters of a RSA JWK are consistent:
+    /// the modulus (n) is 512 to 16384 bits, the public exponent (e) is a non-zero value
+    /// of at most 64 bits, and the private parameters are not longer than the modulus.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - the decoded modulus
+    /// * `e` - the decoded public exponent
+    /// * `private_params` - the names and decoded values of private parameters
+    pub(crate) fn check_jwk_lengths(
+        n: &[u8],
+        e: &[u8],
+        private_params: &[(&str, &[u8])],
+    ) -> Result<(), JwkParameterError> {
+        fn strip_leading_zeros(input: &[u8]) -> &[u8] {
+            let pos = input.iter().position(|b| *b != 0).unwrap_or(input.len());
+            &input[pos..]
+        }
+
+        let n = strip_leading_zeros(n);
+        let n_bits = match n.first() {
+            Some(val) => n.len() * 8 - val.leading_zeros() as usize,
+            None => 0,
+        };
+        if !(MIN_JWK_MODULUS_BITS..=MAX_JWK_MODULUS_BITS).contains(&n_bits) {
+            return Err(JwkParameterError::invalid_value(
+                "n",
+                anyhow::anyhow!(
+                    "The modulus must be {} to {} bits: {}",
+                    MIN_JWK_MODULUS_BITS,
+                    MAX_JWK_MODULUS_BITS,
+                    n_bits
+                ),
+            ));
+        }
+
+        let e = strip_leading_zeros(e);
+        if e.is_empty() {
+            return Err(JwkParameterError::invalid_value(
+                "e",
+                anyhow::anyhow!("The public exponent must not be zero."),
+            ));
+        } else if e.len() > MAX_JWK_PUBLIC_EXPONENT_LEN {
+            return Err(JwkParameterError::invalid_value(
+                "e",
+                anyhow::anyhow!(
+                    "The public exponent must be {} bytes or less: {}",
+                    MAX_JWK_PUBLIC_EXPONENT_LEN,
+                    e.len()
+                ),
+            ));
+        }
+
+        for (name, value) in private_params {
+            let value = strip_leading_zeros(value);
+            if value.len() > n.len() {
+                return Err(JwkParameterError::invalid_value(
+                    *name,
+                    anyhow::anyhow!(
+                        "The parameter must not be longer than the modulus: {} > {} bytes",
+                        value.len(),
+                        n.len()
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn to_raw_private_key(&self) -> Vec<u8> {
         let rsa = self.private_key.rsa().unwrap();
         rsa.private_key_to_der().unwrap()
@@ -302,6 +402,7 @@ impl RsaKeyPair {
 
     pub(crate) fn detect_pkcs8(input: impl AsRef<[u8]>, is_public: bool) -> Option<()> {
         let mut reader = DerReader::from_reader(input.as_ref());
+        reader.set_strict(true);
 
         match reader.next() {
             Ok(Some(DerType::Sequence)) => {}
@@ -312,7 +413,7 @@ impl RsaKeyPair {
             if !is_public {
                 // Version
                 match reader.next() {
-                    Ok(Some(DerType::Integer)) => match reader.to_u8() {
+                    Ok(Some(DerType::Integer)) => match reader.to_u32() {
                         Ok(val) => {
                             if val != 0 {
                                 return None;
@@ -394,6 +495,10 @@ impl KeyPair for RsaKeyPair {
         }
     }
 
+    fn key_bits(&self) -> u32 {
+        self.private_key.bits()
+    }
+
     fn to_der_private_key(&self) -> Vec<u8> {
         Self::to_pkcs8(&self.to_raw_private_key(), false)
     }
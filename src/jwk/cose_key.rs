@@ -0,0 +1,485 @@
+use std::collections::BTreeMap;
+
+use anyhow::bail;
+
+use crate::jwk::alg::ec::EcCurve;
+use crate::jwk::Jwk;
+use crate::util;
+use crate::util::cbor::CborValue;
+use crate::{JoseError, Value};
+
+const LABEL_KTY: i64 = 1;
+const LABEL_KID: i64 = 2;
+const LABEL_ALG: i64 = 3;
+const LABEL_KEY_OPS: i64 = 4;
+
+const KTY_OKP: i64 = 1;
+const KTY_EC2: i64 = 2;
+const KTY_RSA: i64 = 3;
+const KTY_SYMMETRIC: i64 = 4;
+
+const OKP_CURVES: [(i64, &str); 4] = [(4, "X25519"), (5, "X448"), (6, "Ed25519"), (7, "Ed448")];
+
+const EC2_CURVES: [(i64, EcCurve); 4] = [
+    (1, EcCurve::P256),
+    (2, EcCurve::P384),
+    (3, EcCurve::P521),
+    (8, EcCurve::Secp256k1),
+];
+
+const RSA_PARAMETERS: [(i64, &str); 8] = [
+    (-1, "n"),
+    (-2, "e"),
+    (-3, "d"),
+    (-4, "p"),
+    (-5, "q"),
+    (-6, "dp"),
+    (-7, "dq"),
+    (-8, "qi"),
+];
+
+const ALGORITHMS: [(i64, &str); 20] = [
+    (-7, "ES256"),
+    (-35, "ES384"),
+    (-36, "ES512"),
+    (-47, "ES256K"),
+    (-8, "EdDSA"),
+    (-37, "PS256"),
+    (-38, "PS384"),
+    (-39, "PS512"),
+    (-257, "RS256"),
+    (-258, "RS384"),
+    (-259, "RS512"),
+    (5, "HS256"),
+    (6, "HS384"),
+    (7, "HS512"),
+    (-3, "A128KW"),
+    (-4, "A192KW"),
+    (-5, "A256KW"),
+    (-6, "dir"),
+    (-40, "RSA-OAEP"),
+    (-41, "RSA-OAEP-256"),
+];
+
+const KEY_OPERATIONS: [(i64, &str); 8] = [
+    (1, "sign"),
+    (2, "verify"),
+    (3, "encrypt"),
+    (4, "decrypt"),
+    (5, "wrapKey"),
+    (6, "unwrapKey"),
+    (7, "deriveKey"),
+    (8, "deriveBits"),
+];
+
+/// Create a JWK from a COSE key (RFC 8152 Section 7).
+///
+/// The key types OKP, EC2, RSA and Symmetric are supported. The key ID,
+/// algorithm and key operations are carried over, and labels that have no
+/// JWK counterpart are ignored.
+///
+/// # Arguments
+///
+/// * `input` - A CBOR encoded COSE_Key structure.
+pub fn from_cose_key(input: impl AsRef<[u8]>) -> Result<Jwk, JoseError> {
+    (|| -> anyhow::Result<Jwk> {
+        let entries = match CborValue::decode(input.as_ref())? {
+            CborValue::Map(entries) => entries,
+            _ => bail!("A COSE key must be a CBOR map."),
+        };
+        let mut params = BTreeMap::new();
+        for (key, val) in entries {
+            if let CborValue::Integer(label) = key {
+                params.insert(label, val);
+            }
+        }
+
+        let mut jwk = match params.get(&LABEL_KTY) {
+            Some(CborValue::Integer(KTY_OKP)) => {
+                let crv = match integer_param(&params, -1, "crv")? {
+                    Some(val) => match OKP_CURVES.iter().find(|(label, _)| *label == val) {
+                        Some((_, name)) => *name,
+                        None => bail!("Unknown OKP curve: {}", val),
+                    },
+                    None => bail!("The key type OKP must have parameter crv (-1)."),
+                };
+
+                let mut jwk = Jwk::new("OKP");
+                jwk.set_curve(crv);
+                set_bytes(&mut jwk, "x", required_bytes_param(&params, -2, "x")?)?;
+                if let Some(d) = bytes_param(&params, -4, "d")? {
+                    set_bytes(&mut jwk, "d", d)?;
+                }
+                jwk
+            }
+            Some(CborValue::Integer(KTY_EC2)) => {
+                let curve = match integer_param(&params, -1, "crv")? {
+                    Some(val) => match EC2_CURVES.iter().find(|(label, _)| *label == val) {
+                        Some((_, curve)) => *curve,
+                        None => bail!("Unknown EC2 curve: {}", val),
+                    },
+                    None => bail!("The key type EC2 must have parameter crv (-1)."),
+                };
+
+                let x = required_bytes_param(&params, -2, "x")?;
+                let y = match params.get(&-3) {
+                    Some(CborValue::Bytes(val)) => val.clone(),
+                    // A sign bit of a compressed point.
+                    Some(CborValue::Bool(val)) => {
+                        let mut point = Vec::with_capacity(1 + x.len());
+                        point.push(if *val { 0x03 } else { 0x02 });
+                        point.extend_from_slice(x);
+                        let point = curve.decompress_point(&point)?;
+                        point[(1 + x.len())..].to_vec()
+                    }
+                    Some(_) => bail!("The parameter y (-3) must be a byte string or a bool."),
+                    None => bail!("The key type EC2 must have parameter y (-3)."),
+                };
+
+                match bytes_param(&params, -4, "d")? {
+                    Some(d) => Jwk::from_ec_private(curve, d, x, &y)?,
+                    None => Jwk::from_ec_public(curve, x, &y)?,
+                }
+            }
+            Some(CborValue::Integer(KTY_RSA)) => {
+                if params.contains_key(&-9) {
+                    bail!("Multi-prime RSA keys are not supported.");
+                }
+
+                let mut jwk = Jwk::new("RSA");
+                for (label, name) in &RSA_PARAMETERS {
+                    match bytes_param(&params, *label, name)? {
+                        Some(val) => set_bytes(&mut jwk, name, val)?,
+                        None if *label >= -2 => {
+                            bail!("The key type RSA must have parameter {} ({}).", name, label)
+                        }
+                        None => {}
+                    }
+                }
+                jwk
+            }
+            Some(CborValue::Integer(KTY_SYMMETRIC)) => {
+                let mut jwk = Jwk::new("oct");
+                jwk.set_key_value(required_bytes_param(&params, -1, "k")?);
+                jwk
+            }
+            Some(CborValue::Integer(val)) => bail!("Unknown COSE key type: {}", val),
+            Some(_) => bail!("The parameter kty (1) must be an integer."),
+            None => bail!("A COSE key must have parameter kty (1)."),
+        };
+
+        if let Some(kid) = bytes_param(&params, LABEL_KID, "kid")? {
+            match std::str::from_utf8(kid) {
+                Ok(val) => jwk.set_key_id(val),
+                Err(_) => bail!("The parameter kid (2) must be a UTF-8 string."),
+            }
+        }
+
+        match params.get(&LABEL_ALG) {
+            Some(CborValue::Integer(val)) => {
+                match ALGORITHMS.iter().find(|(label, _)| label == val) {
+                    Some((_, name)) => jwk.set_algorithm(*name),
+                    None => bail!("Unknown COSE algorithm: {}", val),
+                }
+            }
+            Some(CborValue::Text(val)) => jwk.set_algorithm(val.as_str()),
+            Some(_) => bail!("The parameter alg (3) must be an integer or a text string."),
+            None => {}
+        }
+
+        match params.get(&LABEL_KEY_OPS) {
+            Some(CborValue::Array(vals)) => {
+                let mut key_ops = Vec::with_capacity(vals.len());
+                for val in vals {
+                    let name = match val {
+                        CborValue::Integer(val) => {
+                            match KEY_OPERATIONS.iter().find(|(label, _)| label == val) {
+                                Some((_, name)) => *name,
+                                None => bail!("Unknown COSE key operation: {}", val),
+                            }
+                        }
+                        CborValue::Text(val) => val.as_str(),
+                        _ => bail!("The parameter key_ops (4) must contain integers."),
+                    };
+                    key_ops.push(name);
+                }
+                jwk.set_key_operations(key_ops);
+            }
+            Some(_) => bail!("The parameter key_ops (4) must be an array."),
+            None => {}
+        }
+
+        jwk.validate()?;
+        Ok(jwk)
+    })()
+    .map_err(|err| match err.downcast::<JoseError>() {
+        Ok(err) => err,
+        Err(err) => JoseError::InvalidKeyFormat(err),
+    })
+}
+
+pub(crate) fn to_cose_key(jwk: &Jwk) -> anyhow::Result<Vec<u8>> {
+    let mut entries = Vec::new();
+    match jwk.key_type() {
+        "OKP" => {
+            let crv = match jwk.curve() {
+                Some(val) => match OKP_CURVES.iter().find(|(_, name)| *name == val) {
+                    Some((label, _)) => *label,
+                    None => bail!("Unknown curve: {}", val),
+                },
+                None => bail!("The key type 'OKP' must have parameter 'crv'."),
+            };
+            entries.push(int_entry(LABEL_KTY, KTY_OKP));
+            entries.push(int_entry(-1, crv));
+            entries.push(bytes_entry(-2, required_jwk_bytes(jwk, "x")?));
+            if let Some(d) = jwk_bytes(jwk, "d")? {
+                entries.push(bytes_entry(-4, d));
+            }
+        }
+        "EC" => {
+            let crv = match jwk.curve() {
+                Some(val) => match EC2_CURVES.iter().find(|(_, curve)| curve.name() == val) {
+                    Some((label, _)) => *label,
+                    None => bail!("Unknown curve: {}", val),
+                },
+                None => bail!("The key type 'EC' must have parameter 'crv'."),
+            };
+            entries.push(int_entry(LABEL_KTY, KTY_EC2));
+            entries.push(int_entry(-1, crv));
+            entries.push(bytes_entry(-2, required_jwk_bytes(jwk, "x")?));
+            entries.push(bytes_entry(-3, required_jwk_bytes(jwk, "y")?));
+            if let Some(d) = jwk_bytes(jwk, "d")? {
+                entries.push(bytes_entry(-4, d));
+            }
+        }
+        "RSA" => {
+            if jwk.parameter("oth").is_some() {
+                bail!("Multi-prime RSA keys are not supported.");
+            }
+
+            entries.push(int_entry(LABEL_KTY, KTY_RSA));
+            for (label, name) in &RSA_PARAMETERS {
+                match jwk_bytes(jwk, name)? {
+                    Some(val) => entries.push(bytes_entry(*label, val)),
+                    None if *label >= -2 => {
+                        bail!("The key type 'RSA' must have parameter '{}'.", name)
+                    }
+                    None => {}
+                }
+            }
+        }
+        "oct" => {
+            entries.push(int_entry(LABEL_KTY, KTY_SYMMETRIC));
+            entries.push(bytes_entry(-1, required_jwk_bytes(jwk, "k")?));
+        }
+        val => bail!("Unsupported key type: {}", val),
+    }
+
+    if let Some(kid) = jwk.key_id() {
+        entries.push(bytes_entry(LABEL_KID, kid.as_bytes().to_vec()));
+    }
+
+    if let Some(alg) = jwk.algorithm() {
+        match ALGORITHMS.iter().find(|(_, name)| *name == alg) {
+            Some((label, _)) => entries.push(int_entry(LABEL_ALG, *label)),
+            None => bail!("The algorithm has no COSE identifier: {}", alg),
+        }
+    }
+
+    if let Some(key_ops) = jwk.key_operations() {
+        let mut vals = Vec::with_capacity(key_ops.len());
+        for key_op in key_ops {
+            match KEY_OPERATIONS.iter().find(|(_, name)| *name == key_op) {
+                Some((label, _)) => vals.push(CborValue::Integer(*label)),
+                None => bail!("The key operation has no COSE identifier: {}", key_op),
+            }
+        }
+        entries.push((CborValue::Integer(LABEL_KEY_OPS), CborValue::Array(vals)));
+    }
+
+    Ok(CborValue::Map(entries).encode())
+}
+
+fn integer_param(
+    params: &BTreeMap<i64, CborValue>,
+    label: i64,
+    name: &str,
+) -> anyhow::Result<Option<i64>> {
+    match params.get(&label) {
+        Some(CborValue::Integer(val)) => Ok(Some(*val)),
+        Some(_) => bail!("The parameter {} ({}) must be an integer.", name, label),
+        None => Ok(None),
+    }
+}
+
+fn bytes_param<'a>(
+    params: &'a BTreeMap<i64, CborValue>,
+    label: i64,
+    name: &str,
+) -> anyhow::Result<Option<&'a [u8]>> {
+    match params.get(&label) {
+        Some(CborValue::Bytes(val)) => Ok(Some(val)),
+        Some(_) => bail!("The parameter {} ({}) must be a byte string.", name, label),
+        None => Ok(None),
+    }
+}
+
+fn required_bytes_param<'a>(
+    params: &'a BTreeMap<i64, CborValue>,
+    label: i64,
+    name: &str,
+) -> anyhow::Result<&'a [u8]> {
+    match bytes_param(params, label, name)? {
+        Some(val) => Ok(val),
+        None => bail!("A COSE key must have parameter {} ({}).", name, label),
+    }
+}
+
+fn set_bytes(jwk: &mut Jwk, name: &str, value: &[u8]) -> Result<(), JoseError> {
+    let value = Value::String(util::encode_base64url(value));
+    jwk.set_parameter(name, Some(value))
+}
+
+fn jwk_bytes(jwk: &Jwk, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+    match jwk.parameter(name) {
+        Some(Value::String(val)) => Ok(Some(util::decode_base64url(val)?)),
+        Some(_) => bail!("The parameter '{}' must be a string.", name),
+        None => Ok(None),
+    }
+}
+
+fn required_jwk_bytes(jwk: &Jwk, name: &str) -> anyhow::Result<Vec<u8>> {
+    match jwk_bytes(jwk, name)? {
+        Some(val) => Ok(val),
+        None => bail!(
+            "The key type '{}' must have parameter '{}'.",
+            jwk.key_type(),
+            name
+        ),
+    }
+}
+
+fn int_entry(label: i64, value: i64) -> (CborValue, CborValue) {
+    (CborValue::Integer(label), CborValue::Integer(value))
+}
+
+fn bytes_entry(label: i64, value: Vec<u8>) -> (CborValue, CborValue) {
+    (CborValue::Integer(label), CborValue::Bytes(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::jwk::alg::ec::{EcCurve, EcKeyPair};
+    use crate::jwk::alg::ed::{EdCurve, EdKeyPair};
+    use crate::jwk::alg::rsa::RsaKeyPair;
+    use crate::jwk::{self, Jwk, KeyPair};
+    use crate::jws::{EdDSA, ES256, RS256};
+    use crate::util;
+    use crate::Value;
+
+    #[test]
+    fn test_webauthn_p256_cose_key() -> Result<()> {
+        let key_pair = EcKeyPair::generate(EcCurve::P256)?;
+        let public_jwk = key_pair.to_jwk_public_key();
+        let coordinate = |name: &str| -> Result<Vec<u8>> {
+            match public_jwk.parameter(name) {
+                Some(Value::String(val)) => Ok(util::decode_base64url(val)?),
+                _ => unreachable!(),
+            }
+        };
+
+        // A credential public key as found in WebAuthn authenticator data:
+        // {1: 2, 3: -7, -1: 1, -2: x, -3: y}
+        let mut cose_key = vec![0xA5, 0x01, 0x02, 0x03, 0x26, 0x20, 0x01, 0x21, 0x58, 0x20];
+        cose_key.extend_from_slice(&coordinate("x")?);
+        cose_key.extend_from_slice(&[0x22, 0x58, 0x20]);
+        cose_key.extend_from_slice(&coordinate("y")?);
+
+        let jwk = jwk::from_cose_key(&cose_key)?;
+        assert_eq!(jwk.key_type(), "EC");
+        assert_eq!(jwk.curve(), Some("P-256"));
+        assert_eq!(jwk.algorithm(), Some("ES256"));
+        assert!(jwk.public_key_eq(&public_jwk));
+
+        let signature = ES256
+            .signer_from_jwk(&key_pair.to_jwk_key_pair())?
+            .sign(b"abcde12345")?;
+        ES256
+            .verifier_from_jwk(&jwk)?
+            .verify(b"abcde12345", &signature)?;
+
+        assert_eq!(jwk.to_cose_key()?, cose_key);
+
+        // A compressed point with the sign bit of y.
+        let y = coordinate("y")?;
+        let mut compressed = cose_key[..(cose_key.len() - 35)].to_vec();
+        compressed.extend_from_slice(&[0x22, if y[31] & 1 == 1 { 0xF5 } else { 0xF4 }]);
+        assert!(jwk::from_cose_key(&compressed)?.public_key_eq(&public_jwk));
+
+        // A point that is not on the curve.
+        let mut invalid = cose_key.clone();
+        let last = invalid.len() - 1;
+        invalid[last] ^= 1;
+        assert!(jwk::from_cose_key(&invalid).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cose_key_round_trip() -> Result<()> {
+        let mut ed_jwk = EdKeyPair::generate(EdCurve::Ed25519)?.to_jwk_key_pair();
+        ed_jwk.set_key_id("ed-key");
+        ed_jwk.set_algorithm("EdDSA");
+        ed_jwk.set_key_operations(vec!["sign"]);
+        let rsa_jwk = RsaKeyPair::generate(2048)?.to_jwk_key_pair();
+        let ec_jwk = EcKeyPair::generate(EcCurve::P384)?.to_jwk_key_pair();
+        let oct_jwk = Jwk::generate_oct_key(32)?;
+
+        for jwk in &[ed_jwk, rsa_jwk, ec_jwk, oct_jwk] {
+            let decoded = jwk::from_cose_key(&jwk.to_cose_key()?)?;
+            // COSE keys have no counterpart of the public key use.
+            let mut expected = jwk.clone();
+            expected.set_parameter("use", None)?;
+            assert_eq!(decoded.to_string(), expected.to_string());
+        }
+
+        let ed_jwk = EdKeyPair::generate(EdCurve::Ed25519)?.to_jwk_key_pair();
+        let decoded = jwk::from_cose_key(&ed_jwk.to_public_key()?.to_cose_key()?)?;
+        let signature = EdDSA.signer_from_jwk(&ed_jwk)?.sign(b"abcde12345")?;
+        EdDSA
+            .verifier_from_jwk(&decoded)?
+            .verify(b"abcde12345", &signature)?;
+
+        let rsa_key_pair = RsaKeyPair::generate(2048)?;
+        let decoded = jwk::from_cose_key(&rsa_key_pair.to_jwk_public_key().to_cose_key()?)?;
+        assert!(!decoded.is_private());
+        let signature = RS256
+            .signer_from_jwk(&rsa_key_pair.to_jwk_key_pair())?
+            .sign(b"abcde12345")?;
+        RS256
+            .verifier_from_jwk(&decoded)?
+            .verify(b"abcde12345", &signature)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cose_key_invalid() -> Result<()> {
+        // not a map
+        assert!(jwk::from_cose_key([0x80]).is_err());
+        // missing kty
+        assert!(jwk::from_cose_key([0xA0]).is_err());
+        // unknown kty
+        assert!(jwk::from_cose_key([0xA1, 0x01, 0x09]).is_err());
+        // EC2 without coordinates
+        assert!(jwk::from_cose_key([0xA2, 0x01, 0x02, 0x20, 0x01]).is_err());
+
+        let mut jwk = Jwk::generate_oct_key(32)?;
+        jwk.set_algorithm("A128GCMKW");
+        assert!(jwk.to_cose_key().is_err());
+
+        Ok(())
+    }
+}
@@ -1,18 +1,31 @@
-use std::fmt::Display;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display};
 use std::io::Read;
 use std::string::ToString;
 
 use anyhow::bail;
+use openssl::hash;
+use openssl::pkey::PKey;
 
 use crate::jwk::alg::ec::{EcCurve, EcKeyPair};
 use crate::jwk::alg::ecx::{EcxCurve, EcxKeyPair};
 use crate::jwk::alg::ed::{EdCurve, EdKeyPair};
 use crate::jwk::alg::rsa::RsaKeyPair;
+use crate::jwk::cose_key;
+use crate::jwk::KeyStrengthPolicy;
 use crate::util;
+use crate::util::HashAlgorithm;
 use crate::{JoseError, Map, Value};
 
+/// The JWK parameters that hold private or secret key material.
+const SECRET_PARAMETERS: [&str; 8] = ["d", "p", "q", "dp", "dq", "qi", "oth", "k"];
+
 /// Represents JWK object.
-#[derive(Debug, Eq, PartialEq, Clone)]
+///
+/// The Debug output redacts the private and secret parameters
+/// (d, p, q, dp, dq, qi, oth and k). The Display output is the full JSON
+/// with the members sorted by name, so equal JWKs are serialized to the same bytes.
+#[derive(Eq, PartialEq, Clone)]
 pub struct Jwk {
     map: Map<String, Value>,
 }
@@ -62,7 +75,7 @@ impl Jwk {
     /// # Arguments
     /// * `key_len` - A key byte length
     pub fn generate_oct_key(key_len: u8) -> Result<Self, JoseError> {
-        let k = util::random_bytes(key_len as usize);
+        let k = util::try_random_bytes(key_len as usize)?;
 
         let mut jwk = Self::new("oct");
         jwk.map.insert(
@@ -108,6 +121,73 @@ impl Jwk {
         Ok(key_pair.to_jwk_key_pair())
     }
 
+    /// Create a EC type public JWK from raw affine coordinates.
+    ///
+    /// # Arguments
+    /// * `curve` - A EC curve algorithm
+    /// * `x` - A big-endian x coordinate padded to the field size
+    /// * `y` - A big-endian y coordinate padded to the field size
+    pub fn from_ec_public(curve: EcCurve, x: &[u8], y: &[u8]) -> Result<Self, JoseError> {
+        (|| -> anyhow::Result<Jwk> {
+            let size = curve.coordinate_size();
+            if x.len() != size {
+                bail!("The length of x must be {}: {}", size, x.len());
+            }
+            if y.len() != size {
+                bail!("The length of y must be {}: {}", size, y.len());
+            }
+
+            let mut point = Vec::with_capacity(1 + x.len() + y.len());
+            point.push(0x04);
+            point.extend_from_slice(x);
+            point.extend_from_slice(y);
+            let spki = EcKeyPair::to_pkcs8(&point, true, curve);
+            PKey::public_key_from_der(&spki)?;
+
+            let mut jwk = Jwk::new("EC");
+            jwk.set_curve(curve.name());
+            jwk.map
+                .insert("x".to_string(), Value::String(util::encode_base64url(x)));
+            jwk.map
+                .insert("y".to_string(), Value::String(util::encode_base64url(y)));
+            Ok(jwk)
+        })()
+        .map_err(JoseError::InvalidKeyFormat)
+    }
+
+    /// Create a EC type private JWK from raw private scalar and affine coordinates.
+    ///
+    /// # Arguments
+    /// * `curve` - A EC curve algorithm
+    /// * `d` - A big-endian private scalar padded to the field size
+    /// * `x` - A big-endian x coordinate padded to the field size
+    /// * `y` - A big-endian y coordinate padded to the field size
+    pub fn from_ec_private(
+        curve: EcCurve,
+        d: &[u8],
+        x: &[u8],
+        y: &[u8],
+    ) -> Result<Self, JoseError> {
+        let mut jwk = Self::from_ec_public(curve, x, y)?;
+        (|| -> anyhow::Result<()> {
+            let size = curve.coordinate_size();
+            if d.len() != size {
+                bail!("The length of d must be {}: {}", size, d.len());
+            }
+
+            let point = curve.public_point(d)?;
+            if point[1..] != [x, y].concat()[..] {
+                bail!("The private key does not match the public key.");
+            }
+            Ok(())
+        })()
+        .map_err(JoseError::InvalidKeyFormat)?;
+
+        jwk.map
+            .insert("d".to_string(), Value::String(util::encode_base64url(d)));
+        Ok(jwk)
+    }
+
     /// Generate private key from private key.
     pub fn to_public_key(&self) -> Result<Self, JoseError> {
         (|| -> anyhow::Result<Jwk> {
@@ -210,6 +290,185 @@ impl Jwk {
         .map_err(|err| JoseError::InvalidJwkFormat(err))
     }
 
+    /// Return whether this JWK contains private key material:
+    /// the parameter "d" for RSA, EC and OKP keys, or "k" for oct keys.
+    pub fn is_private(&self) -> bool {
+        match self.key_type() {
+            "oct" => self.map.contains_key("k"),
+            _ => self.map.contains_key("d"),
+        }
+    }
+
+    /// Return a copy of this JWK without private key parameters.
+    ///
+    /// Unlike to_public_key, the other parameters (e.g. kid, alg, x5c) are kept.
+    /// An oct key loses its key value "k" and is no longer usable.
+    pub fn to_public(&self) -> Jwk {
+        let private_params: &[&str] = match self.key_type() {
+            "oct" => &["k"],
+            "RSA" => &["d", "p", "q", "dp", "dq", "qi", "oth"],
+            _ => &["d"],
+        };
+
+        let mut jwk = self.clone();
+        for key in private_params {
+            jwk.map.remove(*key);
+        }
+        jwk
+    }
+
+    /// Validate the key length is strong enough for the algorithm parameter (alg)
+    /// under the specified policy.
+    ///
+    /// If the alg parameter is missing or the policy has no minimum key length for it,
+    /// the validation succeeds. The key length is checked for RSA and oct keys.
+    ///
+    /// # Arguments
+    /// * `policy` - A policy of the minimum key length for each algorithm
+    pub fn validate_alg_key_strength(&self, policy: &KeyStrengthPolicy) -> Result<(), JoseError> {
+        (|| -> anyhow::Result<()> {
+            let alg = match self.algorithm() {
+                Some(val) => val,
+                None => return Ok(()),
+            };
+            let min_key_bits = match policy.min_key_bits(alg) {
+                Some(val) => val,
+                None => return Ok(()),
+            };
+
+            let key_name = match self.key_type() {
+                "RSA" => "n",
+                "oct" => "k",
+                _ => return Ok(()),
+            };
+            let key = match self.map.get(key_name) {
+                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
+                Some(_) => bail!("The JWK {} parameter must be a string.", key_name),
+                None => bail!("The JWK {} parameter is required.", key_name),
+            };
+
+            let key_bits = if key_name == "n" {
+                match key.iter().position(|b| *b != 0) {
+                    Some(pos) => (key.len() - pos) as u32 * 8 - key[pos].leading_zeros(),
+                    None => 0,
+                }
+            } else {
+                key.len() as u32 * 8
+            };
+
+            if key_bits < min_key_bits {
+                bail!(
+                    "The key length for {} must be {} bits or more: {}",
+                    alg,
+                    min_key_bits,
+                    key_bits
+                );
+            }
+
+            Ok(())
+        })()
+        .map_err(JoseError::InvalidKeyFormat)
+    }
+
+    /// Return the JWK thumbprint (RFC 7638) of this key as a base64url string.
+    ///
+    /// The thumbprint is computed from the required public parameters of the key type,
+    /// so a private key and its public key have the same thumbprint.
+    ///
+    /// # Arguments
+    /// * `hash_algorithm` - A hash algorithm to compute the thumbprint
+    pub fn thumbprint(&self, hash_algorithm: HashAlgorithm) -> Result<String, JoseError> {
+        (|| -> anyhow::Result<String> {
+            let required_keys: &[&str] = match self.key_type() {
+                "oct" => &["k"],
+                "RSA" => &["e", "n"],
+                "EC" => &["crv", "x", "y"],
+                "OKP" => &["crv", "x"],
+                val => bail!("Unknown key type: {}", val),
+            };
+
+            let mut members = BTreeMap::new();
+            members.insert("kty", Value::String(self.key_type().to_string()));
+            for key in required_keys {
+                match self.map.get(*key) {
+                    Some(Value::String(val)) => {
+                        members.insert(key, Value::String(val.clone()));
+                    }
+                    Some(_) => bail!("The JWK {} parameter must be a string.", key),
+                    None => bail!("The JWK {} parameter is required.", key),
+                }
+            }
+
+            let json = serde_json::to_vec(&members)?;
+            let digest = hash::hash(hash_algorithm.message_digest(), &json)?;
+            Ok(base64::encode_config(digest, base64::URL_SAFE_NO_PAD))
+        })()
+        .map_err(JoseError::InvalidJwkFormat)
+    }
+
+    /// Set the SHA-256 JWK thumbprint (RFC 7638) to the key ID parameter (kid)
+    /// if the parameter is missing.
+    pub fn ensure_key_id(&mut self) -> Result<(), JoseError> {
+        if self.key_id().is_none() {
+            let thumbprint = self.thumbprint(HashAlgorithm::Sha256)?;
+            self.set_key_id(thumbprint);
+        }
+        Ok(())
+    }
+
+    /// Return whether this JWK and the other JWK have the same public key.
+    ///
+    /// Only the public key parameters are compared: n and e for RSA keys,
+    /// crv, x and y for EC keys, and crv and x for OKP keys. The other parameters
+    /// (e.g. kid, use, alg) are ignored. Leading zero octets of n and e are not
+    /// significant. A key of another type or a key missing a parameter is never equal.
+    ///
+    /// # Arguments
+    /// * `other` - A JWK to be compared
+    pub fn public_key_eq(&self, other: &Jwk) -> bool {
+        if self.key_type() != other.key_type() {
+            return false;
+        }
+
+        let (integer_keys, octet_keys): (&[&str], &[&str]) = match self.key_type() {
+            "RSA" => (&["n", "e"], &[]),
+            "EC" => (&[], &["x", "y"]),
+            "OKP" => (&[], &["x"]),
+            _ => return false,
+        };
+
+        if self.key_type() != "RSA" {
+            match (self.curve(), other.curve()) {
+                (Some(val1), Some(val2)) if val1 == val2 => {}
+                _ => return false,
+            }
+        }
+
+        let decode = |jwk: &Jwk, key: &str| match jwk.map.get(key) {
+            Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD).ok(),
+            _ => None,
+        };
+
+        let trim =
+            |val: &[u8]| -> Vec<u8> { val.iter().skip_while(|b| **b == 0).copied().collect() };
+
+        for key in integer_keys {
+            match (decode(self, key), decode(other, key)) {
+                (Some(val1), Some(val2)) if trim(&val1) == trim(&val2) => {}
+                _ => return false,
+            }
+        }
+
+        for key in octet_keys {
+            match (decode(self, key), decode(other, key)) {
+                (Some(val1), Some(val2)) if val1 == val2 => {}
+                _ => return false,
+            }
+        }
+
+        true
+    }
+
     /// Set a value for a key type parameter (kty).
     ///
     /// # Arguments
@@ -469,7 +728,7 @@ impl Jwk {
     pub fn set_parameter(&mut self, key: &str, value: Option<Value>) -> Result<(), JoseError> {
         match value {
             Some(val) => {
-                Self::check_parameter(key, &val)?;
+                Self::check_parameter(self.key_type(), key, &val)?;
                 self.map.insert(key.to_string(), val);
             }
             None => {
@@ -497,10 +756,40 @@ impl Jwk {
         self.map.get(key)
     }
 
+    /// Return the JSON document of this JWK as bytes.
+    ///
+    /// The members are sorted by name, so the output is stable for equal JWKs.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.to_string().into_bytes()
+    }
+
+    /// Return the COSE key (RFC 8152 Section 7) of this JWK encoded in CBOR.
+    ///
+    /// The key types RSA, EC, OKP and oct are supported. The key ID, algorithm
+    /// and key operations are carried over, and parameters that have no COSE
+    /// counterpart such as use or x5c are dropped.
+    pub fn to_cose_key(&self) -> Result<Vec<u8>, JoseError> {
+        cose_key::to_cose_key(self).map_err(JoseError::InvalidJwkFormat)
+    }
+
+    /// Validate the consistency of the parameters of this JWK.
+    ///
+    /// The public key use (use) and the key operations (key_ops) must not conflict
+    /// when both are present (RFC 7517 Section 4.3), and key_ops must not contain
+    /// duplicate values. This validation is also applied when a JWK is parsed.
+    pub fn validate(&self) -> Result<(), JoseError> {
+        Self::check_key_use_and_operations(&self.map)
+    }
+
     pub(crate) fn check_map(map: &Map<String, Value>) -> Result<(), JoseError> {
+        let key_type = match map.get("kty") {
+            Some(Value::String(val)) => val.as_str(),
+            _ => "",
+        };
         for (key, value) in map {
-            Self::check_parameter(key, value)?;
+            Self::check_parameter(key_type, key, value)?;
         }
+        Self::check_key_use_and_operations(map)?;
 
         (|| -> anyhow::Result<()> {
             if !map.contains_key("kty") {
@@ -511,9 +800,55 @@ impl Jwk {
         .map_err(|err| JoseError::InvalidJwsFormat(err))
     }
 
-    fn check_parameter(key: &str, value: &Value) -> Result<(), JoseError> {
+    fn check_key_use_and_operations(map: &Map<String, Value>) -> Result<(), JoseError> {
+        (|| -> anyhow::Result<()> {
+            let key_ops = match map.get("key_ops") {
+                Some(Value::Array(vals)) => vals,
+                _ => return Ok(()),
+            };
+
+            let mut checked = Vec::with_capacity(key_ops.len());
+            for key_op in key_ops {
+                let key_op = match key_op {
+                    Value::String(val) => val.as_str(),
+                    _ => continue,
+                };
+                if checked.contains(&key_op) {
+                    bail!(
+                        "The JWK key_ops parameter has a duplicate value: {}",
+                        key_op
+                    );
+                }
+                checked.push(key_op);
+
+                let expected_use = match key_op {
+                    "sign" | "verify" => "sig",
+                    "encrypt" | "decrypt" | "wrapKey" | "unwrapKey" | "deriveKey"
+                    | "deriveBits" => "enc",
+                    _ => continue,
+                };
+                if let Some(Value::String(val)) = map.get("use") {
+                    if (val == "sig" || val == "enc") && val != expected_use {
+                        bail!(
+                            "The JWK key_ops parameter is inconsistent with use {}: {}",
+                            val,
+                            key_op
+                        );
+                    }
+                }
+            }
+
+            Ok(())
+        })()
+        .map_err(JoseError::InvalidJwkFormat)
+    }
+
+    fn check_parameter(key_type: &str, key: &str, value: &Value) -> Result<(), JoseError> {
         (|| -> anyhow::Result<()> {
             match key {
+                // The key type specific parameters of an unknown key type are kept as is.
+                "k" | "d" | "p" | "q" | "dp" | "dq" | "qi" | "x" | "y"
+                    if !matches!(key_type, "oct" | "RSA" | "EC" | "OKP") => {}
                 "kty" | "use" | "alg" | "kid" | "x5u" | "crv" => match &value {
                     Value::String(_) => {}
                     _ => bail!("The JWK {} parameter must be a string.", key),
@@ -581,9 +916,315 @@ impl Into<Map<String, Value>> for Jwk {
     }
 }
 
+impl Debug for Jwk {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        struct RedactedMap<'a>(&'a Map<String, Value>);
+
+        impl Debug for RedactedMap<'_> {
+            fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let mut map = fmt.debug_map();
+                for (key, value) in self.0 {
+                    if SECRET_PARAMETERS.contains(&key.as_str()) {
+                        map.entry(key, &format_args!("[REDACTED]"));
+                    } else {
+                        map.entry(key, value);
+                    }
+                }
+                map.finish()
+            }
+        }
+
+        fmt.debug_struct("Jwk")
+            .field("map", &RedactedMap(&self.map))
+            .finish()
+    }
+}
+
 impl Display for Jwk {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        let val = serde_json::to_string(&self.map).map_err(|_e| std::fmt::Error {})?;
+        let val = util::to_sorted_json_string(&self.map).map_err(|_e| std::fmt::Error {})?;
         fmt.write_str(&val)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use serde_json::json;
+
+    use crate::jwe::Dir;
+    use crate::jwk::alg::ec::{EcCurve, EcKeyPair};
+    use crate::jwk::alg::ed::EdCurve;
+    use crate::jwk::alg::rsa::RsaKeyPair;
+    use crate::jwk::{Jwk, JwkSet, KeyPair};
+    use crate::jws::{ES256, HS256};
+    use crate::util::{self, HashAlgorithm};
+    use crate::Value;
+
+    #[test]
+    fn test_validate_key_use_and_operations() -> Result<()> {
+        let result = Jwk::from_bytes(
+            json!({
+                "kty": "oct",
+                "use": "enc",
+                "key_ops": ["sign"],
+                "k": "MDEyMzQ1Njc4OUFCQ0RFRjAxMjM0NTY3ODlBQkNERUY"
+            })
+            .to_string(),
+        );
+        assert!(result.is_err());
+
+        let result = Jwk::from_bytes(
+            json!({
+                "kty": "oct",
+                "use": "sig",
+                "key_ops": ["encrypt", "decrypt"],
+                "k": "MDEyMzQ1Njc4OUFCQ0RFRjAxMjM0NTY3ODlBQkNERUY"
+            })
+            .to_string(),
+        );
+        assert!(result.is_err());
+
+        let result = Jwk::from_bytes(
+            json!({
+                "kty": "oct",
+                "key_ops": ["sign", "sign"],
+                "k": "MDEyMzQ1Njc4OUFCQ0RFRjAxMjM0NTY3ODlBQkNERUY"
+            })
+            .to_string(),
+        );
+        assert!(result.is_err());
+
+        let jwk = Jwk::from_bytes(
+            json!({
+                "kty": "oct",
+                "use": "sig",
+                "key_ops": ["sign", "verify"],
+                "k": "MDEyMzQ1Njc4OUFCQ0RFRjAxMjM0NTY3ODlBQkNERUY"
+            })
+            .to_string(),
+        )?;
+        jwk.validate()?;
+
+        let mut jwk = jwk;
+        jwk.set_key_operations(vec!["wrapKey"]);
+        assert!(jwk.validate().is_err());
+        jwk.set_key_use("enc");
+        jwk.validate()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_thumbprint() -> Result<()> {
+        // RFC 7638 3.1. Example JWK Thumbprint Computation
+        let jwk = Jwk::from_bytes(
+            json!({
+                "kty": "RSA",
+                "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+                "e": "AQAB",
+                "alg": "RS256",
+                "kid": "2011-04-29"
+            })
+            .to_string(),
+        )?;
+        assert_eq!(
+            jwk.thumbprint(HashAlgorithm::Sha256)?,
+            "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+        );
+
+        assert!(Jwk::new("unknown")
+            .thumbprint(HashAlgorithm::Sha256)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_private_and_to_public() -> Result<()> {
+        let mut rsa_jwk = RsaKeyPair::generate(2048)?.to_jwk_key_pair();
+        rsa_jwk.set_key_id("rsa-key");
+        assert!(rsa_jwk.is_private());
+        let public_jwk = rsa_jwk.to_public();
+        assert!(!public_jwk.is_private());
+        for key in &["d", "p", "q", "dp", "dq", "qi"] {
+            assert!(rsa_jwk.parameter(key).is_some(), "{}", key);
+            assert!(public_jwk.parameter(key).is_none(), "{}", key);
+        }
+        assert_eq!(public_jwk.parameter("n"), rsa_jwk.parameter("n"));
+        assert_eq!(public_jwk.parameter("e"), rsa_jwk.parameter("e"));
+        assert_eq!(public_jwk.key_id(), Some("rsa-key"));
+
+        let ec_jwk = EcKeyPair::generate(EcCurve::P256)?.to_jwk_key_pair();
+        assert!(ec_jwk.is_private());
+        let public_jwk = ec_jwk.to_public();
+        assert!(!public_jwk.is_private());
+        assert_eq!(public_jwk.parameter("d"), None);
+        assert_eq!(public_jwk.parameter("x"), ec_jwk.parameter("x"));
+        assert_eq!(public_jwk, ec_jwk.to_public_key()?);
+
+        let oct_jwk = Jwk::generate_oct_key(32)?;
+        assert!(oct_jwk.is_private());
+        assert!(!oct_jwk.to_public().is_private());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ensure_key_id() -> Result<()> {
+        let mut private_jwk = RsaKeyPair::generate(2048)?.to_jwk_private_key();
+        assert_eq!(private_jwk.key_id(), None);
+        private_jwk.ensure_key_id()?;
+        let thumbprint = private_jwk.thumbprint(HashAlgorithm::Sha256)?;
+        assert_eq!(private_jwk.key_id(), Some(thumbprint.as_str()));
+
+        let mut public_jwk = private_jwk.to_public_key()?;
+        public_jwk.ensure_key_id()?;
+        assert_eq!(public_jwk.key_id(), Some(thumbprint.as_str()));
+
+        let mut ec_jwk = EcKeyPair::generate(EcCurve::P256)?.to_jwk_key_pair();
+        ec_jwk.set_key_id("my-key");
+        ec_jwk.ensure_key_id()?;
+        assert_eq!(ec_jwk.key_id(), Some("my-key"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_public_key_eq() -> Result<()> {
+        let rsa_jwk = RsaKeyPair::generate(2048)?.to_jwk_key_pair();
+        let mut public_jwk = rsa_jwk.to_public_key()?;
+        public_jwk.set_key_id("rotated-key");
+        public_jwk.set_key_use("sig");
+        public_jwk.set_algorithm("RS256");
+        assert!(rsa_jwk.public_key_eq(&public_jwk));
+        assert!(public_jwk.public_key_eq(&rsa_jwk));
+
+        let mut other_jwk = RsaKeyPair::generate(2048)?.to_jwk_public_key();
+        assert!(!rsa_jwk.public_key_eq(&other_jwk));
+        other_jwk.set_parameter("e", rsa_jwk.parameter("e").cloned())?;
+        assert!(!rsa_jwk.public_key_eq(&other_jwk));
+
+        let ec_jwk = EcKeyPair::generate(EcCurve::P256)?.to_jwk_key_pair();
+        let mut public_jwk = ec_jwk.to_public_key()?;
+        public_jwk.set_key_id("ec-key");
+        assert!(ec_jwk.public_key_eq(&public_jwk));
+        assert!(!ec_jwk.public_key_eq(&rsa_jwk));
+        let other_jwk = EcKeyPair::generate(EcCurve::P256)?.to_jwk_public_key();
+        assert!(!ec_jwk.public_key_eq(&other_jwk));
+
+        let ed_jwk = Jwk::generate_ed_key(EdCurve::Ed25519)?;
+        let mut public_jwk = ed_jwk.to_public_key()?;
+        public_jwk.set_key_id("ed-key");
+        assert!(ed_jwk.public_key_eq(&public_jwk));
+        public_jwk.set_curve("Ed448");
+        assert!(!ed_jwk.public_key_eq(&public_jwk));
+
+        let oct_jwk = Jwk::generate_oct_key(32)?;
+        assert!(!oct_jwk.public_key_eq(&oct_jwk));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_ec_coordinates() -> Result<()> {
+        let key_pair = EcKeyPair::generate(EcCurve::P256)?;
+        let generated = key_pair.to_jwk_key_pair();
+        let param = |name: &str| -> Result<Vec<u8>> {
+            match generated.parameter(name) {
+                Some(Value::String(val)) => Ok(util::decode_base64url(val)?),
+                _ => unreachable!(),
+            }
+        };
+        let (d, x, y) = (param("d")?, param("x")?, param("y")?);
+
+        let public_jwk = Jwk::from_ec_public(EcCurve::P256, &x, &y)?;
+        assert_eq!(public_jwk.key_type(), "EC");
+        assert_eq!(public_jwk.curve(), Some("P-256"));
+        assert!(!public_jwk.is_private());
+        assert!(public_jwk.public_key_eq(&generated));
+
+        let signer = ES256.signer_from_jwk(&generated)?;
+        let signature = signer.sign(b"abcde12345")?;
+        let verifier = ES256.verifier_from_jwk(&public_jwk)?;
+        verifier.verify(b"abcde12345", &signature)?;
+
+        let private_jwk = Jwk::from_ec_private(EcCurve::P256, &d, &x, &y)?;
+        assert!(private_jwk.is_private());
+        let signature = ES256.signer_from_jwk(&private_jwk)?.sign(b"abcde12345")?;
+        verifier.verify(b"abcde12345", &signature)?;
+
+        assert!(Jwk::from_ec_public(EcCurve::P256, &x[1..], &y).is_err());
+        assert!(Jwk::from_ec_public(EcCurve::P384, &x, &y).is_err());
+        let mut bad_y = y.clone();
+        bad_y[31] ^= 1;
+        assert!(Jwk::from_ec_public(EcCurve::P256, &x, &bad_y).is_err());
+
+        let other = Jwk::generate_ec_key(EcCurve::P256)?;
+        let other_d = match other.parameter("d") {
+            Some(Value::String(val)) => util::decode_base64url(val)?,
+            _ => unreachable!(),
+        };
+        assert!(Jwk::from_ec_private(EcCurve::P256, &other_d, &x, &y).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stable_serialization() -> Result<()> {
+        let jwk = RsaKeyPair::generate(2048)?.to_jwk_key_pair();
+        assert_eq!(jwk.to_vec(), jwk.to_vec());
+        assert_eq!(jwk.to_vec(), Jwk::from_bytes(jwk.to_vec())?.to_vec());
+
+        let jwk1 =
+            Jwk::from_bytes(br#"{"kty":"oct","kid":"key-1","k":"AAECAwQFBgc","use":"sig"}"#)?;
+        let jwk2 =
+            Jwk::from_bytes(br#"{"use":"sig","k":"AAECAwQFBgc","kid":"key-1","kty":"oct"}"#)?;
+        assert_eq!(jwk1, jwk2);
+        assert_eq!(jwk1.to_vec(), jwk2.to_vec());
+        assert_eq!(
+            jwk1.to_string(),
+            r#"{"k":"AAECAwQFBgc","kid":"key-1","kty":"oct","use":"sig"}"#
+        );
+
+        let jwk_set1: JwkSet = vec![jwk1].into_iter().collect();
+        let jwk_set2: JwkSet = vec![jwk2].into_iter().collect();
+        assert_eq!(jwk_set1.to_vec(), jwk_set2.to_vec());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_redacts_secret_parameters() -> Result<()> {
+        let rsa_jwk = RsaKeyPair::generate(2048)?.to_jwk_key_pair();
+        let debug = format!("{:?}", rsa_jwk);
+        for key in &["d", "p", "q", "dp", "dq", "qi"] {
+            match rsa_jwk.parameter(key) {
+                Some(Value::String(val)) => assert!(!debug.contains(val.as_str())),
+                _ => unreachable!(),
+            }
+        }
+        assert!(debug.contains("[REDACTED]"));
+        match rsa_jwk.parameter("n") {
+            Some(Value::String(val)) => assert!(debug.contains(val.as_str())),
+            _ => unreachable!(),
+        }
+
+        let oct_jwk = Jwk::generate_oct_key(32)?;
+        let k = util::encode_base64url(oct_jwk.key_value().unwrap());
+        assert!(!format!("{:?}", oct_jwk).contains(&k));
+        assert!(format!("{}", oct_jwk).contains(&k));
+
+        let jwk_set: JwkSet = vec![rsa_jwk, oct_jwk.clone()].into_iter().collect();
+        assert!(!format!("{:?}", jwk_set).contains(&k));
+
+        let signer = HS256.signer_from_jwk(&oct_jwk)?;
+        assert!(format!("{:?}", signer).contains("[REDACTED]"));
+        let encrypter = Dir.encrypter_from_jwk(&oct_jwk)?;
+        let debug = format!("{:?}", encrypter);
+        assert!(debug.contains("[REDACTED]"));
+        assert!(!debug.contains(&format!("{:?}", oct_jwk.key_value().unwrap())));
+
+        Ok(())
+    }
+}
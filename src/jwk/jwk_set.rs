@@ -1,17 +1,21 @@
 use std::collections::BTreeMap;
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 use std::io::Read;
+use std::iter::FromIterator;
 use std::ops::Bound::Included;
 use std::string::ToString;
 use std::sync::Arc;
 
 use anyhow::bail;
 
-use crate::jwk::Jwk;
+use crate::jwk::{self, Jwk};
+use crate::util;
 use crate::{JoseError, Map, Value};
 
 /// Represents JWK set.
-#[derive(Debug, Eq, PartialEq, Clone)]
+///
+/// The Debug output shows the keys with their private and secret parameters redacted.
+#[derive(Eq, PartialEq, Clone)]
 pub struct JwkSet {
     keys: Vec<Arc<Jwk>>,
     params: Map<String, Value>,
@@ -20,9 +24,12 @@ pub struct JwkSet {
 
 impl JwkSet {
     pub fn new() -> Self {
+        let mut params = Map::new();
+        params.insert("keys".to_string(), Value::Array(Vec::new()));
+
         Self {
             keys: Vec::new(),
-            params: Map::new(),
+            params,
             kid_map: BTreeMap::new(),
         }
     }
@@ -87,6 +94,36 @@ impl JwkSet {
         })
     }
 
+    /// Return a JWK set of the public keys in concatenated PEM blocks
+    /// (PUBLIC KEY or RSA PUBLIC KEY) and the errors of the skipped blocks.
+    ///
+    /// The key type and, if the key determines it, the algorithm are inferred from
+    /// each key, and the key ID is set to the SHA-256 JWK thumbprint (RFC 7638).
+    /// A block that cannot be parsed (e.g. a certificate or a private key) is skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - public keys of PEM format.
+    pub fn from_pem_bundle(input: impl AsRef<[u8]>) -> (Self, Vec<JoseError>) {
+        let mut jwk_set = Self::new();
+        let mut errors = Vec::new();
+
+        let blocks = util::parse_pem_blocks(input.as_ref());
+        for (index, block) in blocks.into_iter().enumerate() {
+            match block.and_then(|(_, data)| jwk::public_key_jwk_from_der(&data)) {
+                Ok(mut jwk) => match jwk.ensure_key_id() {
+                    Ok(()) => jwk_set.push_key(jwk),
+                    Err(err) => errors.push(err),
+                },
+                Err(err) => errors.push(JoseError::InvalidKeyFormat(
+                    err.context(format!("The PEM block {} is skipped.", index)),
+                )),
+            }
+        }
+
+        (jwk_set, errors)
+    }
+
     pub fn get(&self, key_id: &str) -> Vec<&Jwk> {
         let mut vec = Vec::new();
         for (_, val) in self.kid_map.range((
@@ -119,6 +156,27 @@ impl JwkSet {
         self.keys.push(jwk);
     }
 
+    /// Return the key IDs that are shared by two or more keys.
+    ///
+    /// Duplicate key IDs are allowed, for example while a key is rotated,
+    /// but a caller publishing the set may want to warn about them.
+    pub fn duplicate_key_ids(&self) -> Vec<&str> {
+        let mut vec: Vec<&str> = Vec::new();
+        let mut prev = None;
+        for (kid, _) in self.kid_map.keys() {
+            if prev == Some(kid.as_str()) && vec.last() != Some(&kid.as_str()) {
+                vec.push(kid);
+            }
+            prev = Some(kid.as_str());
+        }
+        vec
+    }
+
+    /// Return the JSON document of this JWK set as bytes.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.to_string().into_bytes()
+    }
+
     pub fn remove_key(&mut self, jwk: &Jwk) {
         let index = self.keys.iter().position(|e| e.as_ref() == jwk);
         if let Some(index) = index {
@@ -133,6 +191,24 @@ impl JwkSet {
     }
 }
 
+impl Debug for JwkSet {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("JwkSet")
+            .field("keys", &self.keys)
+            .finish()
+    }
+}
+
+impl FromIterator<Jwk> for JwkSet {
+    fn from_iter<I: IntoIterator<Item = Jwk>>(iter: I) -> Self {
+        let mut jwk_set = Self::new();
+        for jwk in iter {
+            jwk_set.push_key(jwk);
+        }
+        jwk_set
+    }
+}
+
 impl AsRef<Map<String, Value>> for JwkSet {
     fn as_ref(&self) -> &Map<String, Value> {
         &self.params
@@ -154,9 +230,7 @@ impl Display for JwkSet {
                 fmt.write_str(",")?;
             }
 
-            let map: &Map<String, Value> = &jwk.as_ref().as_ref();
-            let val = serde_json::to_string(map).map_err(|_e| std::fmt::Error {})?;
-            fmt.write_str(&val)?;
+            fmt.write_str(&jwk.to_string())?;
         }
 
         fmt.write_str("]}")?;
@@ -168,6 +242,7 @@ impl Display for JwkSet {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::jws::RS256;
     use anyhow::Result;
     use std::fs::File;
     use std::path::PathBuf;
@@ -186,6 +261,139 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_load_jwt_set_with_unknown_key_type() -> Result<()> {
+        let mut file = load_file("jwks/mixed.jwks")?;
+        let jwks = JwkSet::from_reader(&mut file)?;
+
+        let keys = jwks.keys();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].key_type(), "EXPERIMENTAL");
+        assert_eq!(keys[1].key_type(), "RSA");
+
+        let unknown = jwks.get("unknown");
+        assert_eq!(unknown.len(), 1);
+        assert!(RS256.verifier_from_jwk(unknown[0]).is_err());
+
+        let rsa = jwks.get("rsa");
+        assert_eq!(rsa.len(), 1);
+        RS256.verifier_from_jwk(rsa[0])?;
+
+        // An invalid parameter of a known key type is still rejected.
+        let mut map = jwks.as_ref().clone();
+        if let Some(Value::Array(keys)) = map.get_mut("keys") {
+            if let Some(Value::Object(key)) = keys.get_mut(0) {
+                key.insert("kty".to_string(), Value::String("EC".to_string()));
+            }
+        }
+        assert!(JwkSet::from_map(map).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_jwk_set() -> Result<()> {
+        let mut jwks = Vec::new();
+        for kid in &["key-1", "key-2", "key-2"] {
+            let mut jwk = RS256.generate_key_pair(2048)?.to_jwk_public_key();
+            jwk.set_key_id(*kid);
+            jwks.push(jwk);
+        }
+
+        let jwk_set: JwkSet = jwks.clone().into_iter().collect();
+        assert_eq!(jwk_set.keys().len(), 3);
+        assert_eq!(jwk_set.get("key-2").len(), 2);
+        assert_eq!(jwk_set.duplicate_key_ids(), vec!["key-2"]);
+
+        let json = jwk_set.to_vec();
+        assert!(json.starts_with(b"{\"keys\":["));
+        let parsed = JwkSet::from_bytes(&json)?;
+        assert_eq!(parsed.to_string(), jwk_set.to_string());
+        for (jwk, expected) in parsed.keys().iter().zip(jwks.iter()) {
+            assert_eq!(*jwk, expected);
+        }
+
+        let mut jwk_set = JwkSet::new();
+        jwk_set.push_key(jwks[0].clone());
+        assert_eq!(jwk_set.get("key-1").len(), 1);
+        assert!(jwk_set.duplicate_key_ids().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_set_from_pem_bundle() -> Result<()> {
+        let mut rsa_pem = String::new();
+        load_file("pem/RSA_2048bit_public.pem")?.read_to_string(&mut rsa_pem)?;
+        let mut ec_pem = String::new();
+        load_file("pem/EC_P-256_public.pem")?.read_to_string(&mut ec_pem)?;
+        let mut private_pem = String::new();
+        load_file("pem/EC_P-256_private.pem")?.read_to_string(&mut private_pem)?;
+
+        let bundle = format!("{}\n{}{}", rsa_pem, private_pem, ec_pem);
+        let (jwk_set, errors) = JwkSet::from_pem_bundle(&bundle);
+        assert_eq!(errors.len(), 1);
+        assert!(format!("{:#}", errors[0]).contains("PEM block 1"));
+
+        let keys = jwk_set.keys();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].key_type(), "RSA");
+        assert_eq!(keys[0].algorithm(), None);
+        assert_eq!(keys[1].key_type(), "EC");
+        assert_eq!(keys[1].curve(), Some("P-256"));
+        assert_eq!(keys[1].algorithm(), Some("ES256"));
+        for jwk in &keys {
+            let kid = jwk.key_id().unwrap();
+            assert_eq!(kid, jwk.thumbprint(crate::util::SHA_256)?);
+            assert_eq!(jwk_set.get(kid).len(), 1);
+        }
+
+        let jwk = crate::jwk::key_pair_from_pem(&private_pem)?.to_jwk_public_key();
+        assert_eq!(keys[1].parameter("x"), jwk.parameter("x"));
+        assert_eq!(keys[1].parameter("y"), jwk.parameter("y"));
+
+        let mut bundle = String::new();
+        for file in &[
+            "pem/RSA_2048bit_traditional_public.pem",
+            "pem/RSA-PSS_2048bit_SHA-256_public.pem",
+            "pem/EC_secp256k1_public.pem",
+            "pem/ED25519_public.pem",
+            "pem/X448_public.pem",
+        ] {
+            load_file(file)?.read_to_string(&mut bundle)?;
+        }
+        let (jwk_set, errors) = JwkSet::from_pem_bundle(&bundle);
+        assert!(errors.is_empty());
+        let algorithms: Vec<Option<&str>> =
+            jwk_set.keys().iter().map(|jwk| jwk.algorithm()).collect();
+        assert_eq!(
+            algorithms,
+            vec![None, Some("PS256"), Some("ES256K"), Some("EdDSA"), None]
+        );
+
+        // A corrupted block between two valid keys is skipped.
+        let bundle = format!(
+            "{}-----BEGIN PUBLIC KEY-----\nMIIB!corrupted\n-----END PUBLIC KEY-----\n{}",
+            rsa_pem, ec_pem
+        );
+        let (jwk_set, errors) = JwkSet::from_pem_bundle(&bundle);
+        assert_eq!(jwk_set.keys().len(), 2);
+        assert_eq!(errors.len(), 1);
+        assert!(format!("{:#}", errors[0]).contains("PEM block 1"));
+
+        // So is a block with bad base64 data or without an end boundary.
+        let bundle = format!(
+            "{}-----BEGIN PUBLIC KEY-----\nMIIB=A\n-----END PUBLIC KEY-----\n{}{}\n\
+             -----BEGIN PUBLIC KEY-----\n",
+            rsa_pem, ec_pem, rsa_pem
+        );
+        let (jwk_set, errors) = JwkSet::from_pem_bundle(&bundle);
+        assert_eq!(jwk_set.keys().len(), 3);
+        assert_eq!(errors.len(), 2);
+
+        Ok(())
+    }
+
     fn load_file(path: &str) -> Result<File> {
         let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         pb.push("data");
@@ -17,7 +17,7 @@ pub enum KeyAlg {
     RsaPss {
         hash: Option<HashAlgorithm>,
         mgf1_hash: Option<HashAlgorithm>,
-        salt_len: Option<u8>,
+        salt_len: Option<u32>,
     },
     Ec {
         curve: Option<EcCurve>,
@@ -30,6 +30,34 @@ pub enum KeyAlg {
     },
 }
 
+impl KeyAlg {
+    /// Return the JWS algorithm name that is determined by the key itself.
+    ///
+    /// A RSA key returns none because it is usable for any RSASSA algorithm.
+    pub(crate) fn signature_algorithm(&self) -> Option<&'static str> {
+        match self {
+            KeyAlg::RsaPss {
+                hash: Some(hash),
+                mgf1_hash: Some(mgf1_hash),
+                salt_len: _,
+            } if hash == mgf1_hash => match hash {
+                HashAlgorithm::Sha256 => Some("PS256"),
+                HashAlgorithm::Sha384 => Some("PS384"),
+                HashAlgorithm::Sha512 => Some("PS512"),
+                _ => None,
+            },
+            KeyAlg::Ec { curve: Some(curve) } => match curve {
+                EcCurve::P256 => Some("ES256"),
+                EcCurve::P384 => Some("ES384"),
+                EcCurve::P521 => Some("ES512"),
+                EcCurve::Secp256k1 => Some("ES256K"),
+            },
+            KeyAlg::Ed { curve: Some(_) } => Some("EdDSA"),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum KeyFormat {
     Der { raw: bool },
@@ -316,6 +344,7 @@ impl KeyInfo {
 
     fn detect_from_der(input: &[u8]) -> Option<KeyInfo> {
         let mut reader = DerReader::from_reader(input);
+        reader.set_strict(true);
 
         match reader.next().ok()? {
             Some(DerType::Sequence) => {}
@@ -524,7 +553,7 @@ impl KeyInfo {
 
     fn parse_rsa_pss_params(
         reader: &mut DerReader<&[u8]>,
-    ) -> Result<(Option<HashAlgorithm>, Option<HashAlgorithm>, Option<u8>), DerError> {
+    ) -> Result<(Option<HashAlgorithm>, Option<HashAlgorithm>, Option<u32>), DerError> {
         let mut hash = Some(HashAlgorithm::Sha1);
         let mut mgf1_hash = Some(HashAlgorithm::Sha1);
         let mut salt_len = Some(20);
@@ -548,7 +577,12 @@ impl KeyInfo {
                         _ => break,
                     }
 
+                    // The parameters of the hash algorithm may be absent or NULL.
                     match reader.next()? {
+                        Some(DerType::Null) => match reader.next()? {
+                            Some(DerType::EndOfContents) => {}
+                            _ => break,
+                        },
                         Some(DerType::EndOfContents) => {}
                         _ => break,
                     }
@@ -587,7 +621,12 @@ impl KeyInfo {
                         _ => break,
                     }
 
+                    // The parameters of the hash algorithm may be absent or NULL.
                     match reader.next()? {
+                        Some(DerType::Null) => match reader.next()? {
+                            Some(DerType::EndOfContents) => {}
+                            _ => break,
+                        },
                         Some(DerType::EndOfContents) => {}
                         _ => break,
                     }
@@ -603,9 +642,9 @@ impl KeyInfo {
                     }
                 } else if i == 2 {
                     match reader.next()? {
-                        Some(DerType::Integer) => match reader.to_u8()? {
-                            val => salt_len = Some(val),
-                        },
+                        Some(DerType::Integer) => {
+                            salt_len = Some(reader.to_u32()?);
+                        }
                         _ => break,
                     }
 
@@ -1,6 +1,31 @@
 use std::fmt::Debug;
 
+use openssl::pkey::PKey;
+use openssl::symm::Cipher;
+
 use crate::jwk::Jwk;
+use crate::JoseError;
+
+/// Represents a symmetric cipher to encrypt a private key of PKCS#8 format.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum PrivateKeyCipher {
+    /// AES-128 in CBC mode
+    Aes128Cbc,
+    /// AES-192 in CBC mode
+    Aes192Cbc,
+    /// AES-256 in CBC mode
+    Aes256Cbc,
+}
+
+impl PrivateKeyCipher {
+    fn cipher(&self) -> Cipher {
+        match self {
+            Self::Aes128Cbc => Cipher::aes_128_cbc(),
+            Self::Aes192Cbc => Cipher::aes_192_cbc(),
+            Self::Aes256Cbc => Cipher::aes_256_cbc(),
+        }
+    }
+}
 
 pub trait KeyPair: Debug + Send + Sync {
     /// Return the applicatable algorithm.
@@ -9,6 +34,21 @@ pub trait KeyPair: Debug + Send + Sync {
     /// Return the applicatable key ID.
     fn key_id(&self) -> Option<&str>;
 
+    /// Return the number of bits of the key as reported by OpenSSL's `EVP_PKEY_bits`.
+    ///
+    /// This is the modulus length for RSA and the bit length of the group order for EC
+    /// (for example 521 for P-521). It is not always the curve size: X25519 reports 253
+    /// and Ed448 reports 456.
+    ///
+    /// The default implementation parses the DER private key and returns 0 if it cannot
+    /// be read by OpenSSL.
+    fn key_bits(&self) -> u32 {
+        match PKey::private_key_from_der(&self.to_der_private_key()) {
+            Ok(pkey) => pkey.bits(),
+            Err(_) => 0,
+        }
+    }
+
     fn to_der_private_key(&self) -> Vec<u8>;
     fn to_der_public_key(&self) -> Vec<u8>;
     fn to_pem_private_key(&self) -> Vec<u8>;
@@ -17,6 +57,44 @@ pub trait KeyPair: Debug + Send + Sync {
     fn to_jwk_public_key(&self) -> Jwk;
     fn to_jwk_key_pair(&self) -> Jwk;
 
+    /// Return a private key of encrypted PKCS#8 DER format (EncryptedPrivateKeyInfo).
+    ///
+    /// # Arguments
+    ///
+    /// * `passphrase` - The passphrase to encrypt the key.
+    /// * `cipher` - The symmetric cipher to encrypt the key.
+    fn to_encrypted_der_private_key(
+        &self,
+        passphrase: &[u8],
+        cipher: PrivateKeyCipher,
+    ) -> Result<Vec<u8>, JoseError> {
+        (|| -> anyhow::Result<Vec<u8>> {
+            let pkey = PKey::private_key_from_der(&self.to_der_private_key())?;
+            let der = pkey.private_key_to_pkcs8_passphrase(cipher.cipher(), passphrase)?;
+            Ok(der)
+        })()
+        .map_err(JoseError::InvalidKeyFormat)
+    }
+
+    /// Return a private key of encrypted PKCS#8 PEM format (ENCRYPTED PRIVATE KEY).
+    ///
+    /// # Arguments
+    ///
+    /// * `passphrase` - The passphrase to encrypt the key.
+    /// * `cipher` - The symmetric cipher to encrypt the key.
+    fn to_encrypted_pem_private_key(
+        &self,
+        passphrase: &[u8],
+        cipher: PrivateKeyCipher,
+    ) -> Result<Vec<u8>, JoseError> {
+        (|| -> anyhow::Result<Vec<u8>> {
+            let pkey = PKey::private_key_from_der(&self.to_der_private_key())?;
+            let pem = pkey.private_key_to_pem_pkcs8_passphrase(cipher.cipher(), passphrase)?;
+            Ok(pem)
+        })()
+        .map_err(JoseError::InvalidKeyFormat)
+    }
+
     fn box_clone(&self) -> Box<dyn KeyPair>;
 }
 
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+/// Represents a policy of the minimum key length for each algorithm.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct KeyStrengthPolicy {
+    min_key_bits: BTreeMap<String, u32>,
+}
+
+impl KeyStrengthPolicy {
+    /// Return a new empty policy.
+    pub fn new() -> Self {
+        Self {
+            min_key_bits: BTreeMap::new(),
+        }
+    }
+
+    /// Return a policy that requires the minimum key length of the specifications.
+    pub fn standard() -> Self {
+        let mut policy = Self::new();
+        for alg in &[
+            "RS256",
+            "RS384",
+            "RS512",
+            "PS256",
+            "PS384",
+            "PS512",
+            "RSA1_5",
+            "RSA-OAEP",
+            "RSA-OAEP-256",
+            "RSA-OAEP-384",
+            "RSA-OAEP-512",
+        ] {
+            policy.set_min_key_bits(*alg, 2048);
+        }
+        policy.set_min_key_bits("HS256", 256);
+        policy.set_min_key_bits("HS384", 384);
+        policy.set_min_key_bits("HS512", 512);
+        policy
+    }
+
+    /// Return a policy that requires a larger RSA key for a stronger hash algorithm.
+    pub fn strict() -> Self {
+        let mut policy = Self::standard();
+        policy.set_min_key_bits("RS384", 3072);
+        policy.set_min_key_bits("PS384", 3072);
+        policy.set_min_key_bits("RSA-OAEP-384", 3072);
+        policy.set_min_key_bits("RS512", 4096);
+        policy.set_min_key_bits("PS512", 4096);
+        policy.set_min_key_bits("RSA-OAEP-512", 4096);
+        policy
+    }
+
+    /// Set a minimum key length for a algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `alg` - a algorithm name
+    /// * `bits` - a minimum key length
+    pub fn set_min_key_bits(&mut self, alg: impl Into<String>, bits: u32) {
+        self.min_key_bits.insert(alg.into(), bits);
+    }
+
+    /// Remove the minimum key length for a algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `alg` - a algorithm name
+    pub fn remove_min_key_bits(&mut self, alg: &str) {
+        self.min_key_bits.remove(alg);
+    }
+
+    /// Return the minimum key length for a algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `alg` - a algorithm name
+    pub fn min_key_bits(&self, alg: &str) -> Option<u32> {
+        self.min_key_bits.get(alg).copied()
+    }
+}
+
+impl Default for KeyStrengthPolicy {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use anyhow::Result;
+
+    use crate::jwk::{Jwk, KeyStrengthPolicy};
+
+    #[test]
+    fn test_validate_alg_key_strength() -> Result<()> {
+        let mut jwk_2048 = Jwk::from_bytes(&load_file("jwk/RSA_private.jwk")?)?;
+        jwk_2048.set_algorithm("PS512");
+        jwk_2048.validate_alg_key_strength(&KeyStrengthPolicy::standard())?;
+        assert!(jwk_2048
+            .validate_alg_key_strength(&KeyStrengthPolicy::strict())
+            .is_err());
+
+        let mut jwk_4096 = Jwk::generate_rsa_key(4096)?;
+        jwk_4096.set_algorithm("PS512");
+        jwk_4096.validate_alg_key_strength(&KeyStrengthPolicy::strict())?;
+
+        let mut policy = KeyStrengthPolicy::new();
+        jwk_2048.validate_alg_key_strength(&policy)?;
+        policy.set_min_key_bits("PS512", 3072);
+        assert!(jwk_2048.validate_alg_key_strength(&policy).is_err());
+
+        let mut jwk_oct = Jwk::from_bytes(&load_file("jwk/oct_256bit_private.jwk")?)?;
+        jwk_oct.set_algorithm("HS256");
+        jwk_oct.validate_alg_key_strength(&KeyStrengthPolicy::standard())?;
+        jwk_oct.set_algorithm("HS512");
+        assert!(jwk_oct
+            .validate_alg_key_strength(&KeyStrengthPolicy::standard())
+            .is_err());
+
+        Ok(())
+    }
+
+    fn load_file(path: &str) -> Result<Vec<u8>> {
+        let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        pb.push("data");
+        pb.push(path);
+
+        let data = fs::read(&pb)?;
+        Ok(data)
+    }
+}
@@ -1,20 +1,25 @@
 //! JSON Web Signature (JWS) support.
 
 pub mod alg;
+mod caching_verifier_pool;
 mod jws_algorithm;
 mod jws_context;
 mod jws_header;
 mod jws_header_set;
 
+use anyhow::bail;
 use once_cell::sync::Lazy;
 
+use crate::jwk::{Jwk, KeyAlg, KeyInfo};
 use crate::JoseError;
 
+pub use crate::jws::caching_verifier_pool::CachingVerifierPool;
 pub use crate::jws::jws_algorithm::JwsAlgorithm;
 pub use crate::jws::jws_algorithm::JwsSigner;
 pub use crate::jws::jws_algorithm::JwsVerifier;
 pub use crate::jws::jws_context::JwsContext;
 pub use crate::jws::jws_header::JwsHeader;
+pub use crate::jws::jws_header::{compute_x5t, compute_x5t_s256};
 pub use crate::jws::jws_header_set::JwsHeaderSet;
 
 use crate::jws::alg::hmac::HmacJwsAlgorithm;
@@ -23,6 +28,8 @@ pub use HmacJwsAlgorithm::Hs384 as HS384;
 pub use HmacJwsAlgorithm::Hs512 as HS512;
 
 use crate::jws::alg::rsassa::RsassaJwsAlgorithm;
+#[cfg(feature = "legacy-algorithms")]
+pub use RsassaJwsAlgorithm::Rs1 as RS1;
 pub use RsassaJwsAlgorithm::Rs256 as RS256;
 pub use RsassaJwsAlgorithm::Rs384 as RS384;
 pub use RsassaJwsAlgorithm::Rs512 as RS512;
@@ -43,8 +50,137 @@ pub use EddsaJwsAlgorithm::Eddsa as EdDSA;
 
 static DEFAULT_CONTEXT: Lazy<JwsContext> = Lazy::new(|| JwsContext::new());
 
+/// Return a signer that is created from a private key of common or traditional PEM format.
+///
+/// The algorithm is detected by the PEM label and the algorithm identifier of the key.
+/// RS256 is used for a RSA key, and PS256/PS384/PS512 is selected by the digest
+/// algorithm of a RSA-PSS key.
+///
+/// # Arguments
+///
+/// * `input` - A private key of common or traditional PEM format.
+pub fn signer_from_pem(input: impl AsRef<[u8]>) -> Result<Box<dyn JwsSigner>, JoseError> {
+    (|| -> anyhow::Result<Box<dyn JwsSigner>> {
+        let input = input.as_ref();
+        let name = match KeyInfo::detect(&input).and_then(|val| val.alg()) {
+            Some(KeyAlg::Rsa) => "RS256",
+            Some(val) => match val.signature_algorithm() {
+                Some(val) => val,
+                None => bail!("The key cannot be used for JWS signing."),
+            },
+            None => bail!("The key type cannot be detected."),
+        };
+
+        let signer: Box<dyn JwsSigner> = match name {
+            "RS256" => Box::new(RS256.signer_from_pem(input)?),
+            "PS256" => Box::new(PS256.signer_from_pem(input)?),
+            "PS384" => Box::new(PS384.signer_from_pem(input)?),
+            "PS512" => Box::new(PS512.signer_from_pem(input)?),
+            "ES256" => Box::new(ES256.signer_from_pem(input)?),
+            "ES384" => Box::new(ES384.signer_from_pem(input)?),
+            "ES512" => Box::new(ES512.signer_from_pem(input)?),
+            "ES256K" => Box::new(ES256K.signer_from_pem(input)?),
+            "EdDSA" => Box::new(EdDSA.signer_from_pem(input)?),
+            _ => bail!("Unsupported key type: {}", name),
+        };
+        Ok(signer)
+    })()
+    .map_err(|err| match err.downcast::<JoseError>() {
+        Ok(err) => err,
+        Err(err) => JoseError::InvalidKeyFormat(err),
+    })
+}
+
+/// Return a verifier that is created from a JWK.
+///
+/// The algorithm is read from the alg parameter of the JWK. If it is absent, the algorithm
+/// is inferred from the kty and crv parameters of an EC or OKP key. RSA and oct keys
+/// without an alg parameter are rejected because the algorithm is ambiguous, so use
+/// `verifier_from_jwk_with_algorithm` for them.
+///
+/// # Arguments
+///
+/// * `jwk` - A public key or a shared secret key of JWK format.
+pub fn verifier_from_jwk(jwk: &Jwk) -> Result<Box<dyn JwsVerifier>, JoseError> {
+    (|| -> anyhow::Result<Box<dyn JwsVerifier>> {
+        let name = match jwk.algorithm() {
+            Some(val) => val,
+            None => match (jwk.key_type(), jwk.curve()) {
+                ("EC", Some("P-256")) => "ES256",
+                ("EC", Some("P-384")) => "ES384",
+                ("EC", Some("P-521")) => "ES512",
+                ("EC", Some("secp256k1")) => "ES256K",
+                ("OKP", Some("Ed25519")) | ("OKP", Some("Ed448")) => "EdDSA",
+                ("RSA", _) => bail!("A parameter alg is required to select RS* or PS* algorithm."),
+                ("oct", _) => bail!("A parameter alg is required to select HS* algorithm."),
+                (kty, _) => bail!("The algorithm cannot be inferred from the key: {}", kty),
+            },
+        };
+
+        let verifier = verifier_from_jwk_with_algorithm(jwk, name)?;
+        Ok(verifier)
+    })()
+    .map_err(|err| match err.downcast::<JoseError>() {
+        Ok(err) => err,
+        Err(err) => JoseError::InvalidKeyFormat(err),
+    })
+}
+
+/// Return a verifier of the expected algorithm that is created from a JWK.
+///
+/// The alg parameter of the JWK, if any, must be the same as the expected algorithm.
+///
+/// RS1 is never selected by name, because the name often comes from an untrusted
+/// alg header. Create its verifier with `RS1.verifier_from_jwk` instead.
+///
+/// # Arguments
+///
+/// * `jwk` - A public key or a shared secret key of JWK format.
+/// * `algorithm` - The expected JWS algorithm name.
+pub fn verifier_from_jwk_with_algorithm(
+    jwk: &Jwk,
+    algorithm: &str,
+) -> Result<Box<dyn JwsVerifier>, JoseError> {
+    let verifier: Box<dyn JwsVerifier> = match algorithm {
+        "HS256" => Box::new(HS256.verifier_from_jwk(jwk)?),
+        "HS384" => Box::new(HS384.verifier_from_jwk(jwk)?),
+        "HS512" => Box::new(HS512.verifier_from_jwk(jwk)?),
+        "RS256" => Box::new(RS256.verifier_from_jwk(jwk)?),
+        "RS384" => Box::new(RS384.verifier_from_jwk(jwk)?),
+        "RS512" => Box::new(RS512.verifier_from_jwk(jwk)?),
+        "PS256" => Box::new(PS256.verifier_from_jwk(jwk)?),
+        "PS384" => Box::new(PS384.verifier_from_jwk(jwk)?),
+        "PS512" => Box::new(PS512.verifier_from_jwk(jwk)?),
+        "ES256" => Box::new(ES256.verifier_from_jwk(jwk)?),
+        "ES384" => Box::new(ES384.verifier_from_jwk(jwk)?),
+        "ES512" => Box::new(ES512.verifier_from_jwk(jwk)?),
+        "ES256K" => Box::new(ES256K.verifier_from_jwk(jwk)?),
+        "EdDSA" => Box::new(EdDSA.verifier_from_jwk(jwk)?),
+        val => {
+            return Err(JoseError::UnsupportedSignatureAlgorithm(anyhow::anyhow!(
+                "Unknown algorithm: {}",
+                val
+            )))
+        }
+    };
+    Ok(verifier)
+}
+
+/// Return the JWS signing input that is signed in compact serialization.
+///
+/// # Arguments
+///
+/// * `payload` - The payload data.
+/// * `header` - The JWS heaser claims.
+pub fn signing_input(payload: &[u8], header: &JwsHeader) -> Result<Vec<u8>, JoseError> {
+    DEFAULT_CONTEXT.signing_input(payload, header)
+}
+
 /// Return a representation of the data that is formatted by compact serialization.
 ///
+/// The signer can be any implementation of JwsSigner, including an external signer.
+/// The alg header claim is set from `signer.algorithm().name()`.
+///
 /// # Arguments
 ///
 /// * `payload` - The payload data.
@@ -58,6 +194,22 @@ pub fn serialize_compact(
     DEFAULT_CONTEXT.serialize_compact(payload, header, signer)
 }
 
+/// Return a representation of the data that is formatted by compact serialization,
+/// the header claims that are actually signed and the raw signature.
+///
+/// # Arguments
+///
+/// * `payload` - The payload data.
+/// * `header` - The JWS heaser claims.
+/// * `signer` - The JWS signer.
+pub fn serialize_compact_detailed(
+    payload: &[u8],
+    header: &JwsHeader,
+    signer: &dyn JwsSigner,
+) -> Result<(String, JwsHeader, Vec<u8>), JoseError> {
+    DEFAULT_CONTEXT.serialize_compact_detailed(payload, header, signer)
+}
+
 /// Return a representation of the data that is formatted by compact serialization.
 ///
 /// # Arguments
@@ -144,6 +296,9 @@ where
 
 /// Deserialize the input that is formatted by compact serialization.
 ///
+/// The signature is verified against the header and payload segments of the input as is,
+/// so the key order and whitespace of the original header JSON are preserved.
+///
 /// # Arguments
 ///
 /// * `input` - The input data.
@@ -173,6 +328,33 @@ where
     DEFAULT_CONTEXT.deserialize_compact_with_selector(input, selector)
 }
 
+/// Verify the signature of the input that is formatted by compact serialization
+/// without decoding the payload.
+///
+/// # Arguments
+///
+/// * `input` - The input data.
+/// * `verifier` - The JWS verifier.
+pub fn verify_signature_only(
+    input: impl AsRef<[u8]>,
+    verifier: &dyn JwsVerifier,
+) -> Result<(), JoseError> {
+    DEFAULT_CONTEXT.verify_signature_only(input, verifier)
+}
+
+/// Return the header of the input that is formatted by compact serialization
+/// without verifying the signature.
+///
+/// The returned header is untrusted data that may only be used to select a key.
+/// The payload is not decoded.
+///
+/// # Arguments
+///
+/// * `input` - The input data.
+pub fn decode_header_unverified(input: impl AsRef<[u8]>) -> Result<JwsHeader, JoseError> {
+    DEFAULT_CONTEXT.decode_header_unverified(input)
+}
+
 /// Deserialize the input that is formatted by json serialization.
 ///
 /// # Arguments
@@ -208,11 +390,466 @@ where
 mod tests {
     use std::fs;
     use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
 
     use anyhow::Result;
 
-    use crate::jws::{self, EdDSA, JwsHeader, JwsHeaderSet, ES256, RS256};
-    use crate::Value;
+    use crate::jwk::alg::ed::EdCurve;
+    use crate::jwk::Jwk;
+    use crate::jws::{
+        self, EdDSA, JwsAlgorithm, JwsContext, JwsHeader, JwsHeaderSet, JwsSigner, ES256, ES256K,
+        ES384, HS256, PS256, PS384, RS256,
+    };
+    use crate::util;
+    use crate::{JoseError, Map, Value};
+
+    #[test]
+    fn test_signer_from_pem() -> Result<()> {
+        for (path, expected) in &[
+            ("pem/RSA_2048bit_private.pem", "RS256"),
+            ("pem/RSA_2048bit_traditional_private.pem", "RS256"),
+            ("pem/RSA-PSS_2048bit_SHA-256_private.pem", "PS256"),
+            ("pem/RSA-PSS_2048bit_SHA-384_private.pem", "PS384"),
+            ("pem/RSA-PSS_2048bit_SHA-512_private.pem", "PS512"),
+            ("pem/EC_P-256_private.pem", "ES256"),
+            ("pem/EC_P-384_private.pem", "ES384"),
+            ("pem/EC_P-521_traditional_private.pem", "ES512"),
+            ("pem/EC_secp256k1_private.pem", "ES256K"),
+            ("pem/ED25519_private.pem", "EdDSA"),
+            ("pem/ED448_private.pem", "EdDSA"),
+        ] {
+            let signer = jws::signer_from_pem(&load_file(path)?)?;
+            assert_eq!(signer.algorithm().name(), *expected, "{}", path);
+        }
+
+        assert!(jws::signer_from_pem(&load_file("pem/X25519_private.pem")?).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verifier_from_jwk() -> Result<()> {
+        for (public_path, expected) in &[
+            ("jwk/EC_P-256_public.jwk", "ES256"),
+            ("jwk/EC_P-384_public.jwk", "ES384"),
+            ("jwk/EC_secp256k1_public.jwk", "ES256K"),
+            ("jwk/OKP_Ed25519_public.jwk", "EdDSA"),
+        ] {
+            let jwk = Jwk::from_bytes(&load_file(public_path)?)?;
+            let verifier = jws::verifier_from_jwk(&jwk)?;
+            assert_eq!(verifier.algorithm().name(), *expected, "{}", public_path);
+
+            let private_path = public_path.replace("public", "private");
+            let private_jwk = Jwk::from_bytes(&load_file(&private_path)?)?;
+            let signer = match *expected {
+                "ES256" => ES256.signer_from_jwk(&private_jwk)?.box_clone(),
+                "ES384" => ES384.signer_from_jwk(&private_jwk)?.box_clone(),
+                "ES256K" => ES256K.signer_from_jwk(&private_jwk)?.box_clone(),
+                _ => EdDSA.signer_from_jwk(&private_jwk)?.box_clone(),
+            };
+            let signature = signer.sign(b"message")?;
+            verifier.verify(b"message", &signature)?;
+        }
+
+        let rsa_jwk = Jwk::from_bytes(&load_file("jwk/RSA_public.jwk")?)?;
+        assert!(jws::verifier_from_jwk(&rsa_jwk).is_err());
+        for name in &["RS256", "PS384"] {
+            let verifier = jws::verifier_from_jwk_with_algorithm(&rsa_jwk, name)?;
+            assert_eq!(verifier.algorithm().name(), *name);
+        }
+
+        let mut rsa_jwk = rsa_jwk;
+        rsa_jwk.set_algorithm("PS256");
+        let verifier = jws::verifier_from_jwk(&rsa_jwk)?;
+        assert_eq!(verifier.algorithm().name(), "PS256");
+        assert!(jws::verifier_from_jwk_with_algorithm(&rsa_jwk, "RS256").is_err());
+
+        let mut oct_jwk = Jwk::from_bytes(&load_file("jwk/oct_256bit_private.jwk")?)?;
+        assert!(jws::verifier_from_jwk(&oct_jwk).is_err());
+        oct_jwk.set_algorithm("HS256");
+        let verifier = jws::verifier_from_jwk(&oct_jwk)?;
+        let signature = HS256.signer_from_jwk(&oct_jwk)?.sign(b"message")?;
+        verifier.verify(b"message", &signature)?;
+
+        let err = jws::verifier_from_jwk_with_algorithm(&oct_jwk, "none").unwrap_err();
+        assert!(matches!(err, JoseError::UnsupportedSignatureAlgorithm(_)));
+
+        let x25519_jwk = Jwk::from_bytes(&load_file("jwk/OKP_X25519_public.jwk")?)?;
+        assert!(jws::verifier_from_jwk(&x25519_jwk).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_verify_with_original_header_bytes() -> Result<()> {
+        // RFC 7515 Appendix A.1: the protected header is {"typ":"JWT",\r\n "alg":"HS256"}.
+        let header_b64 = "eyJ0eXAiOiJKV1QiLA0KICJhbGciOiJIUzI1NiJ9";
+        let payload_b64 = "eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFtcGxlLmNvbS9pc19yb290Ijp0cnVlfQ";
+        let signature_b64 = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+
+        let mut jwk = Jwk::new("oct");
+        jwk.set_parameter(
+            "k",
+            Some(Value::String(
+                "AyM1SysPpbyDfgZld3umj1qzKObwVMkoqQ-EstJQLr_T-1qS0gZH75aKtMN3Yj0iPS4hcgUuTwjAzZr1Z9CAow"
+                    .to_string(),
+            )),
+        )?;
+        let verifier = HS256.verifier_from_jwk(&jwk)?;
+
+        let input = format!("{}.{}.{}", header_b64, payload_b64, signature_b64);
+        let (payload, header) = jws::deserialize_compact(&input, &verifier)?;
+        assert_eq!(header.token_type(), Some("JWT"));
+        assert_eq!(
+            payload,
+            b"{\"iss\":\"joe\",\r\n \"exp\":1300819380,\r\n \"http://example.com/is_root\":true}"
+                .to_vec()
+        );
+
+        let input = format!(
+            "{{\"protected\":\"{}\",\"payload\":\"{}\",\"signature\":\"{}\"}}",
+            header_b64, payload_b64, signature_b64
+        );
+        let (json_payload, json_header) = jws::deserialize_json(&input, &verifier)?;
+        assert_eq!(json_header.token_type(), Some("JWT"));
+        assert_eq!(json_payload, payload);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_json_serialization_with_unprotected_key_id() -> Result<()> {
+        let signer = HS256.signer_from_bytes(b"0123456789ABCDEF0123456789ABCDEF")?;
+        let verifier = HS256.verifier_from_bytes(b"0123456789ABCDEF0123456789ABCDEF")?;
+
+        let mut src_header = JwsHeaderSet::new();
+        src_header.set_protected("typ", Value::String("JWT".to_string()))?;
+        src_header.set_unprotected("kid", Value::String("key-1".to_string()))?;
+        let json = jws::serialize_flattened_json(b"test payload!", &src_header, &signer)?;
+
+        let mut map: Map<String, Value> = serde_json::from_str(&json)?;
+        let protected = match map.get("protected") {
+            Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
+            _ => unreachable!(),
+        };
+        let protected: Map<String, Value> = serde_json::from_slice(&protected)?;
+        assert!(!protected.contains_key("kid"));
+        assert_eq!(
+            protected.get("typ"),
+            Some(&Value::String("JWT".to_string()))
+        );
+
+        let (_, dst_header) = jws::deserialize_json(&json, &verifier)?;
+        assert_eq!(dst_header.key_id(), Some("key-1"));
+
+        // The unprotected header is not part of the signing input.
+        let mut unprotected = Map::new();
+        unprotected.insert("kid".to_string(), Value::String("key-2".to_string()));
+        map.insert("header".to_string(), Value::Object(unprotected));
+        let json = serde_json::to_string(&map)?;
+        let (_, dst_header) = jws::deserialize_json(&json, &verifier)?;
+        assert_eq!(dst_header.key_id(), Some("key-2"));
+
+        // A parameter must not appear in both the protected and unprotected header.
+        let mut unprotected = Map::new();
+        unprotected.insert("typ".to_string(), Value::String("JWT".to_string()));
+        map.insert("header".to_string(), Value::Object(unprotected));
+        let json = serde_json::to_string(&map)?;
+        assert!(jws::deserialize_json(&json, &verifier).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_decode_header_unverified() -> Result<()> {
+        let signer = HS256.signer_from_bytes(b"0123456789ABCDEF0123456789ABCDEF")?;
+        let mut header = JwsHeader::new();
+        header.set_key_id("hmac-key");
+        let jws = jws::serialize_compact(b"abcde12345", &header, &signer)?;
+
+        let header = jws::decode_header_unverified(&jws)?;
+        assert_eq!(header.algorithm(), Some("HS256"));
+        assert_eq!(header.key_id(), Some("hmac-key"));
+
+        let err = jws::decode_header_unverified(format!("{}.", jws)).unwrap_err();
+        assert!(matches!(err, JoseError::InvalidJwsFormat(_)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_public_jwk_embedding() -> Result<()> {
+        let mut context = JwsContext::new();
+        context.set_public_jwk_embedding(true);
+        let payload = b"embedded key";
+
+        let ec_jwk = ES256.generate_key_pair()?.to_jwk_key_pair();
+        let rsa_jwk = PS256.generate_key_pair(2048)?.to_jwk_key_pair();
+        let ed_jwk = EdDSA.generate_key_pair(EdCurve::Ed25519)?.to_jwk_key_pair();
+        let signers: Vec<Box<dyn JwsSigner>> = vec![
+            Box::new(ES256.signer_from_jwk(&ec_jwk)?),
+            Box::new(PS256.signer_from_jwk(&rsa_jwk)?),
+            Box::new(EdDSA.signer_from_jwk(&ed_jwk)?),
+        ];
+
+        for signer in &signers {
+            let jws = context.serialize_compact(payload, &JwsHeader::new(), &**signer)?;
+            let header = jws::decode_header_unverified(&jws)?;
+            let embedded = match header.jwk() {
+                Some(val) => val,
+                None => unreachable!(),
+            };
+            for key in &["d", "p", "q", "dp", "dq", "qi", "oth", "k"] {
+                assert!(embedded.parameter(key).is_none(), "{}", key);
+            }
+
+            let verifier =
+                jws::verifier_from_jwk_with_algorithm(&embedded, signer.algorithm().name())?;
+            let (dst_payload, _) = jws::deserialize_compact(&jws, &*verifier)?;
+            assert_eq!(dst_payload, payload);
+
+            let json =
+                context.serialize_flattened_json(payload, &JwsHeaderSet::new(), &**signer)?;
+            let (dst_payload, _) = jws::deserialize_json(&json, &*verifier)?;
+            assert_eq!(dst_payload, payload);
+        }
+
+        // A jwk header claim that is already set is kept.
+        let mut header = JwsHeader::new();
+        header.set_jwk(rsa_jwk.to_public_key()?);
+        let jws = context.serialize_compact(payload, &header, &*signers[0])?;
+        assert_eq!(
+            jws::decode_header_unverified(&jws)?.jwk(),
+            Some(rsa_jwk.to_public_key()?)
+        );
+
+        let hmac_signer = HS256.signer_from_bytes(util::random_bytes(32))?;
+        assert!(context
+            .serialize_compact(payload, &JwsHeader::new(), &hmac_signer)
+            .is_err());
+
+        context.set_public_jwk_embedding(false);
+        let jws = context.serialize_compact(payload, &JwsHeader::new(), &*signers[0])?;
+        assert_eq!(jws::decode_header_unverified(&jws)?.jwk(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_standard_base64_segment() -> Result<()> {
+        let key = b"0123456789ABCDEF0123456789ABCDEF";
+        let signer = HS256.signer_from_bytes(key)?;
+        let verifier = HS256.verifier_from_bytes(key)?;
+
+        let jws = jws::serialize_compact(b"abcde12345", &JwsHeader::new(), &signer)?;
+        let parts: Vec<&str> = jws.split('.').collect();
+        let signature = base64::decode_config(parts[2], base64::URL_SAFE_NO_PAD)?;
+        let signature = base64::encode_config(signature, base64::STANDARD);
+        let jws = format!("{}.{}.{}", parts[0], parts[1], signature);
+
+        let err = jws::deserialize_compact(&jws, &verifier).unwrap_err();
+        assert!(matches!(err, JoseError::InvalidJwsFormat(_)));
+        assert!(format!("{}", err).contains("standard base64 alphabet"));
+
+        let mut context = JwsContext::new();
+        context.set_lenient_base64_decoding(true);
+        context.deserialize_compact(&jws, &verifier)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_max_len() -> Result<()> {
+        let key = b"0123456789ABCDEF0123456789ABCDEF";
+        let signer = HS256.signer_from_bytes(key)?;
+        let verifier = HS256.verifier_from_bytes(key)?;
+
+        let header = JwsHeader::new();
+        let payload = vec![b'a'; 1000];
+        let jws = jws::serialize_compact(&payload, &header, &signer)?;
+        let json = jws::serialize_flattened_json(&payload, &JwsHeaderSet::new(), &signer)?;
+
+        let mut context = JwsContext::new();
+        assert_eq!(context.max_input_len(), Some(8 * 1024 * 1024));
+        assert_eq!(context.max_payload_len(), Some(8 * 1024 * 1024));
+
+        context.set_max_input_len(Some(jws.len()));
+        context.deserialize_compact(&jws, &verifier)?;
+        context.set_max_input_len(Some(jws.len() - 1));
+        assert!(context.deserialize_compact(&jws, &verifier).is_err());
+        assert!(context.verify_signature_only(&jws, &verifier).is_err());
+
+        context.set_max_input_len(Some(json.len()));
+        context.deserialize_json(&json, &verifier)?;
+        context.set_max_input_len(Some(json.len() - 1));
+        assert!(context.deserialize_json(&json, &verifier).is_err());
+
+        context.set_max_input_len(None);
+        context.set_max_payload_len(Some(payload.len()));
+        context.deserialize_compact(&jws, &verifier)?;
+        context.deserialize_json(&json, &verifier)?;
+        context.set_max_payload_len(Some(payload.len() - 1));
+        assert!(context.deserialize_compact(&jws, &verifier).is_err());
+        assert!(context.deserialize_json(&json, &verifier).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_critical_header_claim() -> Result<()> {
+        let key = b"0123456789ABCDEF0123456789ABCDEF";
+        let signer = HS256.signer_from_bytes(key)?;
+        let verifier = HS256.verifier_from_bytes(key)?;
+
+        let mut header = JwsHeader::new();
+        header.set_critical(&vec!["exp"]);
+        header.set_claim("exp", Some(Value::from(1363284000)))?;
+        let jws = jws::serialize_compact(b"payload", &header, &signer)?;
+
+        let mut header_set = JwsHeaderSet::new();
+        header_set.set_protected("crit", Value::from(vec!["exp"]))?;
+        header_set.set_protected("exp", Value::from(1363284000))?;
+        let json = jws::serialize_flattened_json(b"payload", &header_set, &signer)?;
+
+        let mut context = JwsContext::new();
+        assert!(context.deserialize_compact(&jws, &verifier).is_err());
+        assert!(context.deserialize_json(&json, &verifier).is_err());
+
+        context.add_acceptable_critical("exp");
+        let (payload, _) = context.deserialize_compact(&jws, &verifier)?;
+        assert_eq!(payload, b"payload");
+        let (payload, _) = context.deserialize_json(&json, &verifier)?;
+        assert_eq!(payload, b"payload");
+
+        // The listed name must be in the header and must not be a registered claim.
+        for critical in &[vec!["alg"], vec!["kid"], vec!["iss"]] {
+            context.add_acceptable_critical(critical[0]);
+            let mut header = JwsHeader::new();
+            header.set_critical(critical);
+            header.set_key_id("key-1");
+            let jws = jws::serialize_compact(b"payload", &header, &signer)?;
+            assert!(context.deserialize_compact(&jws, &verifier).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_duplicate_header_claims() -> Result<()> {
+        let key = b"0123456789ABCDEF0123456789ABCDEF";
+        let signer = HS256.signer_from_bytes(key)?;
+        let verifier = HS256.verifier_from_bytes(key)?;
+
+        let header =
+            base64::encode_config(r#"{"alg":"none","alg":"HS256"}"#, base64::URL_SAFE_NO_PAD);
+        let payload = base64::encode_config(b"payload", base64::URL_SAFE_NO_PAD);
+        let message = format!("{}.{}", header, payload);
+        let signature =
+            base64::encode_config(signer.sign(message.as_bytes())?, base64::URL_SAFE_NO_PAD);
+        let jws = format!("{}.{}", message, signature);
+
+        assert!(jws::deserialize_compact(&jws, &verifier).is_err());
+        assert!(crate::jwt::decode_with_verifier(&jws, &verifier).is_err());
+        assert!(JwsHeader::from_bytes(br#"{"alg":"none","alg":"HS256"}"#).is_err());
+
+        let json = format!(
+            r#"{{"protected":"{}","payload":"{}","signature":"{}"}}"#,
+            header, payload, signature
+        );
+        assert!(jws::deserialize_json(&json, &verifier).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_verify_signature_only() -> Result<()> {
+        let key = b"0123456789ABCDEF0123456789ABCDEF";
+        let signer = HS256.signer_from_bytes(key)?;
+        let verifier = HS256.verifier_from_bytes(key)?;
+
+        let jws = jws::serialize_compact(b"payload", &JwsHeader::new(), &signer)?;
+        jws::verify_signature_only(&jws, &verifier)?;
+
+        let mut tampered = jws.clone().into_bytes();
+        let pos = jws.find('.').unwrap() + 1;
+        tampered[pos] = if tampered[pos] == b'A' { b'B' } else { b'A' };
+        assert!(jws::verify_signature_only(&tampered, &verifier).is_err());
+
+        let other_verifier = HS256.verifier_from_bytes(b"ABCDEF0123456789ABCDEF0123456789")?;
+        assert!(jws::verify_signature_only(&jws, &other_verifier).is_err());
+
+        // The payload is never decoded: a signed payload part that is not base64url
+        // is rejected by deserialize_compact but accepted by verify_signature_only.
+        let header = base64::encode_config(r#"{"alg":"HS256"}"#, base64::URL_SAFE_NO_PAD);
+        let message = format!("{}.{}", header, "not*base64url");
+        let signature = signer.sign(message.as_bytes())?;
+        let jws = format!(
+            "{}.{}",
+            message,
+            base64::encode_config(&signature, base64::URL_SAFE_NO_PAD)
+        );
+        assert!(jws::deserialize_compact(&jws, &verifier).is_err());
+        jws::verify_signature_only(&jws, &verifier)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_serialize_compact_detailed() -> Result<()> {
+        let key_pair = ES256.generate_key_pair()?;
+        let mut jwk = key_pair.to_jwk_private_key();
+        jwk.set_key_id("key-1");
+        let signer = ES256.signer_from_jwk(&jwk)?;
+        let verifier = ES256.verifier_from_der(key_pair.to_der_public_key())?;
+
+        let mut header = JwsHeader::new();
+        header.set_token_type("JWT");
+        let (jws, signed_header, signature) =
+            jws::serialize_compact_detailed(b"payload", &header, &signer)?;
+        assert_eq!(signed_header.algorithm(), Some(signer.algorithm().name()));
+        assert_eq!(signed_header.key_id(), Some("key-1"));
+        assert_eq!(signed_header.token_type(), Some("JWT"));
+        assert_eq!(signature.len(), signer.signature_len());
+
+        let parts: Vec<&str> = jws.split('.').collect();
+        assert_eq!(
+            parts[2],
+            base64::encode_config(&signature, base64::URL_SAFE_NO_PAD)
+        );
+        let (payload, decoded_header) = jws::deserialize_compact(&jws, &verifier)?;
+        assert_eq!(payload, b"payload");
+        assert_eq!(decoded_header, signed_header);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_compact_segment_count() -> Result<()> {
+        let signer = HS256.signer_from_bytes(b"0123456789ABCDEF0123456789ABCDEF")?;
+        let verifier = HS256.verifier_from_bytes(b"0123456789ABCDEF0123456789ABCDEF")?;
+
+        let jwt = jws::serialize_compact(b"test payload!", &JwsHeader::new(), &signer)?;
+        jws::deserialize_compact(&jwt, &verifier)?;
+
+        let parts: Vec<&str> = jwt.split('.').collect();
+        for input in &[
+            format!("{}.{}", parts[0], parts[1]),
+            format!("{}.{}.{}.{}", parts[0], parts[1], parts[2], parts[2]),
+        ] {
+            let err = jws::deserialize_compact(input, &verifier).unwrap_err();
+            assert!(format!("{}", err).contains("three parts"), "{}", err);
+        }
+
+        let input = format!(".{}.{}", parts[1], parts[2]);
+        let err = jws::deserialize_compact(&input, &verifier).unwrap_err();
+        assert!(format!("{}", err).contains("header part"), "{}", err);
+
+        let jwt = jws::serialize_compact(b"", &JwsHeader::new(), &signer)?;
+        let (payload, _) = jws::deserialize_compact(&jwt, &verifier)?;
+        assert!(payload.is_empty());
+
+        Ok(())
+    }
 
     #[test]
     fn test_jws_compact_serialization() -> Result<()> {
@@ -237,6 +874,58 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_jws_compact_serialization_with_signer_key_id() -> Result<()> {
+        let private_key = load_file("pem/RSA-PSS_2048bit_SHA-256_private.pem")?;
+        let public_key = load_file("pem/RSA-PSS_2048bit_SHA-256_public.pem")?;
+
+        let mut signer = PS256.signer_from_pem(&private_key)?;
+        signer.set_key_id("abc");
+        let mut verifier = PS256.verifier_from_pem(&public_key)?;
+        verifier.set_key_id("abc");
+
+        let jws = jws::serialize_compact(b"payload", &JwsHeader::new(), &signer)?;
+        let (_, header) = jws::deserialize_compact(&jws, &verifier)?;
+        assert_eq!(header.key_id(), Some("abc"));
+
+        // An explicit kid header claim takes precedence over the key ID of the signer.
+        let mut src_header = JwsHeader::new();
+        src_header.set_key_id("explicit");
+        let jws = jws::serialize_compact(b"payload", &src_header, &signer)?;
+        verifier.remove_key_id();
+        let (_, header) = jws::deserialize_compact(&jws, &verifier)?;
+        assert_eq!(header.key_id(), Some("explicit"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_rejects_other_algorithm() -> Result<()> {
+        let private_key = load_file("der/RSA_2048bit_pkcs8_private.der")?;
+        let public_key = load_file("der/RSA_2048bit_spki_public.der")?;
+
+        let signer = PS384.signer_from_der(&private_key)?;
+        let verifier = PS256.verifier_from_der(&public_key)?;
+
+        let jws = jws::serialize_compact(b"payload", &JwsHeader::new(), &signer)?;
+        let err = jws::deserialize_compact(&jws, &verifier).expect_err("PS384 was accepted");
+        assert!(format!("{}", err).contains("The JWS alg header claim is not PS256: PS384"));
+        assert!(jws::verify_signature_only(&jws, &verifier).is_err());
+
+        let json = jws::serialize_flattened_json(b"payload", &JwsHeaderSet::new(), &signer)?;
+        assert!(jws::deserialize_json(&json, &verifier).is_err());
+
+        // A PS256 signature relabeled as PS384 is rejected before the signature is checked.
+        let signer = PS256.signer_from_der(&private_key)?;
+        let jws = jws::serialize_compact(b"payload", &JwsHeader::new(), &signer)?;
+        let mut parts: Vec<&str> = jws.split('.').collect();
+        let header = util::encode_base64url(br#"{"alg":"PS384"}"#);
+        parts[0] = &header;
+        assert!(jws::deserialize_compact(parts.join("."), &verifier).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_jws_json_serialization() -> Result<()> {
         let alg = RS256;
@@ -307,6 +996,179 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_jws_signing_input() -> Result<()> {
+        let private_key = load_file("pem/RSA_2048bit_private.pem")?;
+        let public_key = load_file("pem/RSA_2048bit_public.pem")?;
+
+        let mut header = JwsHeader::new();
+        header.set_algorithm(RS256.name());
+        header.set_token_type("JWT");
+        let payload = b"test payload!";
+
+        let signer = RS256.signer_from_pem(&private_key)?;
+        let jwt = jws::serialize_compact(payload, &header, &signer)?;
+
+        let input = jws::signing_input(payload, &header)?;
+        let pos = jwt.rfind('.').unwrap();
+        assert_eq!(input, &jwt.as_bytes()[..pos]);
+
+        // Reassemble the compact serialization with an externally made signature.
+        let signature = signer.sign(&input)?;
+        let mut reassembled = String::from_utf8(input)?;
+        reassembled.push('.');
+        reassembled.push_str(&base64::encode_config(signature, base64::URL_SAFE_NO_PAD));
+
+        let verifier = RS256.verifier_from_pem(&public_key)?;
+        let (dst_payload, _) = jws::deserialize_compact(&reassembled, &verifier)?;
+        assert_eq!(payload.to_vec(), dst_payload);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_signing_input_unencoded_payload() -> Result<()> {
+        let mut header = JwsHeader::new();
+        header.set_algorithm(RS256.name());
+        header.set_base64url_encode_payload(false);
+        header.set_critical(&vec!["b64"]);
+
+        let input = jws::signing_input(b"$.02", &header);
+        assert!(input.is_err());
+
+        let input = jws::signing_input(b"$02", &header)?;
+        assert!(input.ends_with(b".$02"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_b64_requires_critical() -> Result<()> {
+        let key = b"0123456789ABCDEF0123456789ABCDEF";
+        let signer = HS256.signer_from_bytes(key)?;
+        let verifier = HS256.verifier_from_bytes(key)?;
+        let mut context = JwsContext::new();
+        context.add_acceptable_critical("b64");
+
+        // Conformant: b64 is listed in crit.
+        let mut header = JwsHeader::new();
+        header.set_base64url_encode_payload(false);
+        header.set_critical(&vec!["b64"]);
+        let jws = context.serialize_compact(b"$02", &header, &signer)?;
+        assert!(jws.contains(".$02."));
+        let (payload, _) = context.deserialize_compact(&jws, &verifier)?;
+        assert_eq!(payload, b"$02");
+
+        // Non-conformant: b64 is false but not listed in crit.
+        let mut header = JwsHeader::new();
+        header.set_base64url_encode_payload(false);
+        assert!(context.serialize_compact(b"$02", &header, &signer).is_err());
+        header.set_algorithm("HS256");
+        assert!(context.signing_input(b"$02", &header).is_err());
+
+        let mut header_set = JwsHeaderSet::new();
+        header_set.set_base64url_encode_payload(false);
+        assert!(context
+            .serialize_flattened_json(b"$02", &header_set, &signer)
+            .is_err());
+
+        let protected = util::encode_base64url(br#"{"alg":"HS256","b64":false}"#);
+        let message = format!("{}.$02", protected);
+        let signature = util::encode_base64url(&signer.sign(message.as_bytes())?);
+        let jws = format!("{}.{}", message, signature);
+        assert!(context.deserialize_compact(&jws, &verifier).is_err());
+
+        let json = format!(
+            r#"{{"protected":"{}","payload":"$02","signature":"{}"}}"#,
+            protected, signature
+        );
+        assert!(context.deserialize_json(&json, &verifier).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_compact_standard_alphabet_lenient() -> Result<()> {
+        let signer = HS256.signer_from_bytes(b"0123456789ABCDEF0123456789ABCDEF")?;
+        let verifier = HS256.verifier_from_bytes(b"0123456789ABCDEF0123456789ABCDEF")?;
+
+        // A broken producer that uses the standard base64 alphabet with padding.
+        let header = base64::encode_config(br#"{"alg":"HS256"}"#, base64::STANDARD);
+        let payload = base64::encode_config(b"\xfb\xff\xfe", base64::STANDARD);
+        assert!(payload.contains('+') && payload.contains('/'));
+        let message = format!("{}.{}", header, payload);
+        let signature = signer.sign(message.as_bytes())?;
+        let jws = format!(
+            "{}.{}",
+            message,
+            base64::encode_config(signature, base64::STANDARD)
+        );
+
+        let mut context = JwsContext::new();
+        assert!(context.deserialize_compact(&jws, &verifier).is_err());
+
+        context.set_lenient_base64_decoding(true);
+        let (payload, header) = context.deserialize_compact(&jws, &verifier)?;
+        assert_eq!(payload, b"\xfb\xff\xfe");
+        assert_eq!(header.algorithm(), Some("HS256"));
+
+        Ok(())
+    }
+
+    #[derive(Debug, Clone)]
+    struct ExternalSigner {
+        messages: Arc<Mutex<Vec<Vec<u8>>>>,
+    }
+
+    impl JwsSigner for ExternalSigner {
+        fn algorithm(&self) -> &dyn JwsAlgorithm {
+            &RS256
+        }
+
+        fn key_id(&self) -> Option<&str> {
+            Some("hsm-key-1")
+        }
+
+        fn signature_len(&self) -> usize {
+            256
+        }
+
+        fn sign(&self, message: &[u8]) -> Result<Vec<u8>, JoseError> {
+            self.messages.lock().unwrap().push(message.to_vec());
+            Ok(b"canned signature".to_vec())
+        }
+
+        fn box_clone(&self) -> Box<dyn JwsSigner> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[test]
+    fn test_jws_compact_serialization_with_external_signer() -> Result<()> {
+        let signer = ExternalSigner {
+            messages: Arc::new(Mutex::new(Vec::new())),
+        };
+
+        let header = JwsHeader::new();
+        let jws = jws::serialize_compact(b"test payload!", &header, &signer)?;
+
+        let parts: Vec<&str> = jws.split('.').collect();
+        let messages = signer.messages.lock().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0], format!("{}.{}", parts[0], parts[1]).as_bytes());
+        assert_eq!(
+            base64::decode_config(parts[2], base64::URL_SAFE_NO_PAD)?,
+            b"canned signature"
+        );
+
+        let header = base64::decode_config(parts[0], base64::URL_SAFE_NO_PAD)?;
+        let header = JwsHeader::from_map(serde_json::from_slice::<Map<String, Value>>(&header)?)?;
+        assert_eq!(header.algorithm(), Some("RS256"));
+        assert_eq!(header.key_id(), Some("hsm-key-1"));
+
+        Ok(())
+    }
+
     fn load_file(path: &str) -> Result<Vec<u8>> {
         let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         pb.push("data");
@@ -1,18 +1,23 @@
-use std::fmt::Display;
-use std::ops::Deref;
+use std::fmt::{Debug, Display};
+use std::hint;
+use std::ops::{Deref, DerefMut};
 
 use anyhow::bail;
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::EcPoint;
+use openssl::hash::{Hasher, MessageDigest};
 use openssl::pkey::{PKey, Private, Public};
 use openssl::sign::{Signer, Verifier};
 
 use crate::jwk::{
+    self,
     alg::ec::{EcCurve, EcKeyPair},
     Jwk,
 };
 use crate::jws::{JwsAlgorithm, JwsSigner, JwsVerifier};
 use crate::util::der::{DerBuilder, DerReader, DerType};
 use crate::util::{self, HashAlgorithm};
-use crate::{JoseError, Value};
+use crate::{JoseError, JwkParameterError, Value};
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum EcdsaJwsAlgorithm {
@@ -70,6 +75,7 @@ impl EcdsaJwsAlgorithm {
             algorithm: self.clone(),
             private_key: key_pair.into_private_key(),
             key_id: None,
+            deterministic: false,
         })
     }
 
@@ -89,6 +95,7 @@ impl EcdsaJwsAlgorithm {
             algorithm: self.clone(),
             private_key: key_pair.into_private_key(),
             key_id: None,
+            deterministic: false,
         })
     }
 
@@ -114,7 +121,7 @@ impl EcdsaJwsAlgorithm {
             match jwk.curve() {
                 Some(val) if val == self.curve().name() => {}
                 Some(val) => bail!("A parameter crv must be {} but {}", self.name(), val),
-                None => bail!("A parameter crv is required."),
+                None => bail!(JwkParameterError::missing("crv")),
             }
 
             let key_pair = EcKeyPair::from_jwk(jwk)?;
@@ -125,6 +132,7 @@ impl EcdsaJwsAlgorithm {
                 algorithm: self.clone(),
                 private_key,
                 key_id,
+                deterministic: false,
             })
         })()
         .map_err(|err| JoseError::InvalidKeyFormat(err))
@@ -132,6 +140,8 @@ impl EcdsaJwsAlgorithm {
 
     /// Return a verifier from a public key that is a DER encoded SubjectPublicKeyInfo.
     ///
+    /// The public key can be either an uncompressed or a compressed point.
+    ///
     /// # Arguments
     /// * `input` - A public key that is a DER encoded SubjectPublicKeyInfo.
     pub fn verifier_from_der(
@@ -162,6 +172,7 @@ impl EcdsaJwsAlgorithm {
     ///
     /// Common PEM format is a DER and base64 encoded SubjectPublicKeyInfo
     /// that surrounded by "-----BEGIN/END PUBLIC KEY----".
+    /// The public key can be either an uncompressed or a compressed point.
     ///
     /// # Arguments
     /// * `input` - A public key of common or traditional PEM format.
@@ -195,6 +206,9 @@ impl EcdsaJwsAlgorithm {
 
     /// Return a verifier from a public key that is formatted by a JWK of EC type.
     ///
+    /// If the y parameter is absent, the x parameter is accepted as
+    /// a compressed point (0x02 or 0x03 followed by the x coordinate).
+    ///
     /// # Arguments
     /// * `jwk` - A public key that is formatted by a JWK of EC type.
     pub fn verifier_from_jwk(&self, jwk: &Jwk) -> Result<EcdsaJwsVerifier, JoseError> {
@@ -223,25 +237,33 @@ impl EcdsaJwsAlgorithm {
                 Some(Value::String(val)) => {
                     bail!("A parameter crv must be {} but {}", curve.name(), val)
                 }
-                Some(_) => bail!("A parameter crv must be a string."),
-                None => bail!("A parameter crv is required."),
+                Some(_) => bail!(JwkParameterError::wrong_type("crv")),
+                None => bail!(JwkParameterError::missing("crv")),
             }
             let x = match jwk.parameter("x") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter x must be a string."),
-                None => bail!("A parameter x is required."),
+                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                    .map_err(|err| JwkParameterError::decode_error("x", err))?,
+                Some(_) => bail!(JwkParameterError::wrong_type("x")),
+                None => bail!(JwkParameterError::missing("x")),
             };
-            let y = match jwk.parameter("y") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter y must be a string."),
-                None => bail!("A parameter y is required."),
+            let vec = match jwk.parameter("y") {
+                Some(Value::String(val)) => {
+                    let y = base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                        .map_err(|err| JwkParameterError::decode_error("y", err))?;
+
+                    let mut vec = Vec::with_capacity(1 + x.len() + y.len());
+                    vec.push(0x04);
+                    vec.extend_from_slice(&x);
+                    vec.extend_from_slice(&y);
+                    vec
+                }
+                Some(_) => bail!(JwkParameterError::wrong_type("y")),
+                None => match x.first() {
+                    Some(0x02) | Some(0x03) => curve.decompress_point(&x)?,
+                    _ => bail!(JwkParameterError::missing("y")),
+                },
             };
 
-            let mut vec = Vec::with_capacity(1 + x.len() + y.len());
-            vec.push(0x04);
-            vec.extend_from_slice(&x);
-            vec.extend_from_slice(&y);
-
             let pkcs8 = EcKeyPair::to_pkcs8(&vec, true, self.curve());
             let public_key = PKey::public_key_from_der(&pkcs8)?;
             let key_id = jwk.key_id().map(|val| val.to_string());
@@ -311,11 +333,23 @@ impl Deref for EcdsaJwsAlgorithm {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct EcdsaJwsSigner {
     algorithm: EcdsaJwsAlgorithm,
     private_key: PKey<Private>,
     key_id: Option<String>,
+    deterministic: bool,
+}
+
+impl Debug for EcdsaJwsSigner {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("EcdsaJwsSigner")
+            .field("algorithm", &self.algorithm)
+            .field("private_key", &format_args!("[REDACTED]"))
+            .field("key_id", &self.key_id)
+            .field("deterministic", &self.deterministic)
+            .finish()
+    }
 }
 
 impl EcdsaJwsSigner {
@@ -326,6 +360,193 @@ impl EcdsaJwsSigner {
     pub fn remove_key_id(&mut self) {
         self.key_id = None;
     }
+
+    /// Return whether this signer generates deterministic signatures (RFC 6979).
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    /// Set whether this signer generates deterministic signatures (RFC 6979).
+    ///
+    /// A deterministic signature uses the nonce that is derived from the private key
+    /// and the message by HMAC_DRBG instead of a random nonce,
+    /// and its S value is normalized to the lower half of the curve order.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - true to generate deterministic signatures
+    pub fn set_deterministic(&mut self, value: bool) {
+        self.deterministic = value;
+    }
+
+    fn sign_deterministic(&self, message: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let md = self.algorithm.hash_algorithm().message_digest();
+        let ec_key = self.private_key.ec_key()?;
+        let group = ec_key.group();
+        let d = SecretBigNum::from(ec_key.private_key().to_owned()?);
+
+        let mut ctx = BigNumContext::new()?;
+        let mut order = BigNum::new()?;
+        group.order(&mut order, &mut ctx)?;
+        let qlen = order.num_bits() as usize;
+        let rlen = order.num_bytes() as usize;
+
+        let h1 = hash(md, message)?;
+        let e = bits2int(&h1, qlen)?;
+        let mut z2 = BigNum::new()?;
+        z2.nnmod(&e, &order, &mut ctx)?;
+
+        let mut seed = SecretBytes(Vec::with_capacity(rlen * 2));
+        seed.extend_from_slice(&SecretBytes(d.to_vec_padded(rlen as i32)?));
+        seed.extend_from_slice(&z2.to_vec_padded(rlen as i32)?);
+
+        // RFC 6979 3.2. Generation of k
+        let hlen = md.size();
+        let mut v = SecretBytes(vec![0x01; hlen]);
+        let mut k = SecretBytes(hmac(md, &vec![0x00; hlen], &[&v, &[0x00], &seed])?);
+        v = SecretBytes(hmac(md, &k, &[&v])?);
+        k = SecretBytes(hmac(md, &k, &[&v, &[0x01], &seed])?);
+        v = SecretBytes(hmac(md, &k, &[&v])?);
+
+        loop {
+            let mut t = SecretBytes(Vec::with_capacity(rlen + hlen));
+            while t.len() < rlen {
+                v = SecretBytes(hmac(md, &k, &[&v])?);
+                t.extend_from_slice(&v);
+            }
+
+            let nonce = bits2int(&t, qlen)?;
+            if nonce.num_bits() > 0 && *nonce < order {
+                let mut point = EcPoint::new(group)?;
+                point.mul_generator2(group, &nonce, &mut ctx)?;
+
+                let mut x = BigNum::new()?;
+                let mut y = BigNum::new()?;
+                point.affine_coordinates(group, &mut x, &mut y, &mut ctx)?;
+
+                let mut r = BigNum::new()?;
+                r.nnmod(&x, &order, &mut ctx)?;
+
+                if r.num_bits() > 0 {
+                    let mut rd = SecretBigNum::new()?;
+                    rd.mod_mul(&r, &d, &order, &mut ctx)?;
+                    let mut sum = SecretBigNum::new()?;
+                    sum.mod_add(&z2, &rd, &order, &mut ctx)?;
+                    let mut nonce_inv = SecretBigNum::new()?;
+                    nonce_inv.mod_inverse(&nonce, &order, &mut ctx)?;
+                    let mut s = BigNum::new()?;
+                    s.mod_mul(&nonce_inv, &sum, &order, &mut ctx)?;
+
+                    let mut half_order = BigNum::new()?;
+                    half_order.rshift1(&order)?;
+                    if s > half_order {
+                        let mut low_s = BigNum::new()?;
+                        low_s.checked_sub(&order, &s)?;
+                        s = low_s;
+                    }
+
+                    if s.num_bits() > 0 {
+                        let sep = self.signature_len() / 2;
+                        let mut signature = Vec::with_capacity(sep * 2);
+                        signature.extend_from_slice(&r.to_vec_padded(sep as i32)?);
+                        signature.extend_from_slice(&s.to_vec_padded(sep as i32)?);
+                        return Ok(signature);
+                    }
+                }
+            }
+
+            k = SecretBytes(hmac(md, &k, &[&v, &[0x00]])?);
+            v = SecretBytes(hmac(md, &k, &[&v])?);
+        }
+    }
+}
+
+fn hash(md: MessageDigest, message: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut hasher = Hasher::new(md)?;
+    hasher.update(message)?;
+    Ok(hasher.finish()?.to_vec())
+}
+
+fn hmac(md: MessageDigest, key: &[u8], parts: &[&[u8]]) -> anyhow::Result<Vec<u8>> {
+    let pkey = PKey::hmac(key)?;
+    let mut signer = Signer::new(md, &pkey)?;
+    for part in parts {
+        signer.update(part)?;
+    }
+    Ok(signer.sign_to_vec()?)
+}
+
+fn bits2int(input: &[u8], qlen: usize) -> anyhow::Result<SecretBigNum> {
+    let mut val = SecretBigNum::from(BigNum::from_slice(input)?);
+    let blen = input.len() * 8;
+    if blen > qlen {
+        let mut shifted = SecretBigNum::new()?;
+        shifted.rshift(&val, (blen - qlen) as i32)?;
+        val = shifted;
+    }
+    Ok(val)
+}
+
+/// A big number that holds a secret such as a private key or a nonce.
+///
+/// It is computed in constant time and erased when dropped.
+struct SecretBigNum(BigNum);
+
+impl SecretBigNum {
+    fn new() -> anyhow::Result<Self> {
+        Ok(Self::from(BigNum::new()?))
+    }
+}
+
+impl From<BigNum> for SecretBigNum {
+    fn from(mut value: BigNum) -> Self {
+        value.set_const_time();
+        Self(value)
+    }
+}
+
+impl Deref for SecretBigNum {
+    type Target = BigNum;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for SecretBigNum {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Drop for SecretBigNum {
+    fn drop(&mut self) {
+        self.0.clear();
+    }
+}
+
+/// Bytes that hold a secret. They are overwritten with zeros when dropped.
+struct SecretBytes(Vec<u8>);
+
+impl Deref for SecretBytes {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for SecretBytes {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        self.0.fill(0);
+        hint::black_box(&self.0);
+    }
 }
 
 impl JwsSigner for EcdsaJwsSigner {
@@ -346,6 +567,10 @@ impl JwsSigner for EcdsaJwsSigner {
 
     fn sign(&self, message: &[u8]) -> Result<Vec<u8>, JoseError> {
         (|| -> anyhow::Result<Vec<u8>> {
+            if self.deterministic {
+                return self.sign_deterministic(message);
+            }
+
             let md = self.algorithm.hash_algorithm().message_digest();
 
             let mut signer = Signer::new(md, &self.private_key)?;
@@ -374,11 +599,24 @@ impl JwsSigner for EcdsaJwsSigner {
                 _ => unreachable!("A generated signature is invalid."),
             }
 
+            // Each of r and s is left-padded to the byte length of the curve order.
+            if signature.len() != signature_len {
+                bail!(
+                    "A signature size must be {}: {}",
+                    signature_len,
+                    signature.len()
+                );
+            }
+
             Ok(signature)
         })()
         .map_err(|err| JoseError::InvalidSignature(err))
     }
 
+    fn public_key_jwk(&self) -> Option<Jwk> {
+        jwk::public_key_jwk_from_private_key(&self.private_key).ok()
+    }
+
     fn box_clone(&self) -> Box<dyn JwsSigner> {
         Box::new(self.clone())
     }
@@ -475,9 +713,155 @@ mod tests {
     use super::*;
 
     use anyhow::Result;
+    use openssl::ecdsa::EcdsaSig;
     use std::fs;
     use std::path::PathBuf;
 
+    #[test]
+    fn verify_ecdsa_with_compressed_public_key() -> Result<()> {
+        let input = b"abcde12345";
+        let alg = EcdsaJwsAlgorithm::Es256;
+
+        let private_key = load_file("pem/EC_P-256_private.pem")?;
+        let signer = alg.signer_from_pem(&private_key)?;
+        let signature = signer.sign(input)?;
+
+        let public_key = load_file("pem/EC_P-256_compressed_public.pem")?;
+        let verifier = alg.verifier_from_pem(&public_key)?;
+        verifier.verify(input, &signature)?;
+
+        let public_key = load_file("der/EC_P-256_compressed_spki_public.der")?;
+        let verifier = alg.verifier_from_der(&public_key)?;
+        verifier.verify(input, &signature)?;
+
+        // The compressed point is at the end of SubjectPublicKeyInfo.
+        let point = &public_key[public_key.len() - 33..];
+        let mut jwk = Jwk::new("EC");
+        jwk.set_curve("P-256");
+        jwk.set_parameter(
+            "x",
+            Some(Value::String(base64::encode_config(
+                point,
+                base64::URL_SAFE_NO_PAD,
+            ))),
+        )?;
+        let verifier = alg.verifier_from_jwk(&jwk)?;
+        verifier.verify(input, &signature)?;
+
+        let mut invalid = point.to_vec();
+        invalid[0] = 0x04;
+        jwk.set_parameter(
+            "x",
+            Some(Value::String(base64::encode_config(
+                &invalid,
+                base64::URL_SAFE_NO_PAD,
+            ))),
+        )?;
+        assert!(alg.verifier_from_jwk(&jwk).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sign_ecdsa_deterministic_rfc6979() -> Result<()> {
+        // RFC 6979 A.2.5. ECDSA, 256 Bits (Prime Field)
+        let group = openssl::ec::EcGroup::from_curve_name(openssl::nid::Nid::X9_62_PRIME256V1)?;
+        let d = BigNum::from_hex_str(
+            "C9AFA9D845BA75166B5C215767B1D6934E50C3DB36E89B127B8A622B120F6721",
+        )?;
+        let mut ctx = BigNumContext::new()?;
+        let mut public_key = EcPoint::new(&group)?;
+        public_key.mul_generator2(&group, &d, &mut ctx)?;
+        let ec_key = openssl::ec::EcKey::from_private_components(&group, &d, &public_key)?;
+        let private_key = PKey::from_ec_key(ec_key)?;
+
+        let mut signer = EcdsaJwsSigner {
+            algorithm: EcdsaJwsAlgorithm::Es256,
+            private_key,
+            key_id: None,
+            deterministic: false,
+        };
+        signer.set_deterministic(true);
+
+        let mut order = BigNum::new()?;
+        group.order(&mut order, &mut ctx)?;
+
+        for (message, r, s) in &[
+            (
+                "sample",
+                "EFD48B2AACB6A8FD1140DD9CD45E81D69D2C877B56AAF991C34D0EA84EAF3716",
+                "F7CB1C942D657C41D436C7A1B6E29F65F3E900DBB9AFF4064DC4AB2F843ACDA8",
+            ),
+            (
+                "test",
+                "F1ABB023518351CD71D881567B1EA663ED3EFCF6C5132B354F28D3B0B7D38367",
+                "019F4113742A2B14BD25926B49C649155F267E60D3814B4C0CC84250E46F0083",
+            ),
+        ] {
+            let r = BigNum::from_hex_str(r)?;
+            let mut s = BigNum::from_hex_str(s)?;
+            let mut half_order = BigNum::new()?;
+            half_order.rshift1(&order)?;
+            if s > half_order {
+                let mut low_s = BigNum::new()?;
+                low_s.checked_sub(&order, &s)?;
+                s = low_s;
+            }
+
+            let mut expected = r.to_vec_padded(32)?;
+            expected.extend_from_slice(&s.to_vec_padded(32)?);
+
+            let signature = signer.sign(message.as_bytes())?;
+            assert_eq!(signature, expected);
+            assert_eq!(signer.sign(message.as_bytes())?, signature);
+
+            let public_key = PKey::public_key_from_der(&signer.private_key.public_key_to_der()?)?;
+            let verifier = EcdsaJwsVerifier {
+                algorithm: EcdsaJwsAlgorithm::Es256,
+                public_key,
+                key_id: None,
+            };
+            verifier.verify(message.as_bytes(), &signature)?;
+        }
+
+        for alg in &[
+            EcdsaJwsAlgorithm::Es256,
+            EcdsaJwsAlgorithm::Es384,
+            EcdsaJwsAlgorithm::Es512,
+            EcdsaJwsAlgorithm::Es256k,
+        ] {
+            let key_pair = alg.generate_key_pair()?;
+            let mut signer = alg.signer_from_der(key_pair.to_der_private_key())?;
+            signer.set_deterministic(true);
+            let verifier = alg.verifier_from_der(key_pair.to_der_public_key())?;
+
+            let signature = signer.sign(b"abcde12345")?;
+            assert_eq!(signature.len(), signer.signature_len());
+            assert_eq!(signer.sign(b"abcde12345")?, signature);
+            verifier.verify(b"abcde12345", &signature)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn deterministic_nonce_is_constant_time() -> Result<()> {
+        let nonce = bits2int(&[0xAB; 40], 256)?;
+        assert!(nonce.is_const_time());
+        assert_eq!(nonce.num_bits(), 256);
+
+        let group = openssl::ec::EcGroup::from_curve_name(openssl::nid::Nid::X9_62_PRIME256V1)?;
+        let mut ctx = BigNumContext::new()?;
+        let mut modulus = BigNum::new()?;
+        group.order(&mut modulus, &mut ctx)?;
+        let mut secret = SecretBigNum::new()?;
+        assert!(secret.is_const_time());
+        secret.mod_inverse(&nonce, &modulus, &mut ctx)?;
+        assert!(secret.is_const_time());
+
+        Ok(())
+    }
+
     #[test]
     fn sign_and_verify_ecdsa_generated_der() -> Result<()> {
         let input = b"abcde12345";
@@ -712,6 +1096,42 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn sign_es512_with_short_integers() -> Result<()> {
+        let input = b"abcde12345";
+        let alg = EcdsaJwsAlgorithm::Es512;
+
+        let key_pair = alg.generate_key_pair()?;
+        let signer = alg.signer_from_der(key_pair.to_der_private_key())?;
+        let verifier = alg.verifier_from_der(key_pair.to_der_public_key())?;
+        let public_key = PKey::public_key_from_der(&key_pair.to_der_public_key())?;
+        let ec_key = public_key.ec_key()?;
+
+        // A P-521 integer has 66 bytes, and its leading byte is zero about half the time.
+        // Sign until r or s is shorter than 65 bytes so that two bytes of padding are needed.
+        let mut padded = false;
+        for _ in 0..10000 {
+            let signature = signer.sign(input)?;
+            assert_eq!(signature.len(), 132);
+            verifier.verify(input, &signature)?;
+
+            // Verify independently by OpenSSL's own ECDSA_SIG conversion.
+            let r = BigNum::from_slice(&signature[..66])?;
+            let s = BigNum::from_slice(&signature[66..])?;
+            let sig = EcdsaSig::from_private_components(r, s)?;
+            let digest = openssl::hash::hash(MessageDigest::sha512(), input)?;
+            assert!(sig.verify(&digest, &ec_key)?);
+
+            if signature[..2] == [0, 0] || signature[66..68] == [0, 0] {
+                padded = true;
+                break;
+            }
+        }
+        assert!(padded);
+
+        Ok(())
+    }
+
     fn load_file(path: &str) -> Result<Vec<u8>> {
         let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         pb.push("data");
@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 use std::ops::Deref;
 
 use anyhow::bail;
@@ -6,6 +6,7 @@ use openssl::pkey::{PKey, Private, Public};
 use openssl::sign::{Signer, Verifier};
 
 use crate::jwk::{
+    self,
     alg::ed::{EdCurve, EdKeyPair},
     Jwk,
 };
@@ -207,18 +208,20 @@ impl EddsaJwsAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter alg must be {} but {}", self.name(), val),
             }
-            let curve = match jwk.parameter("crv") {
-                Some(Value::String(val)) if val == "Ed25519" => EdCurve::Ed25519,
-                Some(Value::String(val)) if val == "Ed448" => EdCurve::Ed448,
-                Some(Value::String(val)) => bail!("A parameter crv must is invalid: {}", val),
-                Some(_) => bail!("A parameter crv must be a string."),
-                None => bail!("A parameter crv is required."),
-            };
+            let curve = EdCurve::from_jwk(jwk)?;
             let x = match jwk.parameter("x") {
                 Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
                 Some(_) => bail!("A parameter x must be a string."),
                 None => bail!("A parameter x is required."),
             };
+            if x.len() != curve.key_len() {
+                bail!(
+                    "A parameter x of {} must be {} bytes: {}",
+                    curve,
+                    curve.key_len(),
+                    x.len()
+                );
+            }
 
             let pkcs8 = EdKeyPair::to_pkcs8(&x, true, curve);
             let public_key = PKey::public_key_from_der(&pkcs8)?;
@@ -258,7 +261,7 @@ impl Deref for EddsaJwsAlgorithm {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct EddsaJwsSigner {
     algorithm: EddsaJwsAlgorithm,
     curve: EdCurve,
@@ -266,6 +269,17 @@ pub struct EddsaJwsSigner {
     key_id: Option<String>,
 }
 
+impl Debug for EddsaJwsSigner {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("EddsaJwsSigner")
+            .field("algorithm", &self.algorithm)
+            .field("curve", &self.curve)
+            .field("private_key", &format_args!("[REDACTED]"))
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
 impl EddsaJwsSigner {
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
@@ -305,6 +319,10 @@ impl JwsSigner for EddsaJwsSigner {
         .map_err(|err| JoseError::InvalidSignature(err))
     }
 
+    fn public_key_jwk(&self) -> Option<Jwk> {
+        jwk::public_key_jwk_from_private_key(&self.private_key).ok()
+    }
+
     fn box_clone(&self) -> Box<dyn JwsSigner> {
         Box::new(self.clone())
     }
@@ -379,6 +397,36 @@ mod tests {
     use std::fs;
     use std::path::PathBuf;
 
+    #[test]
+    fn sign_and_verify_eddsa_jwk_with_curve_check() -> Result<()> {
+        let input = b"abcde12345";
+        let alg = EddsaJwsAlgorithm::Eddsa;
+
+        let private_key = Jwk::from_bytes(load_file("jwk/OKP_Ed25519_private.jwk")?)?;
+        let public_key = Jwk::from_bytes(load_file("jwk/OKP_Ed25519_public.jwk")?)?;
+        let signer = alg.signer_from_jwk(&private_key)?;
+        let signature = signer.sign(input)?;
+        let verifier = alg.verifier_from_jwk(&public_key)?;
+        verifier.verify(input, &signature)?;
+
+        for path in &["jwk/OKP_X25519_private.jwk", "jwk/OKP_X448_private.jwk"] {
+            let private_key = Jwk::from_bytes(load_file(path)?)?;
+            let err = alg.signer_from_jwk(&private_key).unwrap_err();
+            assert!(err.to_string().contains("key agreement"), "{}", err);
+            assert!(alg.verifier_from_jwk(&private_key.to_public()).is_err());
+        }
+
+        let other_key = Jwk::from_bytes(load_file("jwk/OKP_Ed448_public.jwk")?)?;
+        let mut mismatched = private_key.clone();
+        mismatched.set_parameter("crv", Some(Value::String("Ed448".to_string())))?;
+        assert!(alg.signer_from_jwk(&mismatched).is_err());
+        let mut mismatched = private_key.clone();
+        mismatched.set_parameter("x", other_key.parameter("x").cloned())?;
+        assert!(alg.signer_from_jwk(&mismatched).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn sign_and_verify_eddsa_generated_der() -> Result<()> {
         let input = b"abcde12345";
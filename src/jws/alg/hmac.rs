@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 use std::ops::Deref;
 
 use anyhow::bail;
@@ -42,10 +42,59 @@ impl HmacJwsAlgorithm {
     /// # Arguments
     /// * `data` - A secret key.
     pub fn signer_from_bytes(&self, input: impl AsRef<[u8]>) -> Result<HmacJwsSigner, JoseError> {
+        self.signer_from_bytes_with_min_key_len(input, self.hash_algorithm().output_len())
+    }
+
+    /// Return a signer from a secret key that is formatted by a JWK of oct type.
+    ///
+    /// # Arguments
+    /// * `jwk` - A secret key that is formatted by a JWK of oct type.
+    pub fn signer_from_jwk(&self, jwk: &Jwk) -> Result<HmacJwsSigner, JoseError> {
+        self.signer_from_jwk_with_min_key_len(jwk, self.hash_algorithm().output_len())
+    }
+
+    /// Return a verifier from a secret key.
+    ///
+    /// # Arguments
+    /// * `input` - A secret key.
+    pub fn verifier_from_bytes(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<HmacJwsVerifier, JoseError> {
+        self.verifier_from_bytes_with_min_key_len(input, self.hash_algorithm().output_len())
+    }
+
+    /// Return a verifier from a secret key that is formatted by a JWK of oct type.
+    ///
+    /// # Arguments
+    /// * `jwk` - A secret key that is formatted by a JWK of oct type.
+    pub fn verifier_from_jwk(&self, jwk: &Jwk) -> Result<HmacJwsVerifier, JoseError> {
+        self.verifier_from_jwk_with_min_key_len(jwk, self.hash_algorithm().output_len())
+    }
+
+    /// Return a key length policy of this algorithm that requires a minimum secret key length.
+    ///
+    /// By default, a secret key must be at least as long as the hash output
+    /// (32 bytes for HS256, 48 bytes for HS384 and 64 bytes for HS512).
+    /// A shorter minimum length can be used for interoperability with legacy systems.
+    ///
+    /// # Arguments
+    /// * `len` - A minimum secret key length in bytes
+    pub fn with_min_key_len(&self, len: usize) -> HmacJwsKeyPolicy {
+        HmacJwsKeyPolicy {
+            algorithm: *self,
+            min_key_len: len,
+        }
+    }
+
+    fn signer_from_bytes_with_min_key_len(
+        &self,
+        input: impl AsRef<[u8]>,
+        min_key_len: usize,
+    ) -> Result<HmacJwsSigner, JoseError> {
         (|| -> anyhow::Result<HmacJwsSigner> {
             let input = input.as_ref();
 
-            let min_key_len = self.hash_algorithm().output_len();
             if input.len() < min_key_len {
                 bail!(
                     "Secret key size must be larger than or equal to {}: {}",
@@ -65,11 +114,11 @@ impl HmacJwsAlgorithm {
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
-    /// Return a signer from a secret key that is formatted by a JWK of oct type.
-    ///
-    /// # Arguments
-    /// * `jwk` - A secret key that is formatted by a JWK of oct type.
-    pub fn signer_from_jwk(&self, jwk: &Jwk) -> Result<HmacJwsSigner, JoseError> {
+    fn signer_from_jwk_with_min_key_len(
+        &self,
+        jwk: &Jwk,
+        min_key_len: usize,
+    ) -> Result<HmacJwsSigner, JoseError> {
         (|| -> anyhow::Result<HmacJwsSigner> {
             match jwk.key_type() {
                 val if val == "oct" => {}
@@ -94,7 +143,6 @@ impl HmacJwsAlgorithm {
                 None => bail!("A parameter k is required."),
             };
 
-            let min_key_len = self.hash_algorithm().output_len();
             if k.len() < min_key_len {
                 bail!(
                     "Secret key size must be larger than or equal to {}: {}",
@@ -115,18 +163,14 @@ impl HmacJwsAlgorithm {
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
-    /// Return a verifier from a secret key.
-    ///
-    /// # Arguments
-    /// * `input` - A secret key.
-    pub fn verifier_from_bytes(
+    fn verifier_from_bytes_with_min_key_len(
         &self,
         input: impl AsRef<[u8]>,
+        min_key_len: usize,
     ) -> Result<HmacJwsVerifier, JoseError> {
         (|| -> anyhow::Result<HmacJwsVerifier> {
             let input = input.as_ref();
 
-            let min_key_len = self.hash_algorithm().output_len();
             if input.len() < min_key_len {
                 bail!(
                     "Secret key size must be larger than or equal to {}: {}",
@@ -146,11 +190,11 @@ impl HmacJwsAlgorithm {
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
-    /// Return a verifier from a secret key that is formatted by a JWK of oct type.
-    ///
-    /// # Arguments
-    /// * `jwk` - A secret key that is formatted by a JWK of oct type.
-    pub fn verifier_from_jwk(&self, jwk: &Jwk) -> Result<HmacJwsVerifier, JoseError> {
+    fn verifier_from_jwk_with_min_key_len(
+        &self,
+        jwk: &Jwk,
+        min_key_len: usize,
+    ) -> Result<HmacJwsVerifier, JoseError> {
         (|| -> anyhow::Result<HmacJwsVerifier> {
             match jwk.key_type() {
                 val if val == "oct" => {}
@@ -176,7 +220,6 @@ impl HmacJwsAlgorithm {
                 None => bail!("A parameter k is required."),
             };
 
-            let min_key_len = self.hash_algorithm().output_len();
             if k.len() < min_key_len {
                 bail!(
                     "Secret key size must be larger than or equal to {}: {}",
@@ -234,13 +277,81 @@ impl Deref for HmacJwsAlgorithm {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Represents a HMAC algorithm with a minimum secret key length policy.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct HmacJwsKeyPolicy {
+    algorithm: HmacJwsAlgorithm,
+    min_key_len: usize,
+}
+
+impl HmacJwsKeyPolicy {
+    /// Return the source algorithm.
+    pub fn algorithm(&self) -> HmacJwsAlgorithm {
+        self.algorithm
+    }
+
+    /// Return the minimum secret key length in bytes.
+    pub fn min_key_len(&self) -> usize {
+        self.min_key_len
+    }
+
+    /// Return a signer from a secret key.
+    ///
+    /// # Arguments
+    /// * `input` - A secret key.
+    pub fn signer_from_bytes(&self, input: impl AsRef<[u8]>) -> Result<HmacJwsSigner, JoseError> {
+        self.algorithm
+            .signer_from_bytes_with_min_key_len(input, self.min_key_len)
+    }
+
+    /// Return a signer from a secret key that is formatted by a JWK of oct type.
+    ///
+    /// # Arguments
+    /// * `jwk` - A secret key that is formatted by a JWK of oct type.
+    pub fn signer_from_jwk(&self, jwk: &Jwk) -> Result<HmacJwsSigner, JoseError> {
+        self.algorithm
+            .signer_from_jwk_with_min_key_len(jwk, self.min_key_len)
+    }
+
+    /// Return a verifier from a secret key.
+    ///
+    /// # Arguments
+    /// * `input` - A secret key.
+    pub fn verifier_from_bytes(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<HmacJwsVerifier, JoseError> {
+        self.algorithm
+            .verifier_from_bytes_with_min_key_len(input, self.min_key_len)
+    }
+
+    /// Return a verifier from a secret key that is formatted by a JWK of oct type.
+    ///
+    /// # Arguments
+    /// * `jwk` - A secret key that is formatted by a JWK of oct type.
+    pub fn verifier_from_jwk(&self, jwk: &Jwk) -> Result<HmacJwsVerifier, JoseError> {
+        self.algorithm
+            .verifier_from_jwk_with_min_key_len(jwk, self.min_key_len)
+    }
+}
+
+#[derive(Clone)]
 pub struct HmacJwsSigner {
     algorithm: HmacJwsAlgorithm,
     private_key: PKey<Private>,
     key_id: Option<String>,
 }
 
+impl Debug for HmacJwsSigner {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("HmacJwsSigner")
+            .field("algorithm", &self.algorithm)
+            .field("private_key", &format_args!("[REDACTED]"))
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
 impl HmacJwsSigner {
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
@@ -292,13 +403,23 @@ impl Deref for HmacJwsSigner {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct HmacJwsVerifier {
     algorithm: HmacJwsAlgorithm,
     private_key: PKey<Private>,
     key_id: Option<String>,
 }
 
+impl Debug for HmacJwsVerifier {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("HmacJwsVerifier")
+            .field("algorithm", &self.algorithm)
+            .field("private_key", &format_args!("[REDACTED]"))
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
 impl HmacJwsVerifier {
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
@@ -423,6 +544,34 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn sign_and_verify_hmac_short_key() -> Result<()> {
+        let private_key = util::random_bytes(16);
+        let input = b"abcde12345";
+
+        let alg = HmacJwsAlgorithm::Hs256;
+        assert!(alg.signer_from_bytes(&private_key).is_err());
+        assert!(alg.verifier_from_bytes(&private_key).is_err());
+        assert!(alg.signer_from_jwk(&alg.to_jwk(&private_key)).is_err());
+
+        let policy = alg.with_min_key_len(16);
+        assert_eq!(policy.algorithm(), alg);
+        assert_eq!(policy.min_key_len(), 16);
+
+        let signer = policy.signer_from_bytes(&private_key)?;
+        let signature = signer.sign(input)?;
+
+        let verifier = policy.verifier_from_jwk(&alg.to_jwk(&private_key))?;
+        verifier.verify(input, &signature)?;
+
+        assert!(alg
+            .with_min_key_len(17)
+            .signer_from_bytes(&private_key)
+            .is_err());
+
+        Ok(())
+    }
+
     fn load_file(path: &str) -> Result<Vec<u8>> {
         let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         pb.push("data");
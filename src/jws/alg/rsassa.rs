@@ -1,17 +1,24 @@
-use std::fmt::Display;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display};
 use std::ops::Deref;
 
 use anyhow::bail;
+use openssl::bn::BigNum;
 use openssl::pkey::{PKey, Private, Public};
 use openssl::sign::{Signer, Verifier};
 
-use crate::jwk::{alg::rsa::RsaKeyPair, Jwk};
+use crate::jwk::{self, alg::rsa::RsaKeyPair, Jwk};
 use crate::jws::{JwsAlgorithm, JwsSigner, JwsVerifier};
 use crate::util::der::{DerBuilder, DerType};
 use crate::util::{self, HashAlgorithm};
-use crate::{JoseError, Value};
+use crate::{JoseError, JwkParameterError, Value};
 
+/// Represents RSASSA-PKCS1-v1_5 signature algorithms.
+///
+/// This enum is non-exhaustive because the `legacy-algorithms` feature adds
+/// the `Rs1` variant.
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[non_exhaustive]
 pub enum RsassaJwsAlgorithm {
     /// RSASSA-PKCS1-v1_5 using SHA-256
     Rs256,
@@ -21,6 +28,14 @@ pub enum RsassaJwsAlgorithm {
 
     /// RSASSA-PKCS1-v1_5 using SHA-512
     Rs512,
+
+    /// RSASSA-PKCS1-v1_5 using SHA-1
+    ///
+    /// **Warning**: SHA-1 is broken for collision resistance and RS1 is not
+    /// registered for JWS. It is only available for verifying tokens of legacy
+    /// systems, and a signer of this algorithm cannot be created.
+    #[cfg(feature = "legacy-algorithms")]
+    Rs1,
 }
 
 impl RsassaJwsAlgorithm {
@@ -97,6 +112,7 @@ impl RsassaJwsAlgorithm {
     /// # Arguments
     /// * `input` - A private key that is a DER encoded PKCS#8 PrivateKeyInfo or PKCS#1 RSAPrivateKey.
     pub fn signer_from_der(&self, input: impl AsRef<[u8]>) -> Result<RsassaJwsSigner, JoseError> {
+        self.check_signing_allowed()?;
         let key_pair = self.key_pair_from_der(input.as_ref())?;
         Ok(RsassaJwsSigner {
             algorithm: self.clone(),
@@ -116,6 +132,7 @@ impl RsassaJwsAlgorithm {
     /// # Arguments
     /// * `input` - A private key of common or traditinal PEM format.
     pub fn signer_from_pem(&self, input: impl AsRef<[u8]>) -> Result<RsassaJwsSigner, JoseError> {
+        self.check_signing_allowed()?;
         let key_pair = self.key_pair_from_pem(input.as_ref())?;
         Ok(RsassaJwsSigner {
             algorithm: self.clone(),
@@ -129,6 +146,7 @@ impl RsassaJwsAlgorithm {
     /// # Arguments
     /// * `jwk` - A private key that is formatted by a JWK of RSA type.
     pub fn signer_from_jwk(&self, jwk: &Jwk) -> Result<RsassaJwsSigner, JoseError> {
+        self.check_signing_allowed()?;
         (|| -> anyhow::Result<RsassaJwsSigner> {
             match jwk.key_use() {
                 Some(val) if val == "sig" => {}
@@ -266,16 +284,20 @@ impl RsassaJwsAlgorithm {
             }
 
             let n = match jwk.parameter("n") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter n must be a string."),
-                None => bail!("A parameter n is required."),
+                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                    .map_err(|err| JwkParameterError::decode_error("n", err))?,
+                Some(_) => bail!(JwkParameterError::wrong_type("n")),
+                None => bail!(JwkParameterError::missing("n")),
             };
             let e = match jwk.parameter("e") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter e must be a string."),
-                None => bail!("A parameter e is required."),
+                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                    .map_err(|err| JwkParameterError::decode_error("e", err))?,
+                Some(_) => bail!(JwkParameterError::wrong_type("e")),
+                None => bail!(JwkParameterError::missing("e")),
             };
 
+            RsaKeyPair::check_jwk_lengths(&n, &e, &[])?;
+
             let mut builder = DerBuilder::new();
             builder.begin(DerType::Sequence);
             {
@@ -307,7 +329,20 @@ impl RsassaJwsAlgorithm {
             Self::Rs256 => HashAlgorithm::Sha256,
             Self::Rs384 => HashAlgorithm::Sha384,
             Self::Rs512 => HashAlgorithm::Sha512,
+            #[cfg(feature = "legacy-algorithms")]
+            Self::Rs1 => HashAlgorithm::Sha1,
+        }
+    }
+
+    fn check_signing_allowed(&self) -> Result<(), JoseError> {
+        #[cfg(feature = "legacy-algorithms")]
+        if let Self::Rs1 = self {
+            return Err(JoseError::UnsupportedSignatureAlgorithm(anyhow::anyhow!(
+                "{} is only supported for verification.",
+                self.name()
+            )));
         }
+        Ok(())
     }
 }
 
@@ -317,6 +352,8 @@ impl JwsAlgorithm for RsassaJwsAlgorithm {
             Self::Rs256 => "RS256",
             Self::Rs384 => "RS384",
             Self::Rs512 => "RS512",
+            #[cfg(feature = "legacy-algorithms")]
+            Self::Rs1 => "RS1",
         }
     }
 
@@ -339,13 +376,23 @@ impl Deref for RsassaJwsAlgorithm {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct RsassaJwsSigner {
     algorithm: RsassaJwsAlgorithm,
     private_key: PKey<Private>,
     key_id: Option<String>,
 }
 
+impl Debug for RsassaJwsSigner {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("RsassaJwsSigner")
+            .field("algorithm", &self.algorithm)
+            .field("private_key", &format_args!("[REDACTED]"))
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
 impl RsassaJwsSigner {
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
@@ -384,6 +431,10 @@ impl JwsSigner for RsassaJwsSigner {
         .map_err(|err| JoseError::InvalidSignature(err))
     }
 
+    fn public_key_jwk(&self) -> Option<Jwk> {
+        jwk::public_key_jwk_from_private_key(&self.private_key).ok()
+    }
+
     fn box_clone(&self) -> Box<dyn JwsSigner> {
         Box::new(self.clone())
     }
@@ -412,6 +463,33 @@ impl RsassaJwsVerifier {
     pub fn remove_key_id(&mut self) {
         self.key_id = None;
     }
+
+    /// Return the modulus (n) of the key as big-endian bytes without leading zeros.
+    pub fn modulus(&self) -> Vec<u8> {
+        modulus(&self.public_key)
+    }
+
+    /// Return the public exponent (e) of the key as big-endian bytes without leading zeros.
+    pub fn public_exponent(&self) -> Vec<u8> {
+        public_exponent(&self.public_key)
+    }
+
+    /// Return the bit length of the public exponent (e) of the key.
+    pub fn public_exponent_bits(&self) -> u32 {
+        public_exponent_bits(&self.public_key)
+    }
+
+    /// Return this verifier if the public exponent (e) of the key is not less than a minimum.
+    ///
+    /// No minimum is applied by default, so a key with a small exponent like 3 is accepted.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - a minimum public exponent (e.g. 65537)
+    pub fn with_min_public_exponent(self, min: u32) -> Result<Self, JoseError> {
+        check_min_public_exponent(&self.public_key, min)?;
+        Ok(self)
+    }
 }
 
 impl JwsVerifier for RsassaJwsVerifier {
@@ -453,14 +531,110 @@ impl Deref for RsassaJwsVerifier {
     }
 }
 
+pub(crate) fn modulus(public_key: &PKey<Public>) -> Vec<u8> {
+    match public_key.rsa() {
+        Ok(rsa) => rsa.n().to_vec(),
+        Err(_) => Vec::new(),
+    }
+}
+
+pub(crate) fn public_exponent(public_key: &PKey<Public>) -> Vec<u8> {
+    match public_key.rsa() {
+        Ok(rsa) => rsa.e().to_vec(),
+        Err(_) => Vec::new(),
+    }
+}
+
+pub(crate) fn public_exponent_bits(public_key: &PKey<Public>) -> u32 {
+    match public_key.rsa() {
+        Ok(rsa) => rsa.e().num_bits() as u32,
+        Err(_) => 0,
+    }
+}
+
+pub(crate) fn check_min_public_exponent(
+    public_key: &PKey<Public>,
+    min: u32,
+) -> Result<(), JoseError> {
+    (|| -> anyhow::Result<()> {
+        let rsa = public_key.rsa()?;
+        let min = BigNum::from_u32(min)?;
+        if rsa.e().ucmp(&min) == Ordering::Less {
+            bail!("The public exponent is less than {}: {}", min, rsa.e());
+        }
+        Ok(())
+    })()
+    .map_err(JoseError::InvalidKeyFormat)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::jws::alg::rsassa_pss::RsassaPssJwsAlgorithm;
+    use crate::JwkParameterErrorKind;
+    use openssl::rsa::Rsa;
 
     use anyhow::Result;
     use std::fs;
     use std::path::PathBuf;
 
+    #[test]
+    fn verify_rsassa_with_min_public_exponent() -> Result<()> {
+        let e = BigNum::from_u32(3)?;
+        let rsa = Rsa::generate_with_e(2048, &e)?;
+        let private_key = PKey::from_rsa(rsa)?;
+        let public_key = private_key.public_key_to_der()?;
+
+        let alg = RsassaJwsAlgorithm::Rs256;
+        let verifier = alg.verifier_from_der(&public_key)?;
+        assert_eq!(verifier.public_exponent_bits(), 2);
+        assert!(verifier.with_min_public_exponent(65537).is_err());
+
+        let verifier = alg.verifier_from_der(&public_key)?;
+        let verifier = verifier.with_min_public_exponent(3)?;
+        let signer = alg.signer_from_der(private_key.private_key_to_der()?)?;
+        let signature = signer.sign(b"abcde12345")?;
+        verifier.verify(b"abcde12345", &signature)?;
+
+        let key_pair = alg.generate_key_pair(2048)?;
+        let verifier = alg.verifier_from_der(key_pair.to_der_public_key())?;
+        assert_eq!(verifier.public_exponent_bits(), 17);
+        verifier.with_min_public_exponent(65537)?;
+
+        let alg = RsassaPssJwsAlgorithm::Ps256;
+        let verifier = alg.verifier_from_der(&public_key)?;
+        assert!(verifier.with_min_public_exponent(65537).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_rsassa_jwk_with_inconsistent_lengths() -> Result<()> {
+        let alg = RsassaJwsAlgorithm::Rs256;
+        let private_key = Jwk::from_bytes(&load_file("jwk/RSA_private.jwk")?)?;
+        alg.signer_from_jwk(&private_key)?;
+
+        let mut d = match private_key.parameter("d") {
+            Some(Value::String(val)) => util::decode_base64url(val)?,
+            _ => unreachable!(),
+        };
+        d.insert(0, 0x01);
+        let mut oversized = private_key.clone();
+        oversized.set_parameter("d", Some(Value::String(util::encode_base64url(&d))))?;
+        let err = alg.signer_from_jwk(&oversized).unwrap_err();
+        let param_err = err.jwk_parameter_error().unwrap();
+        assert_eq!(param_err.parameter(), "d");
+        assert_eq!(param_err.kind(), JwkParameterErrorKind::InvalidValue);
+
+        let mut public_key = private_key.to_public_key()?;
+        alg.verifier_from_jwk(&public_key)?;
+        public_key.set_parameter("e", Some(Value::String(util::encode_base64url([1; 9]))))?;
+        let err = alg.verifier_from_jwk(&public_key).unwrap_err();
+        assert_eq!(err.jwk_parameter_error().unwrap().parameter(), "e");
+
+        Ok(())
+    }
+
     #[test]
     fn sign_and_verify_rsassa_generated_der() -> Result<()> {
         let input = b"abcde12345";
@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 use std::ops::Deref;
 
 use anyhow::bail;
@@ -6,11 +6,14 @@ use openssl::pkey::{PKey, Private, Public};
 use openssl::rsa::Rsa;
 use openssl::sign::{Signer, Verifier};
 
-use crate::jwk::{alg::rsa::RsaKeyPair, alg::rsapss::RsaPssKeyPair, Jwk};
-use crate::jws::{JwsAlgorithm, JwsSigner, JwsVerifier};
+use crate::jwk::{self, alg::rsa::RsaKeyPair, alg::rsapss::RsaPssKeyPair, Jwk};
+use crate::jws::alg::rsassa;
+use crate::jws::{JwsAlgorithm, JwsHeader, JwsSigner, JwsVerifier};
 use crate::util::der::{DerBuilder, DerType};
+#[cfg(feature = "pool")]
+use crate::util::pool::MD_CTX_POOL;
 use crate::util::{self, HashAlgorithm};
-use crate::{JoseError, Value};
+use crate::{JoseError, JwkParameterError, Value};
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum RsassaPssJwsAlgorithm {
@@ -48,6 +51,34 @@ impl RsassaPssJwsAlgorithm {
         })
     }
 
+    /// Generate RSA key pair that is restricted to this algorithm.
+    ///
+    /// Unlike generate_key_pair, the key is held by OpenSSL as an RSASSA-PSS key
+    /// that pins the hash algorithm, the MGF1 hash algorithm and the salt length.
+    ///
+    /// # Arguments
+    /// * `bits` - RSA key length
+    pub fn generate_restricted_key_pair(&self, bits: u32) -> Result<RsaPssKeyPair, JoseError> {
+        (|| -> anyhow::Result<RsaPssKeyPair> {
+            if bits < 2048 {
+                bail!("key length must be 2048 or more.");
+            }
+
+            let mut key_pair = RsaPssKeyPair::generate_restricted(
+                bits,
+                self.hash_algorithm(),
+                self.hash_algorithm(),
+                self.salt_len(),
+            )?;
+            key_pair.set_algorithm(Some(self.name()));
+            Ok(key_pair)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidKeyFormat(err),
+        })
+    }
+
     /// Create a RSA-PSS key pair from a private key that is a DER encoded PKCS#8 PrivateKeyInfo or PKCS#1 RSAPrivateKey.
     ///
     /// # Arguments
@@ -335,16 +366,20 @@ impl RsassaPssJwsAlgorithm {
                 Some(val) => bail!("A parameter alg must be {} but {}", self.name(), val),
             }
             let n = match jwk.parameter("n") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter n must be a string."),
-                None => bail!("A parameter n is required."),
+                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                    .map_err(|err| JwkParameterError::decode_error("n", err))?,
+                Some(_) => bail!(JwkParameterError::wrong_type("n")),
+                None => bail!(JwkParameterError::missing("n")),
             };
             let e = match jwk.parameter("e") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter e must be a string."),
-                None => bail!("A parameter e is required."),
+                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                    .map_err(|err| JwkParameterError::decode_error("e", err))?,
+                Some(_) => bail!(JwkParameterError::wrong_type("e")),
+                None => bail!(JwkParameterError::missing("e")),
             };
 
+            RsaKeyPair::check_jwk_lengths(&n, &e, &[])?;
+
             let mut builder = DerBuilder::new();
             builder.begin(DerType::Sequence);
             {
@@ -377,6 +412,19 @@ impl RsassaPssJwsAlgorithm {
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
+    /// Return a key size policy of this algorithm that requires a minimum key length.
+    ///
+    /// A minimum key length less than 2048 bits has no effect.
+    ///
+    /// # Arguments
+    /// * `bits` - A minimum RSA key length
+    pub fn with_min_key_bits(&self, bits: u32) -> RsassaPssJwsKeyPolicy {
+        RsassaPssJwsKeyPolicy {
+            algorithm: *self,
+            min_key_bits: bits,
+        }
+    }
+
     fn hash_algorithm(&self) -> HashAlgorithm {
         match self {
             Self::Ps256 => HashAlgorithm::Sha256,
@@ -385,7 +433,7 @@ impl RsassaPssJwsAlgorithm {
         }
     }
 
-    fn salt_len(&self) -> u8 {
+    fn salt_len(&self) -> u32 {
         match self {
             Self::Ps256 => 32,
             Self::Ps384 => 48,
@@ -422,13 +470,162 @@ impl Deref for RsassaPssJwsAlgorithm {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Represents a RSASSA-PSS algorithm with a minimum key length policy.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct RsassaPssJwsKeyPolicy {
+    algorithm: RsassaPssJwsAlgorithm,
+    min_key_bits: u32,
+}
+
+impl RsassaPssJwsKeyPolicy {
+    /// Return the source algorithm.
+    pub fn algorithm(&self) -> RsassaPssJwsAlgorithm {
+        self.algorithm
+    }
+
+    /// Return the minimum RSA key length.
+    pub fn min_key_bits(&self) -> u32 {
+        self.min_key_bits
+    }
+
+    /// Generate RSA key pair.
+    ///
+    /// # Arguments
+    /// * `bits` - RSA key length
+    pub fn generate_key_pair(&self, bits: u32) -> Result<RsaPssKeyPair, JoseError> {
+        self.check_key_bits(bits)?;
+        self.algorithm.generate_key_pair(bits)
+    }
+
+    /// Generate RSA key pair that is restricted to the algorithm.
+    ///
+    /// # Arguments
+    /// * `bits` - RSA key length
+    pub fn generate_restricted_key_pair(&self, bits: u32) -> Result<RsaPssKeyPair, JoseError> {
+        self.check_key_bits(bits)?;
+        self.algorithm.generate_restricted_key_pair(bits)
+    }
+
+    /// Create a RSA-PSS key pair from a private key that is a DER encoded PKCS#8 PrivateKeyInfo or PKCS#1 RSAPrivateKey.
+    ///
+    /// # Arguments
+    /// * `input` - A private key that is a DER encoded PKCS#8 PrivateKeyInfo or PKCS#1 RSAPrivateKey.
+    pub fn key_pair_from_der(&self, input: impl AsRef<[u8]>) -> Result<RsaPssKeyPair, JoseError> {
+        let key_pair = self.algorithm.key_pair_from_der(input)?;
+        self.check_key_bits(key_pair.key_len() * 8)?;
+        Ok(key_pair)
+    }
+
+    /// Create a RSA-PSS key pair from a private key of common or traditinal PEM format.
+    ///
+    /// # Arguments
+    /// * `input` - A private key of common or traditinal PEM format.
+    pub fn key_pair_from_pem(&self, input: impl AsRef<[u8]>) -> Result<RsaPssKeyPair, JoseError> {
+        let key_pair = self.algorithm.key_pair_from_pem(input)?;
+        self.check_key_bits(key_pair.key_len() * 8)?;
+        Ok(key_pair)
+    }
+
+    /// Return a signer from a private key that is a DER encoded PKCS#8 PrivateKeyInfo or PKCS#1 RSAPrivateKey.
+    ///
+    /// # Arguments
+    /// * `input` - A private key that is a DER encoded PKCS#8 PrivateKeyInfo or PKCS#1 RSAPrivateKey.
+    pub fn signer_from_der(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<RsassaPssJwsSigner, JoseError> {
+        let signer = self.algorithm.signer_from_der(input)?;
+        self.check_key_bits(signer.private_key.bits())?;
+        Ok(signer)
+    }
+
+    /// Return a signer from a private key of common or traditinal PEM format.
+    ///
+    /// # Arguments
+    /// * `input` - A private key of common or traditinal PEM format.
+    pub fn signer_from_pem(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<RsassaPssJwsSigner, JoseError> {
+        let signer = self.algorithm.signer_from_pem(input)?;
+        self.check_key_bits(signer.private_key.bits())?;
+        Ok(signer)
+    }
+
+    /// Return a signer from a private key that is formatted by a JWK of RSA type.
+    ///
+    /// # Arguments
+    /// * `jwk` - A private key that is formatted by a JWK of RSA type.
+    pub fn signer_from_jwk(&self, jwk: &Jwk) -> Result<RsassaPssJwsSigner, JoseError> {
+        let signer = self.algorithm.signer_from_jwk(jwk)?;
+        self.check_key_bits(signer.private_key.bits())?;
+        Ok(signer)
+    }
+
+    /// Return a verifier from a public key that is a DER encoded SubjectPublicKeyInfo or PKCS#1 RSAPublicKey.
+    ///
+    /// # Arguments
+    /// * `input` - A public key that is a DER encoded SubjectPublicKeyInfo or PKCS#1 RSAPublicKey.
+    pub fn verifier_from_der(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<RsassaPssJwsVerifier, JoseError> {
+        let verifier = self.algorithm.verifier_from_der(input)?;
+        self.check_key_bits(verifier.public_key.bits())?;
+        Ok(verifier)
+    }
+
+    /// Return a verifier from a key of common or traditional PEM format.
+    ///
+    /// # Arguments
+    /// * `input` - A public key of common or traditional PEM format.
+    pub fn verifier_from_pem(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<RsassaPssJwsVerifier, JoseError> {
+        let verifier = self.algorithm.verifier_from_pem(input)?;
+        self.check_key_bits(verifier.public_key.bits())?;
+        Ok(verifier)
+    }
+
+    /// Return a verifier from a public key that is formatted by a JWK of RSA type.
+    ///
+    /// # Arguments
+    /// * `jwk` - A public key that is formatted by a JWK of RSA type.
+    pub fn verifier_from_jwk(&self, jwk: &Jwk) -> Result<RsassaPssJwsVerifier, JoseError> {
+        let verifier = self.algorithm.verifier_from_jwk(jwk)?;
+        self.check_key_bits(verifier.public_key.bits())?;
+        Ok(verifier)
+    }
+
+    fn check_key_bits(&self, bits: u32) -> Result<(), JoseError> {
+        (|| -> anyhow::Result<()> {
+            if bits < self.min_key_bits {
+                bail!("key length must be {} or more.", self.min_key_bits);
+            }
+            Ok(())
+        })()
+        .map_err(JoseError::InvalidKeyFormat)
+    }
+}
+
+#[derive(Clone)]
 pub struct RsassaPssJwsSigner {
     algorithm: RsassaPssJwsAlgorithm,
     private_key: PKey<Private>,
     key_id: Option<String>,
 }
 
+impl Debug for RsassaPssJwsSigner {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("RsassaPssJwsSigner")
+            .field("algorithm", &self.algorithm)
+            .field("private_key", &format_args!("[REDACTED]"))
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
 impl RsassaPssJwsSigner {
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
@@ -437,6 +634,46 @@ impl RsassaPssJwsSigner {
     pub fn remove_key_id(&mut self) {
         self.key_id = None;
     }
+
+    /// Return the name of the message digest that is used for signing (e.g. "SHA-256").
+    pub fn digest_name(&self) -> &str {
+        self.algorithm.hash_algorithm().name()
+    }
+
+    /// Return the name of the message digest that is used for MGF1 (e.g. "SHA-256").
+    pub fn mgf1_digest_name(&self) -> &str {
+        self.algorithm.hash_algorithm().name()
+    }
+
+    /// Return the salt length in bytes.
+    pub fn salt_len(&self) -> usize {
+        self.algorithm.salt_len() as usize
+    }
+
+    #[cfg_attr(all(feature = "pool", not(test)), allow(dead_code))]
+    fn sign_unpooled(&self, message: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let md = self.algorithm.hash_algorithm().message_digest();
+
+        let mut signer = Signer::new(md, &self.private_key)?;
+        signer.update(message)?;
+        let signature = signer.sign_to_vec()?;
+        Ok(signature)
+    }
+
+    #[cfg(feature = "pool")]
+    fn sign_pooled(&self, message: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let md = self.algorithm.hash_algorithm().md();
+
+        let mut ctx = MD_CTX_POOL.take(self.algorithm.name())?;
+        let result = (|| -> anyhow::Result<Vec<u8>> {
+            ctx.digest_sign_init(Some(md), &self.private_key)?;
+            let mut signature = Vec::with_capacity(self.signature_len());
+            ctx.digest_sign_to_vec(message, &mut signature)?;
+            Ok(signature)
+        })();
+        MD_CTX_POOL.give_back(self.algorithm.name(), ctx);
+        result
+    }
 }
 
 impl JwsSigner for RsassaPssJwsSigner {
@@ -456,15 +693,16 @@ impl JwsSigner for RsassaPssJwsSigner {
     }
 
     fn sign(&self, message: &[u8]) -> Result<Vec<u8>, JoseError> {
-        (|| -> anyhow::Result<Vec<u8>> {
-            let md = self.algorithm.hash_algorithm().message_digest();
+        #[cfg(feature = "pool")]
+        let result = self.sign_pooled(message);
+        #[cfg(not(feature = "pool"))]
+        let result = self.sign_unpooled(message);
 
-            let mut signer = Signer::new(md, &self.private_key)?;
-            signer.update(message)?;
-            let signature = signer.sign_to_vec()?;
-            Ok(signature)
-        })()
-        .map_err(|err| JoseError::InvalidSignature(err))
+        result.map_err(|err| JoseError::InvalidSignature(err))
+    }
+
+    fn public_key_jwk(&self) -> Option<Jwk> {
+        jwk::public_key_jwk_from_private_key(&self.private_key).ok()
     }
 
     fn box_clone(&self) -> Box<dyn JwsSigner> {
@@ -495,6 +733,48 @@ impl RsassaPssJwsVerifier {
     pub fn remove_key_id(&mut self) {
         self.key_id = None;
     }
+
+    /// Return the name of the message digest that is used for signing (e.g. "SHA-256").
+    pub fn digest_name(&self) -> &str {
+        self.algorithm.hash_algorithm().name()
+    }
+
+    /// Return the name of the message digest that is used for MGF1 (e.g. "SHA-256").
+    pub fn mgf1_digest_name(&self) -> &str {
+        self.algorithm.hash_algorithm().name()
+    }
+
+    /// Return the salt length in bytes.
+    pub fn salt_len(&self) -> usize {
+        self.algorithm.salt_len() as usize
+    }
+
+    /// Return the modulus (n) of the key as big-endian bytes without leading zeros.
+    pub fn modulus(&self) -> Vec<u8> {
+        rsassa::modulus(&self.public_key)
+    }
+
+    /// Return the public exponent (e) of the key as big-endian bytes without leading zeros.
+    pub fn public_exponent(&self) -> Vec<u8> {
+        rsassa::public_exponent(&self.public_key)
+    }
+
+    /// Return the bit length of the public exponent (e) of the key.
+    pub fn public_exponent_bits(&self) -> u32 {
+        rsassa::public_exponent_bits(&self.public_key)
+    }
+
+    /// Return this verifier if the public exponent (e) of the key is not less than a minimum.
+    ///
+    /// No minimum is applied by default, so a key with a small exponent like 3 is accepted.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - a minimum public exponent (e.g. 65537)
+    pub fn with_min_public_exponent(self, min: u32) -> Result<Self, JoseError> {
+        rsassa::check_min_public_exponent(&self.public_key, min)?;
+        Ok(self)
+    }
 }
 
 impl JwsVerifier for RsassaPssJwsVerifier {
@@ -536,14 +816,170 @@ impl Deref for RsassaPssJwsVerifier {
     }
 }
 
+/// Represents a verifier of RSASSA-PSS that accepts any of the allowed algorithms
+/// with the same public key.
+///
+/// The verifier is selected by the JWS alg header claim, and an algorithm
+/// that is not allowed is rejected.
+#[derive(Debug, Clone)]
+pub struct AnyRsassaPssJwsVerifier {
+    verifiers: Vec<RsassaPssJwsVerifier>,
+}
+
+impl AnyRsassaPssJwsVerifier {
+    /// Return a verifier from a public key that is a DER encoded SubjectPublicKeyInfo or PKCS#1 RSAPublicKey.
+    ///
+    /// # Arguments
+    /// * `algorithms` - The allowed algorithms
+    /// * `input` - A public key that is a DER encoded SubjectPublicKeyInfo or PKCS#1 RSAPublicKey.
+    pub fn from_der(
+        algorithms: &[RsassaPssJwsAlgorithm],
+        input: impl AsRef<[u8]>,
+    ) -> Result<Self, JoseError> {
+        Self::from_verifiers(algorithms, |alg| alg.verifier_from_der(input.as_ref()))
+    }
+
+    /// Return a verifier from a key of common or traditional PEM format.
+    ///
+    /// # Arguments
+    /// * `algorithms` - The allowed algorithms
+    /// * `input` - A key of common or traditional PEM format.
+    pub fn from_pem(
+        algorithms: &[RsassaPssJwsAlgorithm],
+        input: impl AsRef<[u8]>,
+    ) -> Result<Self, JoseError> {
+        Self::from_verifiers(algorithms, |alg| alg.verifier_from_pem(input.as_ref()))
+    }
+
+    /// Return a verifier from a key that is formatted by a JWK of RSA type.
+    ///
+    /// # Arguments
+    /// * `algorithms` - The allowed algorithms
+    /// * `jwk` - A key that is formatted by a JWK of RSA type.
+    pub fn from_jwk(algorithms: &[RsassaPssJwsAlgorithm], jwk: &Jwk) -> Result<Self, JoseError> {
+        Self::from_verifiers(algorithms, |alg| alg.verifier_from_jwk(jwk))
+    }
+
+    /// Return the allowed algorithms.
+    pub fn algorithms(&self) -> Vec<RsassaPssJwsAlgorithm> {
+        self.verifiers.iter().map(|val| val.algorithm).collect()
+    }
+
+    /// Return the verifier for a specified algorithm name if the algorithm is allowed.
+    ///
+    /// # Arguments
+    /// * `name` - A algorithm name
+    pub fn verifier(&self, name: &str) -> Option<&RsassaPssJwsVerifier> {
+        self.verifiers
+            .iter()
+            .find(|val| val.algorithm.name() == name)
+    }
+
+    /// Select the verifier for the JWS alg header claim.
+    /// It is used as a selector of the deserialize functions.
+    ///
+    /// # Arguments
+    /// * `header` - The decoded JWS header claims.
+    pub fn select(&self, header: &JwsHeader) -> Result<Option<&dyn JwsVerifier>, JoseError> {
+        (|| -> anyhow::Result<Option<&dyn JwsVerifier>> {
+            let name = match header.algorithm() {
+                Some(val) => val,
+                None => bail!("The JWS alg header claim is required."),
+            };
+            match self.verifier(name) {
+                Some(val) => Ok(Some(val)),
+                None => bail!("The JWS alg header claim is not allowed: {}", name),
+            }
+        })()
+        .map_err(JoseError::UnsupportedSignatureAlgorithm)
+    }
+
+    pub fn set_key_id(&mut self, value: impl Into<String>) {
+        let value = value.into();
+        for verifier in &mut self.verifiers {
+            verifier.set_key_id(value.clone());
+        }
+    }
+
+    pub fn remove_key_id(&mut self) {
+        for verifier in &mut self.verifiers {
+            verifier.remove_key_id();
+        }
+    }
+
+    fn from_verifiers<F>(algorithms: &[RsassaPssJwsAlgorithm], f: F) -> Result<Self, JoseError>
+    where
+        F: Fn(&RsassaPssJwsAlgorithm) -> Result<RsassaPssJwsVerifier, JoseError>,
+    {
+        if algorithms.is_empty() {
+            return Err(JoseError::UnsupportedSignatureAlgorithm(anyhow::anyhow!(
+                "At least one algorithm is required."
+            )));
+        }
+
+        let mut verifiers: Vec<RsassaPssJwsVerifier> = Vec::with_capacity(algorithms.len());
+        for alg in algorithms {
+            if verifiers.iter().all(|val| val.algorithm != *alg) {
+                verifiers.push(f(alg)?);
+            }
+        }
+        Ok(Self { verifiers })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use crate::jwk::KeyPair;
+    use crate::jws;
     use anyhow::Result;
+    use openssl::pkey::Id;
     use std::fs;
     use std::path::PathBuf;
 
+    #[test]
+    fn sign_and_verify_rsassa_pss_generated_restricted_der() -> Result<()> {
+        let input = b"abcde12345";
+
+        for alg in &[
+            RsassaPssJwsAlgorithm::Ps256,
+            RsassaPssJwsAlgorithm::Ps384,
+            RsassaPssJwsAlgorithm::Ps512,
+        ] {
+            let key_pair = alg.generate_restricted_key_pair(2048)?;
+
+            let private_der = key_pair.to_der_private_key();
+            assert_eq!(
+                RsaPssKeyPair::detect_pkcs8(&private_der, false),
+                Some((alg.hash_algorithm(), alg.hash_algorithm(), alg.salt_len()))
+            );
+            let public_der = key_pair.to_der_public_key();
+            assert_eq!(
+                RsaPssKeyPair::detect_pkcs8(&public_der, true),
+                Some((alg.hash_algorithm(), alg.hash_algorithm(), alg.salt_len()))
+            );
+
+            // OpenSSL reads the exported keys as RSASSA-PSS keys, not as generic RSA keys.
+            let private_key = PKey::private_key_from_der(&private_der)?;
+            assert_eq!(private_key.id(), Id::RSA_PSS);
+            let public_key = PKey::public_key_from_der(&public_der)?;
+            assert_eq!(public_key.id(), Id::RSA_PSS);
+
+            let signer = alg.signer_from_der(&private_der)?;
+            let signature = signer.sign(input)?;
+
+            let verifier = alg.verifier_from_der(&public_der)?;
+            verifier.verify(input, &signature)?;
+        }
+
+        assert!(RsassaPssJwsAlgorithm::Ps256
+            .generate_restricted_key_pair(1024)
+            .is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn sign_and_verify_rsassa_pss_generated_der() -> Result<()> {
         let input = b"abcde12345";
@@ -751,6 +1187,212 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn reject_rsassa_pss_key_restricted_to_other_digest() -> Result<()> {
+        let private_key = load_file("pem/RSA-PSS_2048bit_SHA-256_md_only_private.pem")?;
+        RsassaPssJwsAlgorithm::Ps512
+            .signer_from_pem(&private_key)
+            .expect_err("A key restricted to SHA-256 was accepted by PS512");
+
+        let private_key = load_file("der/RSA-PSS_2048bit_SHA-256_md_only_pkcs8_private.der")?;
+        RsassaPssJwsAlgorithm::Ps512
+            .signer_from_der(&private_key)
+            .expect_err("A key restricted to SHA-256 was accepted by PS512");
+
+        // The MGF1 hash algorithm of the key is defaulted to SHA-1.
+        RsassaPssJwsAlgorithm::Ps256
+            .signer_from_der(&private_key)
+            .expect_err("A key restricted to MGF1 with SHA-1 was accepted by PS256");
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_rsassa_pss_traditional_pem_for_other_variant() -> Result<()> {
+        let input = b"abcde12345";
+
+        let key_pair = RsassaPssJwsAlgorithm::Ps256.generate_key_pair(2048)?;
+        let private_key = key_pair.to_traditional_pem_private_key();
+
+        let signer = RsassaPssJwsAlgorithm::Ps256.signer_from_pem(&private_key)?;
+        let signature = signer.sign(input)?;
+        let verifier =
+            RsassaPssJwsAlgorithm::Ps256.verifier_from_pem(key_pair.to_pem_public_key())?;
+        verifier.verify(input, &signature)?;
+
+        let err = RsassaPssJwsAlgorithm::Ps512
+            .signer_from_pem(&private_key)
+            .expect_err("A PS256 key was accepted by PS512");
+        assert!(format!("{}", err).contains("restricts the hash algorithm to SHA-256 but SHA-512"));
+
+        // A traditional PKCS#1 RSA private key has no restriction.
+        let private_key = load_file("pem/RSA_2048bit_traditional_private.pem")?;
+        for alg in &[RsassaPssJwsAlgorithm::Ps256, RsassaPssJwsAlgorithm::Ps512] {
+            let key_pair = alg.key_pair_from_pem(&private_key)?;
+            let signer = alg.signer_from_pem(key_pair.to_traditional_pem_private_key())?;
+            let signature = signer.sign(input)?;
+            let verifier = alg.verifier_from_pem(key_pair.to_pem_public_key())?;
+            verifier.verify(input, &signature)?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "pool")]
+    #[test]
+    fn sign_and_verify_rsassa_pss_pooled() -> Result<()> {
+        let input = b"abcde12345";
+
+        for alg in &[
+            RsassaPssJwsAlgorithm::Ps256,
+            RsassaPssJwsAlgorithm::Ps384,
+            RsassaPssJwsAlgorithm::Ps512,
+        ] {
+            let key_pair = alg.generate_key_pair(2048)?;
+            let signer = alg.signer_from_der(&key_pair.to_der_private_key())?;
+            let verifier = alg.verifier_from_der(&key_pair.to_der_public_key())?;
+
+            // RSASSA-PSS signatures are randomized, so the pooled and the unpooled
+            // signatures are compared by verifying both of them.
+            for _ in 0..3 {
+                let pooled = signer.sign_pooled(input)?;
+                let unpooled = signer.sign_unpooled(input)?;
+                assert_eq!(pooled.len(), unpooled.len());
+
+                verifier.verify(input, &pooled)?;
+                verifier.verify(input, &unpooled)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn rsassa_pss_min_key_bits_policy() -> Result<()> {
+        let private_key = load_file("pem/RSA-PSS_2048bit_SHA-256_private.pem")?;
+        let public_key = load_file("pem/RSA-PSS_2048bit_SHA-256_public.pem")?;
+
+        let alg = RsassaPssJwsAlgorithm::Ps256;
+        alg.signer_from_pem(&private_key)?;
+        alg.verifier_from_pem(&public_key)?;
+
+        let policy = alg.with_min_key_bits(3072);
+        assert_eq!(policy.min_key_bits(), 3072);
+        assert!(policy.signer_from_pem(&private_key).is_err());
+        assert!(policy.verifier_from_pem(&public_key).is_err());
+        assert!(policy.key_pair_from_pem(&private_key).is_err());
+        assert!(policy.generate_key_pair(2048).is_err());
+
+        let key_pair = policy.generate_key_pair(3072)?;
+        let signer = policy.signer_from_der(key_pair.to_der_private_key())?;
+        let verifier = policy.verifier_from_der(key_pair.to_der_public_key())?;
+        let signature = signer.sign(b"abcde12345")?;
+        verifier.verify(b"abcde12345", &signature)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn rsassa_pss_parameters() -> Result<()> {
+        for (alg, digest_name, salt_len) in &[
+            (RsassaPssJwsAlgorithm::Ps256, "SHA-256", 32),
+            (RsassaPssJwsAlgorithm::Ps384, "SHA-384", 48),
+            (RsassaPssJwsAlgorithm::Ps512, "SHA-512", 64),
+        ] {
+            let private_key = load_file(match alg {
+                RsassaPssJwsAlgorithm::Ps256 => "pem/RSA-PSS_2048bit_SHA-256_private.pem",
+                RsassaPssJwsAlgorithm::Ps384 => "pem/RSA-PSS_2048bit_SHA-384_private.pem",
+                RsassaPssJwsAlgorithm::Ps512 => "pem/RSA-PSS_2048bit_SHA-512_private.pem",
+            })?;
+            let public_key = load_file(match alg {
+                RsassaPssJwsAlgorithm::Ps256 => "pem/RSA-PSS_2048bit_SHA-256_public.pem",
+                RsassaPssJwsAlgorithm::Ps384 => "pem/RSA-PSS_2048bit_SHA-384_public.pem",
+                RsassaPssJwsAlgorithm::Ps512 => "pem/RSA-PSS_2048bit_SHA-512_public.pem",
+            })?;
+
+            let signer = alg.signer_from_pem(&private_key)?;
+            assert_eq!(signer.digest_name(), *digest_name);
+            assert_eq!(signer.mgf1_digest_name(), *digest_name);
+            assert_eq!(signer.salt_len(), *salt_len);
+
+            let verifier = alg.verifier_from_pem(&public_key)?;
+            assert_eq!(verifier.digest_name(), *digest_name);
+            assert_eq!(verifier.mgf1_digest_name(), *digest_name);
+            assert_eq!(verifier.salt_len(), *salt_len);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_rsassa_pss_with_any_allowed_algorithm() -> Result<()> {
+        let input = b"abcde12345";
+
+        let key_pair = RsaKeyPair::generate(2048)?;
+        let private_key = key_pair.to_jwk_private_key();
+        let public_key = key_pair.to_jwk_public_key();
+
+        let verifier = AnyRsassaPssJwsVerifier::from_jwk(
+            &[RsassaPssJwsAlgorithm::Ps256, RsassaPssJwsAlgorithm::Ps384],
+            &public_key,
+        )?;
+        assert_eq!(
+            verifier.algorithms(),
+            vec![RsassaPssJwsAlgorithm::Ps256, RsassaPssJwsAlgorithm::Ps384]
+        );
+
+        for alg in &[RsassaPssJwsAlgorithm::Ps256, RsassaPssJwsAlgorithm::Ps384] {
+            let signer = alg.signer_from_jwk(&private_key)?;
+            let jwt = jws::serialize_compact(input, &JwsHeader::new(), &signer)?;
+            let (payload, header) =
+                jws::deserialize_compact_with_selector(&jwt, |header| verifier.select(header))?;
+            assert_eq!(payload, input);
+            assert_eq!(header.algorithm(), Some(alg.name()));
+        }
+
+        let signer = RsassaPssJwsAlgorithm::Ps512.signer_from_jwk(&private_key)?;
+        let jwt = jws::serialize_compact(input, &JwsHeader::new(), &signer)?;
+        let err = jws::deserialize_compact_with_selector(&jwt, |header| verifier.select(header))
+            .expect_err("PS512 was accepted by a verifier that allows PS256 and PS384");
+        assert!(matches!(err, JoseError::UnsupportedSignatureAlgorithm(_)));
+
+        assert!(AnyRsassaPssJwsVerifier::from_jwk(&[], &public_key).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn rsassa_pss_verifier_modulus_and_public_exponent() -> Result<()> {
+        for alg in &[RsassaPssJwsAlgorithm::Ps256, RsassaPssJwsAlgorithm::Ps512] {
+            let key_pair = alg.generate_key_pair(2048)?;
+            let jwk = key_pair.to_jwk_public_key();
+            let n = match jwk.parameter("n") {
+                Some(Value::String(val)) => util::decode_base64url(val)?,
+                _ => unreachable!(),
+            };
+            let e = match jwk.parameter("e") {
+                Some(Value::String(val)) => util::decode_base64url(val)?,
+                _ => unreachable!(),
+            };
+
+            let verifier = alg.verifier_from_der(key_pair.to_der_public_key())?;
+            assert_eq!(verifier.modulus(), n);
+            assert_eq!(verifier.public_exponent(), e);
+            assert_eq!(verifier.public_exponent(), vec![0x01, 0x00, 0x01]);
+            assert_ne!(verifier.modulus()[0], 0);
+
+            let verifier = alg.verifier_from_jwk(&jwk)?;
+            assert_eq!(verifier.modulus(), n);
+        }
+
+        let key_pair = RsaKeyPair::generate(2048)?;
+        let verifier = jws::RS256.verifier_from_der(key_pair.to_der_public_key())?;
+        assert_eq!(verifier.modulus().len(), 256);
+        assert_eq!(verifier.public_exponent(), vec![0x01, 0x00, 0x01]);
+
+        Ok(())
+    }
+
     #[test]
     fn sign_and_verify_rsassa_pss_mismatch() -> Result<()> {
         let input = b"abcde12345";
@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, RwLock};
+
+use once_cell::unsync::OnceCell;
+
+use crate::jwk::{Jwk, JwkSet};
+use crate::jws::{self, JwsHeader, JwsVerifier};
+use crate::jwt::{self, JwtPayload};
+use crate::JoseError;
+
+type VerifierBuilder = dyn Fn(&Jwk, &str) -> Result<Box<dyn JwsVerifier>, JoseError> + Send + Sync;
+type VerifierCache = HashMap<(Option<String>, String), Arc<dyn JwsVerifier>>;
+
+/// Represents a thread-safe cache of verifiers that are built from a JWK set.
+///
+/// The verifiers are keyed by a key ID (kid) and an algorithm (alg) so that
+/// repeated verifications against the same key reuse a verifier instead of
+/// parsing the key each time. The keys that have both kid and alg parameters
+/// are built in advance, and the others are built on first use.
+///
+/// A key without an alg parameter is only used for the algorithms allowed by
+/// `with_allowed_algorithms`, so that the alg header claim of a token cannot
+/// choose how such a key is used.
+pub struct CachingVerifierPool {
+    jwk_set: JwkSet,
+    builder: Box<VerifierBuilder>,
+    algorithms: Vec<String>,
+    verifiers: RwLock<VerifierCache>,
+}
+
+impl CachingVerifierPool {
+    /// Return a new pool for a JWK set.
+    ///
+    /// # Arguments
+    ///
+    /// * `jwk_set` - a JWK set of verification keys
+    pub fn new(jwk_set: JwkSet) -> Self {
+        Self::with_builder(jwk_set, jws::verifier_from_jwk_with_algorithm)
+    }
+
+    /// Return a new pool for a JWK set that builds verifiers by a specified function.
+    ///
+    /// # Arguments
+    ///
+    /// * `jwk_set` - a JWK set of verification keys
+    /// * `builder` - a function that builds a verifier from a JWK and an algorithm name
+    pub fn with_builder<F>(jwk_set: JwkSet, builder: F) -> Self
+    where
+        F: Fn(&Jwk, &str) -> Result<Box<dyn JwsVerifier>, JoseError> + Send + Sync + 'static,
+    {
+        let pool = Self {
+            jwk_set,
+            builder: Box::new(builder),
+            algorithms: Vec::new(),
+            verifiers: RwLock::new(HashMap::new()),
+        };
+
+        for jwk in pool.jwk_set.keys() {
+            if let (Some(key_id), Some(algorithm)) = (jwk.key_id(), jwk.algorithm()) {
+                if jwk.key_use() != Some("enc") {
+                    let _ = pool.verifier(Some(key_id), algorithm);
+                }
+            }
+        }
+        pool
+    }
+
+    /// Return this pool that uses keys without an alg parameter for specified algorithms.
+    ///
+    /// Keys with an alg parameter are only used for that algorithm regardless of this list.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithms` - algorithm names that are allowed for keys without an alg parameter
+    pub fn with_allowed_algorithms<I, S>(mut self, algorithms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.algorithms = algorithms.into_iter().map(|val| val.into()).collect();
+
+        let jwk_set = &self.jwk_set;
+        let allowed = &self.algorithms;
+        let verifiers = match self.verifiers.get_mut() {
+            Ok(val) => val,
+            Err(err) => err.into_inner(),
+        };
+        verifiers.retain(|(key_id, algorithm), _| {
+            if allowed.contains(algorithm) {
+                return true;
+            }
+            match key_id {
+                Some(key_id) => jwk_set
+                    .get(key_id)
+                    .iter()
+                    .any(|jwk| jwk.algorithm() == Some(algorithm.as_str())),
+                None => false,
+            }
+        });
+        self
+    }
+
+    /// Return the algorithms that are allowed for keys without an alg parameter.
+    pub fn allowed_algorithms(&self) -> &[String] {
+        &self.algorithms
+    }
+
+    /// Return the JWK set of this pool.
+    pub fn jwk_set(&self) -> &JwkSet {
+        &self.jwk_set
+    }
+
+    /// Return a cached verifier for a key ID and an algorithm, or build it.
+    ///
+    /// If the key ID is none, the first key in the JWK set that is usable for
+    /// the algorithm is selected. Keys for encryption (use=enc), keys
+    /// with a different alg parameter and keys without an alg parameter
+    /// for an algorithm that is not allowed are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_id` - a key ID (kid)
+    /// * `algorithm` - an algorithm name (alg)
+    pub fn verifier(
+        &self,
+        key_id: Option<&str>,
+        algorithm: &str,
+    ) -> Result<Option<Arc<dyn JwsVerifier>>, JoseError> {
+        let cache_key = (key_id.map(|val| val.to_string()), algorithm.to_string());
+        {
+            let verifiers = match self.verifiers.read() {
+                Ok(val) => val,
+                Err(err) => err.into_inner(),
+            };
+            if let Some(verifier) = verifiers.get(&cache_key) {
+                return Ok(Some(Arc::clone(verifier)));
+            }
+        }
+
+        let candidates = match key_id {
+            Some(val) => self.jwk_set.get(val),
+            None => self.jwk_set.keys(),
+        };
+
+        let mut last_error = None;
+        for jwk in candidates {
+            if jwk.key_use() == Some("enc") {
+                continue;
+            }
+            match jwk.algorithm() {
+                Some(val) if val != algorithm => continue,
+                None if !self.algorithms.iter().any(|val| val == algorithm) => continue,
+                _ => {}
+            }
+
+            match (self.builder)(jwk, algorithm) {
+                Ok(val) => {
+                    let mut verifiers = match self.verifiers.write() {
+                        Ok(val) => val,
+                        Err(err) => err.into_inner(),
+                    };
+                    let verifier = verifiers.entry(cache_key).or_insert_with(|| Arc::from(val));
+                    return Ok(Some(Arc::clone(verifier)));
+                }
+                Err(err) => last_error = Some(err),
+            }
+        }
+
+        match last_error {
+            Some(err) => Err(err),
+            None => Ok(None),
+        }
+    }
+
+    /// Return the number of cached verifiers.
+    pub fn len(&self) -> usize {
+        match self.verifiers.read() {
+            Ok(val) => val.len(),
+            Err(err) => err.into_inner().len(),
+        }
+    }
+
+    /// Return whether no verifier is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Deserialize the input that is formatted by JWS compact serialization
+    /// and verify it by a cached verifier selected with the kid and alg header claims.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input data.
+    pub fn deserialize_compact(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<(Vec<u8>, JwsHeader), JoseError> {
+        let cell = OnceCell::new();
+        jws::deserialize_compact_with_selector(input, |header| self.select(header, &cell))
+    }
+
+    /// Return the JWT object decoded by a cached verifier selected with
+    /// the kid and alg header claims.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - a JWT string representation.
+    pub fn decode_jwt(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<(JwtPayload, JwsHeader), JoseError> {
+        let cell = OnceCell::new();
+        jwt::decode_with_verifier_selector(input, |header| self.select(header, &cell))
+    }
+
+    fn select<'a>(
+        &self,
+        header: &JwsHeader,
+        cell: &'a OnceCell<Arc<dyn JwsVerifier>>,
+    ) -> Result<Option<&'a dyn JwsVerifier>, JoseError> {
+        let algorithm = match header.algorithm() {
+            Some(val) => val,
+            None => return Ok(None),
+        };
+        match self.verifier(header.key_id(), algorithm)? {
+            Some(val) => Ok(Some(cell.get_or_init(|| val).as_ref())),
+            None => Ok(None),
+        }
+    }
+}
+
+impl Debug for CachingVerifierPool {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("CachingVerifierPool")
+            .field("jwk_set", &self.jwk_set)
+            .field("algorithms", &self.algorithms)
+            .field("verifiers", &self.verifiers)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use anyhow::Result;
+
+    use super::CachingVerifierPool;
+    use crate::jwk::{Jwk, JwkSet};
+    use crate::jws::{self, JwsHeader, ES256, HS256, HS512};
+    use crate::{Map, Value};
+
+    #[test]
+    fn test_caching_verifier_pool() -> Result<()> {
+        let mut ec_jwk = ES256.generate_key_pair()?.to_jwk_key_pair();
+        ec_jwk.set_key_id("ec-key");
+        ec_jwk.set_algorithm("ES256");
+        let ec_signer = ES256.signer_from_jwk(&ec_jwk)?;
+
+        let mut oct_jwk = Jwk::generate_oct_key(32)?;
+        oct_jwk.set_key_id("oct-key");
+        let oct_signer = HS256.signer_from_jwk(&oct_jwk)?;
+
+        let mut map = Map::new();
+        map.insert(
+            "keys".to_string(),
+            Value::Array(vec![
+                Value::Object(ec_jwk.to_public().into()),
+                Value::Object(oct_jwk.clone().into()),
+            ]),
+        );
+        let jwk_set = JwkSet::from_map(map)?;
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let builder_count = Arc::clone(&count);
+        let pool = CachingVerifierPool::with_builder(jwk_set, move |jwk, algorithm| {
+            builder_count.fetch_add(1, Ordering::SeqCst);
+            jws::verifier_from_jwk_with_algorithm(jwk, algorithm)
+        })
+        .with_allowed_algorithms(vec!["HS256"]);
+
+        // The EC key has kid and alg, so it is built in advance.
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert_eq!(pool.len(), 1);
+
+        for i in 0..1000 {
+            let payload = format!("token {}", i);
+            let mut header = JwsHeader::new();
+            let token = if i % 2 == 0 {
+                header.set_key_id("ec-key");
+                jws::serialize_compact(payload.as_bytes(), &header, &ec_signer)?
+            } else {
+                header.set_key_id("oct-key");
+                jws::serialize_compact(payload.as_bytes(), &header, &oct_signer)?
+            };
+
+            let (verified_payload, _) = pool.deserialize_compact(&token)?;
+            assert_eq!(verified_payload, payload.as_bytes());
+        }
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert_eq!(pool.len(), 2);
+
+        // A token signed by a different key fails without rebuilding.
+        let mut header = JwsHeader::new();
+        header.set_key_id("ec-key");
+        let other_signer = ES256.signer_from_jwk(&ES256.generate_key_pair()?.to_jwk_key_pair())?;
+        let token = jws::serialize_compact(b"forged", &header, &other_signer)?;
+        assert!(pool.deserialize_compact(&token).is_err());
+
+        header.set_key_id("unknown-key");
+        let token = jws::serialize_compact(b"unknown", &header, &other_signer)?;
+        assert!(pool.deserialize_compact(&token).is_err());
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_caching_verifier_pool_requires_alg() -> Result<()> {
+        let mut oct_jwk = Jwk::generate_oct_key(64)?;
+        oct_jwk.set_key_id("oct-key");
+
+        let mut map = Map::new();
+        map.insert(
+            "keys".to_string(),
+            Value::Array(vec![Value::Object(oct_jwk.clone().into())]),
+        );
+        let jwk_set = JwkSet::from_map(map)?;
+
+        let mut header = JwsHeader::new();
+        header.set_key_id("oct-key");
+        let hs256_token =
+            jws::serialize_compact(b"hs256", &header, &HS256.signer_from_jwk(&oct_jwk)?)?;
+        let hs512_token =
+            jws::serialize_compact(b"hs512", &header, &HS512.signer_from_jwk(&oct_jwk)?)?;
+
+        // The key has no alg parameter, so the alg header claim cannot select it.
+        let pool = CachingVerifierPool::new(jwk_set.clone());
+        assert!(pool.allowed_algorithms().is_empty());
+        assert!(pool.verifier(Some("oct-key"), "HS256")?.is_none());
+        assert!(pool.deserialize_compact(&hs256_token).is_err());
+        assert!(pool.deserialize_compact(&hs512_token).is_err());
+        assert!(pool.is_empty());
+
+        let pool = CachingVerifierPool::new(jwk_set).with_allowed_algorithms(vec!["HS256"]);
+        let (payload, _) = pool.deserialize_compact(&hs256_token)?;
+        assert_eq!(payload, b"hs256");
+        assert!(pool.deserialize_compact(&hs512_token).is_err());
+        assert_eq!(pool.len(), 1);
+
+        // Narrowing the list drops the verifiers that are no longer allowed.
+        let pool = pool.with_allowed_algorithms(Vec::<String>::new());
+        assert!(pool.is_empty());
+        assert!(pool.deserialize_compact(&hs256_token).is_err());
+
+        Ok(())
+    }
+}
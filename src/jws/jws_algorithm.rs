@@ -1,5 +1,6 @@
 use std::fmt::Debug;
 
+use crate::jwk::Jwk;
 use crate::JoseError;
 
 pub trait JwsAlgorithm: Debug + Send + Sync {
@@ -23,12 +24,20 @@ impl Clone for Box<dyn JwsAlgorithm> {
     }
 }
 
+/// Represents a signer of JWS.
+///
+/// This trait can be implemented by an external signer (e.g. a cloud KMS or
+/// a PKCS#11 token) that does not expose its private key.
+/// The name of `algorithm()` is set to the "alg" header claim and
+/// `key_id()` is set to the "kid" header claim in a serialization.
 pub trait JwsSigner: Debug + Send + Sync {
     /// Return the source algorithm instance.
+    /// The name of the algorithm is used as the alg header claim.
     fn algorithm(&self) -> &dyn JwsAlgorithm;
 
     /// Return the source key ID.
     /// The default value is a value of kid parameter in JWK.
+    /// If it is some, it is used as the kid header claim.
     fn key_id(&self) -> Option<&str>;
 
     /// Return the signature length of JWS.
@@ -41,6 +50,13 @@ pub trait JwsSigner: Debug + Send + Sync {
     /// * `message` - The message data to sign.
     fn sign(&self, message: &[u8]) -> Result<Vec<u8>, JoseError>;
 
+    /// Return the public key of the signing key as a JWK that has no private parameters.
+    /// It is used as the jwk header claim when the public JWK embedding is enabled.
+    /// The default value is none, e.g. for a shared secret key.
+    fn public_key_jwk(&self) -> Option<Jwk> {
+        None
+    }
+
     fn box_clone(&self) -> Box<dyn JwsSigner>;
 }
 
@@ -60,6 +76,9 @@ pub trait JwsVerifier: Debug + Send + Sync {
 
     /// Verify the data by the signature.
     ///
+    /// This method does not see the header, so the alg header claim is compared
+    /// with the name of the algorithm by the deserializers of JwsContext before it is called.
+    ///
     /// # Arguments
     ///
     /// * `message` - a message data to verify.
@@ -7,15 +7,29 @@ use crate::jws::{JwsHeader, JwsHeaderSet, JwsSigner, JwsVerifier};
 use crate::util;
 use crate::{JoseError, Map, Value};
 
+/// The default maximum length of a serialized input.
+const DEFAULT_MAX_INPUT_LEN: usize = 8 * 1024 * 1024;
+
+/// The default maximum length of a decoded payload.
+const DEFAULT_MAX_PAYLOAD_LEN: usize = 8 * 1024 * 1024;
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct JwsContext {
     acceptable_criticals: BTreeSet<String>,
+    lenient_base64: bool,
+    embed_public_jwk: bool,
+    max_input_len: Option<usize>,
+    max_payload_len: Option<usize>,
 }
 
 impl JwsContext {
     pub fn new() -> Self {
         Self {
             acceptable_criticals: BTreeSet::new(),
+            lenient_base64: false,
+            embed_public_jwk: false,
+            max_input_len: Some(DEFAULT_MAX_INPUT_LEN),
+            max_payload_len: Some(DEFAULT_MAX_PAYLOAD_LEN),
         }
     }
 
@@ -46,8 +60,101 @@ impl JwsContext {
         self.acceptable_criticals.remove(name);
     }
 
+    /// Test the base64 decoding of a deserialization is lenient.
+    pub fn is_lenient_base64_decoding(&self) -> bool {
+        self.lenient_base64
+    }
+
+    /// Set the base64 decoding of a deserialization is lenient.
+    ///
+    /// In lenient mode, the standard alphabet ("+" and "/") and trailing padding
+    /// are also accepted in addition to the base64url alphabet.
+    ///
+    /// # Arguments
+    ///
+    /// * `lenient` - true if the base64 decoding is lenient
+    pub fn set_lenient_base64_decoding(&mut self, lenient: bool) {
+        self.lenient_base64 = lenient;
+    }
+
+    /// Test the public key of a signer is embedded as the jwk header claim.
+    pub fn is_public_jwk_embedding(&self) -> bool {
+        self.embed_public_jwk
+    }
+
+    /// Set the public key of a signer is embedded as the jwk header claim
+    /// of the protected header when serializing.
+    ///
+    /// The embedded JWK has only the public key parameters. A jwk header claim that
+    /// is already set is kept, and a signer without a public key (e.g. HMAC) fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `embed` - true if the public key is embedded
+    pub fn set_public_jwk_embedding(&mut self, embed: bool) {
+        self.embed_public_jwk = embed;
+    }
+
+    /// Return the maximum length of a serialized input for a deserialization.
+    /// The default value is 8 MiB.
+    pub fn max_input_len(&self) -> Option<usize> {
+        self.max_input_len
+    }
+
+    /// Set the maximum length of a serialized input for a deserialization.
+    /// If it is none, the length is not limited.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a maximum length in bytes
+    pub fn set_max_input_len(&mut self, value: Option<usize>) {
+        self.max_input_len = value;
+    }
+
+    /// Return the maximum length of a decoded payload for a deserialization.
+    /// The default value is 8 MiB.
+    pub fn max_payload_len(&self) -> Option<usize> {
+        self.max_payload_len
+    }
+
+    /// Set the maximum length of a decoded payload for a deserialization.
+    /// The length is checked before the payload is decoded.
+    /// If it is none, the length is not limited.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a maximum length in bytes
+    pub fn set_max_payload_len(&mut self, value: Option<usize>) {
+        self.max_payload_len = value;
+    }
+
+    /// Return the JWS signing input that is signed in compact serialization.
+    ///
+    /// The header claims are used as they are, so alg (and kid) must be set
+    /// if the signing input is used to reassemble a compact serialization.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload data.
+    /// * `header` - The JWS heaser claims.
+    pub fn signing_input(&self, payload: &[u8], header: &JwsHeader) -> Result<Vec<u8>, JoseError> {
+        (|| -> anyhow::Result<Vec<u8>> {
+            let b64 = Self::detect_b64(header.claims_set())?;
+
+            let message = Self::build_signing_input(header.claims_set(), payload, b64, 0)?;
+            Ok(message.into_bytes())
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwsFormat(err),
+        })
+    }
+
     /// Return a representation of the data that is formatted by compact serialization.
     ///
+    /// If the signer has a key ID and the header has no kid header claim,
+    /// the key ID is set to the kid header claim. An explicit kid header claim wins.
+    ///
     /// # Arguments
     ///
     /// * `payload` - The payload data.
@@ -64,6 +171,9 @@ impl JwsContext {
 
     /// Return a representation of the data that is formatted by compact serialization.
     ///
+    /// If the signer has a key ID and the header has no kid header claim,
+    /// the key ID is set to the kid header claim. An explicit kid header claim wins.
+    ///
     /// # Arguments
     ///
     /// * `payload` - The payload data.
@@ -78,15 +188,40 @@ impl JwsContext {
     where
         F: Fn(&JwsHeader) -> Option<&'a dyn JwsSigner>,
     {
-        (|| -> anyhow::Result<String> {
-            let mut b64 = true;
-            if let Some(vals) = header.critical() {
-                if vals.contains(&"b64") {
-                    if let Some(val) = header.base64url_encode_payload() {
-                        b64 = val;
-                    }
-                }
-            }
+        let (jws, _, _) =
+            self.serialize_compact_detailed_with_selector(payload, header, selector)?;
+        Ok(jws)
+    }
+
+    /// Return a representation of the data that is formatted by compact serialization,
+    /// the header claims that are actually signed (e.g. with alg and kid set by the signer)
+    /// and the raw signature.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload data.
+    /// * `header` - The JWS heaser claims.
+    /// * `signer` - The JWS signer.
+    pub fn serialize_compact_detailed(
+        &self,
+        payload: &[u8],
+        header: &JwsHeader,
+        signer: &dyn JwsSigner,
+    ) -> Result<(String, JwsHeader, Vec<u8>), JoseError> {
+        self.serialize_compact_detailed_with_selector(payload, header, |_header| Some(signer))
+    }
+
+    fn serialize_compact_detailed_with_selector<'a, F>(
+        &self,
+        payload: &[u8],
+        header: &JwsHeader,
+        selector: F,
+    ) -> Result<(String, JwsHeader, Vec<u8>), JoseError>
+    where
+        F: Fn(&JwsHeader) -> Option<&'a dyn JwsSigner>,
+    {
+        (|| -> anyhow::Result<(String, JwsHeader, Vec<u8>)> {
+            let b64 = Self::detect_b64(header.claims_set())?;
 
             let signer = match selector(header) {
                 Some(val) => val,
@@ -99,38 +234,21 @@ impl JwsContext {
                 Value::String(signer.algorithm().name().to_string()),
             );
             if let Some(key_id) = signer.key_id() {
-                header.insert("kid".to_string(), Value::String(key_id.to_string()));
-            }
-            let header_bytes = serde_json::to_vec(&header)?;
-
-            let mut capacity = 2;
-            capacity += util::ceiling(header_bytes.len() * 4, 3);
-            capacity += if b64 {
-                util::ceiling(payload.len() * 4, 3)
-            } else {
-                payload.len()
-            };
-            capacity += util::ceiling(signer.signature_len() * 4, 3);
-
-            let mut message = String::with_capacity(capacity);
-            base64::encode_config_buf(header_bytes, base64::URL_SAFE_NO_PAD, &mut message);
-            message.push_str(".");
-            if b64 {
-                base64::encode_config_buf(payload, base64::URL_SAFE_NO_PAD, &mut message);
-            } else {
-                let payload = std::str::from_utf8(payload)?;
-                if payload.contains(".") {
-                    bail!("A JWS payload cannot contain dot.");
+                if !header.contains_key("kid") {
+                    header.insert("kid".to_string(), Value::String(key_id.to_string()));
                 }
-                message.push_str(payload);
             }
+            self.embed_public_jwk(&mut header, signer)?;
+            let mut message =
+                Self::build_signing_input(&header, payload, b64, signer.signature_len())?;
 
             let signature = signer.sign(message.as_bytes())?;
 
             message.push_str(".");
-            base64::encode_config_buf(signature, base64::URL_SAFE_NO_PAD, &mut message);
+            base64::encode_config_buf(&signature, base64::URL_SAFE_NO_PAD, &mut message);
 
-            Ok(message)
+            let header = JwsHeader::from_map(header)?;
+            Ok((message, header, signature))
         })()
         .map_err(|err| match err.downcast::<JoseError>() {
             Ok(err) => err,
@@ -185,6 +303,13 @@ impl JwsContext {
             result.push_str("{\"signatures\":[");
 
             for (i, header) in headers.iter().enumerate() {
+                if header.claims_set(false).contains_key("b64") {
+                    bail!("The JWS b64 header claim must be in protected.");
+                }
+                if !Self::detect_b64(header.claims_set(true))? {
+                    bail!("The general JSON serialization does not support a unencoded payload.");
+                }
+
                 let merged_map = header.to_map();
                 let merged = JwsHeader::from_map(merged_map)?;
                 let signer = match selector(i, &merged) {
@@ -210,6 +335,9 @@ impl JwsContext {
                         protected_map.insert("kid".to_string(), Value::String(key_id.to_string()));
                     }
                 }
+                if !merged.claims_set().contains_key("jwk") {
+                    self.embed_public_jwk(&mut protected_map, signer)?;
+                }
 
                 if i > 0 {
                     result.push_str(",");
@@ -285,21 +413,10 @@ impl JwsContext {
     {
         (|| -> anyhow::Result<String> {
             let protected_map = header.claims_set(true);
-            let mut b64 = true;
-            match protected_map.get("crit") {
-                Some(Value::Array(vals)) => {
-                    if vals.iter().any(|val| match val {
-                        Value::String(val2) => val2 == "b64",
-                        _ => false,
-                    }) {
-                        b64 = match protected_map.get("b64") {
-                            Some(Value::Bool(val3)) => *val3,
-                            _ => false,
-                        };
-                    }
-                }
-                _ => {}
+            if header.claims_set(false).contains_key("b64") {
+                bail!("The JWS b64 header claim must be in protected.");
             }
+            let b64 = Self::detect_b64(protected_map)?;
 
             let merged_map = header.to_map();
             let merged = JwsHeader::from_map(merged_map)?;
@@ -326,6 +443,9 @@ impl JwsContext {
                     protected_map.insert("kid".to_string(), Value::String(key_id.to_string()));
                 }
             }
+            if !merged.claims_set().contains_key("jwk") {
+                self.embed_public_jwk(&mut protected_map, signer)?;
+            }
 
             let protected_json = serde_json::to_string(&protected_map)?;
             let protected_b64 = base64::encode_config(protected_json, base64::URL_SAFE_NO_PAD);
@@ -400,80 +520,79 @@ impl JwsContext {
         F: Fn(&JwsHeader) -> Result<Option<&'a dyn JwsVerifier>, JoseError>,
     {
         (|| -> anyhow::Result<(Vec<u8>, JwsHeader)> {
-            let input = input.as_ref();
-            let indexies: Vec<usize> = input
-                .iter()
-                .enumerate()
-                .filter(|(_, b)| **b == b'.' as u8)
-                .map(|(pos, _)| pos)
-                .collect();
-            if indexies.len() != 2 {
-                bail!(
-                    "The compact serialization form of JWS must be three parts separated by colon."
-                );
-            }
-
-            let header = &input[0..indexies[0]];
-            let payload = &input[(indexies[0] + 1)..(indexies[1])];
-            let signature = &input[(indexies[1] + 1)..];
-
-            let header = base64::decode_config(header, base64::URL_SAFE_NO_PAD)?;
-            let header: Map<String, Value> = serde_json::from_slice(&header)?;
-            let header = JwsHeader::from_map(header)?;
+            let (payload, header, b64) = self.verify_compact(input.as_ref(), selector)?;
 
-            let verifier = match selector(&header)? {
-                Some(val) => val,
-                None => bail!("A verifier is not found."),
+            self.check_payload_len(payload.len(), b64)?;
+            let payload = if b64 {
+                self.decode_base64(payload)?
+            } else {
+                payload.to_vec()
             };
 
-            match header.claim("alg") {
-                Some(Value::String(val)) => {
-                    let expected_alg = verifier.algorithm().name();
-                    if val != expected_alg {
-                        bail!("The JWS alg header claim is not {}: {}", expected_alg, val);
-                    }
-                }
-                Some(_) => bail!("The JWS alg header claim must be a string."),
-                None => bail!("The JWS alg header claim is required."),
-            }
+            Ok((payload, header))
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwsFormat(err),
+        })
+    }
 
-            match verifier.key_id() {
-                Some(expected) => match header.key_id() {
-                    Some(actual) if expected == actual => {}
-                    Some(actual) => bail!("The JWS kid header claim is mismatched: {}", actual),
-                    None => bail!("The JWS kid header claim is required."),
-                },
-                None => {}
-            }
+    /// Verify the signature of the input that is formatted by compact serialization
+    /// without decoding the payload.
+    ///
+    /// The header is decoded and checked in the same way as deserialize_compact,
+    /// but the payload part is only used as a part of the signing input.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input data.
+    /// * `verifier` - The JWS verifier.
+    pub fn verify_signature_only(
+        &self,
+        input: impl AsRef<[u8]>,
+        verifier: &dyn JwsVerifier,
+    ) -> Result<(), JoseError> {
+        self.verify_compact(input.as_ref(), |_header| Ok(Some(verifier)))
+            .map(|_| ())
+            .map_err(|err| match err.downcast::<JoseError>() {
+                Ok(err) => err,
+                Err(err) => JoseError::InvalidJwsFormat(err),
+            })
+    }
 
-            let mut b64 = true;
-            if let Some(Value::Array(vals)) = header.claim("crit") {
-                for val in vals {
-                    if let Value::String(val2) = val {
-                        if !self.is_acceptable_critical(val2) {
-                            bail!("The critical name '{}' is not supported.", val2);
-                        }
+    /// Return the header of the input that is formatted by compact serialization
+    /// without verifying the signature.
+    ///
+    /// The returned header is untrusted data. It may only be used to select a key
+    /// or a verifier (e.g. by the alg and kid header claims), and the input must
+    /// be verified before any claim is relied on. The payload is not decoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input data.
+    pub fn decode_header_unverified(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<JwsHeader, JoseError> {
+        (|| -> anyhow::Result<JwsHeader> {
+            let input = input.as_ref();
+            self.check_input_len(input.len())?;
 
-                        if val2 == "b64" {
-                            if let Some(val) = header.base64url_encode_payload() {
-                                b64 = val;
-                            }
-                        }
-                    }
-                }
+            let parts: Vec<&[u8]> = input.split(|b| *b == b'.').collect();
+            if parts.len() != 3 {
+                bail!(
+                    "The compact form of JWS must be three parts separated by period: {}",
+                    parts.len()
+                );
+            }
+            if parts[0].is_empty() {
+                bail!("The JWS header part must not be empty.");
             }
 
-            let message = &input[..(indexies[1])];
-            let signature = base64::decode_config(signature, base64::URL_SAFE_NO_PAD)?;
-            verifier.verify(message, &signature)?;
-
-            let payload = if b64 {
-                base64::decode_config(payload, base64::URL_SAFE_NO_PAD)?
-            } else {
-                payload.to_vec()
-            };
-
-            Ok((payload, header))
+            let header = self.decode_base64(parts[0])?;
+            let header = util::parse_json_object(&header)?;
+            let header = JwsHeader::from_map(header)?;
+            Ok(header)
         })()
         .map_err(|err| match err.downcast::<JoseError>() {
             Ok(err) => err,
@@ -533,7 +652,8 @@ impl JwsContext {
     {
         (|| -> anyhow::Result<(Vec<u8>, JwsHeader)> {
             let input = input.as_ref();
-            let mut map: Map<String, Value> = serde_json::from_slice(input)?;
+            self.check_input_len(input.len())?;
+            let mut map = util::parse_json_object(input)?;
 
             let payload_b64 = match map.remove("payload") {
                 Some(Value::String(val)) => val,
@@ -570,32 +690,17 @@ impl JwsContext {
                     None => bail!("The JWS alg header claim must be in protected."),
                 };
 
-                let protected_vec = base64::decode_config(&protected_b64, base64::URL_SAFE_NO_PAD)?;
-                let protected_map: Map<String, Value> = serde_json::from_slice(&protected_vec)?;
+                let protected_vec = self.decode_base64(&protected_b64)?;
+                let protected_map = util::parse_json_object(&protected_vec)?;
 
-                let mut b64 = true;
-                if let Some(Value::Array(vals)) = protected_map.get("critical") {
-                    for val in vals {
-                        match val {
-                            Value::String(name) => {
-                                if !self.is_acceptable_critical(name) {
-                                    bail!("The critical name '{}' is not supported.", name);
-                                }
-
-                                if name == "b64" {
-                                    match protected_map.get("b64") {
-                                        Some(Value::Bool(b64_val)) => {
-                                            b64 = *b64_val;
-                                        }
-                                        Some(_) => bail!("The JWS b64 header claim must be bool."),
-                                        None => {}
-                                    }
-                                }
-                            }
-                            _ => bail!("The JWS critical header claim must be a array of string."),
-                        }
+                if let Some(Value::Object(val)) = &header {
+                    if val.contains_key("crit") {
+                        bail!("The JWS crit header claim must be in protected.");
+                    } else if val.contains_key("b64") {
+                        bail!("The JWS b64 header claim must be in protected.");
                     }
                 }
+                let b64 = self.check_critical(&protected_map)?;
 
                 let merged_map = match header {
                     Some(Value::Object(mut val)) => {
@@ -617,9 +722,7 @@ impl JwsContext {
                 }
 
                 let signature = match sig.get("signature") {
-                    Some(Value::String(val)) => {
-                        base64::decode_config(val, base64::URL_SAFE_NO_PAD)?
-                    }
+                    Some(Value::String(val)) => self.decode_base64(val)?,
                     Some(_) => bail!("The signature field must be string."),
                     None => bail!("The signature field is required."),
                 };
@@ -653,8 +756,9 @@ impl JwsContext {
                 let message = format!("{}.{}", &protected_b64, &payload_b64);
                 verifier.verify(message.as_bytes(), &signature)?;
 
+                self.check_payload_len(payload_b64.len(), b64)?;
                 let payload = if b64 {
-                    base64::decode_config(&payload_b64, base64::URL_SAFE_NO_PAD)?
+                    self.decode_base64(&payload_b64)?
                 } else {
                     payload_b64.into_bytes()
                 };
@@ -669,4 +773,214 @@ impl JwsContext {
             Err(err) => JoseError::InvalidJwsFormat(err),
         })
     }
+
+    /// Verify the signature of the compact serialization and return the raw
+    /// payload part, the header and whether the payload is base64url encoded.
+    fn verify_compact<'a, 'b, F>(
+        &self,
+        input: &'b [u8],
+        selector: F,
+    ) -> anyhow::Result<(&'b [u8], JwsHeader, bool)>
+    where
+        F: Fn(&JwsHeader) -> Result<Option<&'a dyn JwsVerifier>, JoseError>,
+    {
+        self.check_input_len(input.len())?;
+
+        let indexies: Vec<usize> = input
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| **b == b'.' as u8)
+            .map(|(pos, _)| pos)
+            .collect();
+        if indexies.len() != 2 {
+            bail!(
+                "The compact form of JWS must be three parts separated by period: {}",
+                indexies.len() + 1
+            );
+        }
+
+        let header = &input[0..indexies[0]];
+        let payload = &input[(indexies[0] + 1)..(indexies[1])];
+        let signature = &input[(indexies[1] + 1)..];
+
+        // The payload may be empty (e.g. detached content), and the signature is
+        // checked by the verifier (e.g. empty for an unsecured JWS).
+        if header.is_empty() {
+            bail!("The JWS header part must not be empty.");
+        }
+
+        let header = self.decode_base64(header)?;
+        let header = util::parse_json_object(&header)?;
+        let header = JwsHeader::from_map(header)?;
+
+        let verifier = match selector(&header)? {
+            Some(val) => val,
+            None => bail!("A verifier is not found."),
+        };
+
+        match header.claim("alg") {
+            Some(Value::String(val)) => {
+                let expected_alg = verifier.algorithm().name();
+                if val != expected_alg {
+                    bail!("The JWS alg header claim is not {}: {}", expected_alg, val);
+                }
+            }
+            Some(_) => bail!("The JWS alg header claim must be a string."),
+            None => bail!("The JWS alg header claim is required."),
+        }
+
+        match verifier.key_id() {
+            Some(expected) => match header.key_id() {
+                Some(actual) if expected == actual => {}
+                Some(actual) => bail!("The JWS kid header claim is mismatched: {}", actual),
+                None => bail!("The JWS kid header claim is required."),
+            },
+            None => {}
+        }
+
+        let b64 = self.check_critical(header.claims_set())?;
+
+        let message = &input[..(indexies[1])];
+        let signature = self.decode_base64(signature)?;
+        verifier.verify(message, &signature)?;
+
+        Ok((payload, header, b64))
+    }
+
+    /// Check the crit header claim (RFC 7515 Section 4.1.11) and return
+    /// whether the payload is base64url encoded (RFC 7797).
+    ///
+    /// Each name must be registered by add_acceptable_critical, must not be
+    /// a header claim defined by RFC 7515 and must be present in the header.
+    fn check_critical(&self, header: &Map<String, Value>) -> anyhow::Result<bool> {
+        let vals = match header.get("crit") {
+            Some(Value::Array(vals)) if !vals.is_empty() => vals,
+            Some(Value::Array(_)) => bail!("The JWS crit header claim must not be empty."),
+            Some(_) => bail!("The JWS crit header claim must be a array of string."),
+            None => return Self::detect_b64(header),
+        };
+
+        for val in vals {
+            let name = match val {
+                Value::String(val) => val.as_str(),
+                _ => bail!("The JWS crit header claim must be a array of string."),
+            };
+            match name {
+                "alg" | "jku" | "jwk" | "kid" | "x5u" | "x5c" | "x5t" | "x5t#S256" | "typ"
+                | "cty" | "crit" => {
+                    bail!("The JWS crit header claim must not contain '{}'.", name);
+                }
+                _ => {}
+            }
+            if !self.is_acceptable_critical(name) {
+                bail!("The critical name '{}' is not supported.", name);
+            }
+            if !header.contains_key(name) {
+                bail!("The critical name '{}' is not in the header.", name);
+            }
+        }
+
+        Self::detect_b64(header)
+    }
+
+    /// Return whether the payload is base64url encoded by the b64 header claim.
+    ///
+    /// RFC 7797 Section 6 requires b64 to be listed in the crit header claim,
+    /// so a b64 header claim of false without it is rejected.
+    fn detect_b64(header: &Map<String, Value>) -> anyhow::Result<bool> {
+        let critical = match header.get("crit") {
+            Some(Value::Array(vals)) => vals.iter().any(|val| val == "b64"),
+            _ => false,
+        };
+
+        match header.get("b64") {
+            Some(Value::Bool(false)) if !critical => {
+                bail!("The JWS b64 header claim must be listed in the crit header claim.")
+            }
+            Some(Value::Bool(val)) => Ok(*val),
+            Some(_) => bail!("The JWS b64 header claim must be bool."),
+            None if critical => bail!("The critical name 'b64' is not in the header."),
+            None => Ok(true),
+        }
+    }
+
+    fn embed_public_jwk(
+        &self,
+        header: &mut Map<String, Value>,
+        signer: &dyn JwsSigner,
+    ) -> anyhow::Result<()> {
+        if !self.embed_public_jwk || header.contains_key("jwk") {
+            return Ok(());
+        }
+
+        match signer.public_key_jwk() {
+            Some(val) => {
+                header.insert("jwk".to_string(), Value::Object(val.into()));
+                Ok(())
+            }
+            None => bail!("The signer has no public key to embed as the jwk header claim."),
+        }
+    }
+
+    fn check_input_len(&self, len: usize) -> anyhow::Result<()> {
+        match self.max_input_len {
+            Some(max) if len > max => bail!("The input is too long: {} > {}", len, max),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the length of a payload part before it is decoded.
+    /// A base64url text of n characters is decoded to at most n * 3 / 4 bytes.
+    fn check_payload_len(&self, encoded_len: usize, b64: bool) -> anyhow::Result<()> {
+        let len = if b64 {
+            encoded_len / 4 * 3 + encoded_len % 4 * 3 / 4
+        } else {
+            encoded_len
+        };
+        match self.max_payload_len {
+            Some(max) if len > max => bail!("The payload is too long: {} > {}", len, max),
+            _ => Ok(()),
+        }
+    }
+
+    fn decode_base64(&self, input: impl AsRef<[u8]>) -> anyhow::Result<Vec<u8>> {
+        if self.lenient_base64 {
+            Ok(util::decode_base64_url_lenient(input)?)
+        } else {
+            util::decode_base64url_segment(input)
+        }
+    }
+
+    fn build_signing_input(
+        header: &Map<String, Value>,
+        payload: &[u8],
+        b64: bool,
+        signature_len: usize,
+    ) -> anyhow::Result<String> {
+        let header_bytes = serde_json::to_vec(header)?;
+
+        let mut capacity = 2;
+        capacity += util::ceiling(header_bytes.len() * 4, 3);
+        capacity += if b64 {
+            util::ceiling(payload.len() * 4, 3)
+        } else {
+            payload.len()
+        };
+        capacity += util::ceiling(signature_len * 4, 3);
+
+        let mut message = String::with_capacity(capacity);
+        base64::encode_config_buf(header_bytes, base64::URL_SAFE_NO_PAD, &mut message);
+        message.push_str(".");
+        if b64 {
+            base64::encode_config_buf(payload, base64::URL_SAFE_NO_PAD, &mut message);
+        } else {
+            let payload = std::str::from_utf8(payload)?;
+            if payload.contains(".") {
+                bail!("A JWS payload cannot contain dot.");
+            }
+            message.push_str(payload);
+        }
+
+        Ok(message)
+    }
 }
@@ -2,11 +2,35 @@ use std::fmt::{Debug, Display};
 use std::ops::Deref;
 
 use anyhow::bail;
+use openssl::hash;
 
 use crate::jwk::Jwk;
-use crate::util;
+use crate::util::{self, HashAlgorithm};
 use crate::{JoseError, JoseHeader, Map, Value};
 
+/// Return the X.509 certificate SHA-1 thumbprint (x5t) of a certificate.
+///
+/// # Arguments
+///
+/// * `cert_der` - A DER encoded X.509 certificate
+pub fn compute_x5t(cert_der: impl AsRef<[u8]>) -> String {
+    compute_thumbprint(cert_der.as_ref(), HashAlgorithm::Sha1)
+}
+
+/// Return the X.509 certificate SHA-256 thumbprint (x5t#S256) of a certificate.
+///
+/// # Arguments
+///
+/// * `cert_der` - A DER encoded X.509 certificate
+pub fn compute_x5t_s256(cert_der: impl AsRef<[u8]>) -> String {
+    compute_thumbprint(cert_der.as_ref(), HashAlgorithm::Sha256)
+}
+
+fn compute_thumbprint(cert_der: &[u8], hash_algorithm: HashAlgorithm) -> String {
+    let digest = hash::hash(hash_algorithm.message_digest(), cert_der).unwrap();
+    base64::encode_config(digest, base64::URL_SAFE_NO_PAD)
+}
+
 /// Represent JWS header claims
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct JwsHeader {
@@ -26,7 +50,7 @@ impl JwsHeader {
     /// * `value` - The json style header claims
     pub fn from_bytes(value: &[u8]) -> Result<Self, JoseError> {
         let claims = (|| -> anyhow::Result<Map<String, Value>> {
-            let claims: Map<String, Value> = serde_json::from_slice(value)?;
+            let claims = util::parse_json_object(value)?;
             Ok(claims)
         })()
         .map_err(|err| JoseError::InvalidJson(err))?;
@@ -220,6 +244,44 @@ impl JwsHeader {
         }
     }
 
+    /// Test whether the X.509 certificate thumbprint header claims (x5t, x5t#S256)
+    /// match a certificate.
+    ///
+    /// All of the thumbprint header claims that are present must match.
+    /// If no thumbprint header claim is present, this returns false.
+    ///
+    /// # Arguments
+    ///
+    /// * `cert_der` - A DER encoded X.509 certificate
+    pub fn matches_certificate(&self, cert_der: impl AsRef<[u8]>) -> bool {
+        let cert_der = cert_der.as_ref();
+        let mut matched = false;
+
+        match self.claims.get("x5t") {
+            Some(Value::String(val)) => {
+                if val != &compute_x5t(cert_der) {
+                    return false;
+                }
+                matched = true;
+            }
+            Some(_) => return false,
+            None => {}
+        }
+
+        match self.claims.get("x5t#S256") {
+            Some(Value::String(val)) => {
+                if val != &compute_x5t_s256(cert_der) {
+                    return false;
+                }
+                matched = true;
+            }
+            Some(_) => return false,
+            None => {}
+        }
+
+        matched
+    }
+
     /// Set a value for key ID header claim (kid).
     ///
     /// # Arguments
@@ -498,11 +560,14 @@ impl Deref for JwsHeader {
 
 #[cfg(test)]
 mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
     use anyhow::Result;
     use serde_json::json;
 
     use crate::jwk::Jwk;
-    use crate::jws::JwsHeader;
+    use crate::jws::{self, JwsHeader};
 
     #[test]
     fn test_new_jws_header() -> Result<()> {
@@ -547,4 +612,38 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_x509_certificate_thumbprint() -> Result<()> {
+        let cert = load_file("der/RSA_2048bit_cert.der")?;
+
+        let x5t = jws::compute_x5t(&cert);
+        let x5t_s256 = jws::compute_x5t_s256(&cert);
+        assert_eq!(x5t, "7RlUxw3V_PXPBoOCwI611JeI--s");
+        assert_eq!(x5t_s256, "B_b6REmfNaFaFR01D3rXzNO6oawokPX0ZxpfWVjCebw");
+
+        let mut header = JwsHeader::new();
+        assert!(!header.matches_certificate(&cert));
+
+        header.set_claim("x5t", Some(json!(x5t)))?;
+        assert!(header.matches_certificate(&cert));
+
+        header.set_claim("x5t#S256", Some(json!(x5t_s256)))?;
+        assert!(header.matches_certificate(&cert));
+        assert!(!header.matches_certificate(b"other certificate"));
+
+        header.set_x509_certificate_sha256_thumbprint(b"unmatched thumbprint");
+        assert!(!header.matches_certificate(&cert));
+
+        Ok(())
+    }
+
+    fn load_file(path: &str) -> Result<Vec<u8>> {
+        let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        pb.push("data");
+        pb.push(path);
+
+        let data = fs::read(&pb)?;
+        Ok(data)
+    }
 }
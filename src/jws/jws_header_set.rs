@@ -447,6 +447,31 @@ impl JwsHeaderSet {
         Ok(())
     }
 
+    /// Set a value for a header claim in the protected header.
+    ///
+    /// The claim is removed from the unprotected header if it exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key name of a header claim
+    /// * `value` - A typed value of a header claim
+    pub fn set_protected(&mut self, key: &str, value: Value) -> Result<(), JoseError> {
+        self.set_claim(key, Some(value), true)
+    }
+
+    /// Set a value for a header claim in the unprotected header.
+    ///
+    /// The claim is removed from the protected header if it exists.
+    /// An unprotected claim is not covered by the signature.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key name of a header claim
+    /// * `value` - A typed value of a header claim
+    pub fn set_unprotected(&mut self, key: &str, value: Value) -> Result<(), JoseError> {
+        self.set_claim(key, Some(value), false)
+    }
+
     /// Return values for header claims set
     pub fn claims_set(&self, protection: bool) -> &Map<String, Value> {
         if protection {
@@ -1,16 +1,20 @@
 //! JSON Web Token (JWT) support.
 
 pub mod alg;
+mod clock;
 mod jwt_context;
 mod jwt_payload;
 mod jwt_payload_validator;
 
+pub use crate::jwt::clock::{Clock, SystemClock};
 pub use crate::jwt::jwt_context::JwtContext;
 pub use crate::jwt::jwt_payload::JwtPayload;
 pub use crate::jwt::jwt_payload_validator::JwtPayloadValidator;
 
 pub use crate::jwt::alg::unsecured::UnsecuredJwsAlgorithm::None;
 
+use std::time::{Duration, SystemTime};
+
 use once_cell::sync::Lazy;
 
 use crate::jwe::{JweDecrypter, JweEncrypter, JweHeader};
@@ -31,6 +35,7 @@ pub fn encode_unsecured(payload: &JwtPayload, header: &JwsHeader) -> Result<Stri
 }
 
 /// Return the string repsentation of the JWT with the siginig algorithm.
+/// The typ header claim is set to "JWT" if it is absent.
 ///
 /// # Arguments
 ///
@@ -69,6 +74,18 @@ pub fn decode_header(input: impl AsRef<[u8]>) -> Result<Box<dyn JoseHeader>, Jos
     DEFAULT_CONTEXT.decode_header(input)
 }
 
+/// Return the JWS header of a signed JWT without verifying the signature.
+///
+/// The returned header is untrusted data that may only be used to select a key.
+/// The payload is not decoded.
+///
+/// # Arguments
+///
+/// * `input` - a JWT string representation.
+pub fn decode_header_unverified(input: impl AsRef<[u8]>) -> Result<JwsHeader, JoseError> {
+    DEFAULT_CONTEXT.decode_header_unverified(input)
+}
+
 /// Return the JWT object decoded with the "none" algorithm.
 ///
 /// # Arguments
@@ -91,6 +108,22 @@ pub fn decode_with_verifier(
     DEFAULT_CONTEXT.decode_with_verifier(input, verifier)
 }
 
+/// Return the JWT object decoded from the value of an HTTP Authorization header.
+///
+/// A case-insensitive "Bearer" scheme and surrounding whitespace are stripped
+/// before decoding. A bare token is accepted, but any other scheme is rejected.
+///
+/// # Arguments
+///
+/// * `value` - a value of an Authorization header.
+/// * `verifier` - a verifier of the signing algorithm.
+pub fn decode_from_authorization_header(
+    value: &str,
+    verifier: &dyn JwsVerifier,
+) -> Result<(JwtPayload, JwsHeader), JoseError> {
+    DEFAULT_CONTEXT.decode_from_authorization_header(value, verifier)
+}
+
 /// Return the JWT object decoded with a selected verifying algorithm.
 ///
 /// # Arguments
@@ -125,6 +158,45 @@ where
     DEFAULT_CONTEXT.decode_with_verifier_in_jwk_set(input, jwk_set, selector)
 }
 
+/// Return the JWT object decoded by using a JWK set with the JWK that verified it.
+///
+/// Every JWK that has the kid header claim of the input is tried in order.
+///
+/// # Arguments
+///
+/// * `input` - a JWT string representation.
+/// * `jwk_set` - a JWK set.
+/// * `selector` - a function for selecting the verifying algorithm.
+pub fn decode_with_verifier_in_jwk_set_matched<'a, 'b, F>(
+    input: impl AsRef<[u8]>,
+    jwk_set: &'b JwkSet,
+    selector: F,
+) -> Result<(JwtPayload, JwsHeader, &'b Jwk), JoseError>
+where
+    F: Fn(&Jwk) -> Result<Option<&'a dyn JwsVerifier>, JoseError>,
+{
+    DEFAULT_CONTEXT.decode_with_verifier_in_jwk_set_matched(input, jwk_set, selector)
+}
+
+/// Return the JWT object decoded by using a JWK set that is fetched
+/// from the JWK set URL header claim (jku).
+///
+/// The fetcher is responsible for allowing only trusted URLs.
+///
+/// # Arguments
+///
+/// * `input` - a JWT string representation.
+/// * `fetcher` - a function that returns the JWK set of a URL.
+pub fn decode_with_jwk_set_url<F>(
+    input: impl AsRef<[u8]>,
+    fetcher: F,
+) -> Result<(JwtPayload, JwsHeader), JoseError>
+where
+    F: Fn(&str) -> Result<JwkSet, JoseError>,
+{
+    DEFAULT_CONTEXT.decode_with_jwk_set_url(input, fetcher)
+}
+
 /// Return the JWT object decoded by the selected decrypter.
 ///
 /// # Arguments
@@ -172,6 +244,111 @@ where
     DEFAULT_CONTEXT.decode_with_decrypter_in_jwk_set(input, jwk_set, selector)
 }
 
+/// Return the string repsentation of the nested JWT that is signed and then encrypted.
+/// The cty header claim of the JWE is set to "JWT".
+///
+/// # Arguments
+///
+/// * `payload` - The payload data.
+/// * `jws_header` - The JWS heaser claims of the inner JWT.
+/// * `signer` - a signer object.
+/// * `jwe_header` - The JWE heaser claims of the outer JWE.
+/// * `encrypter` - a encrypter object.
+pub fn encode_nested(
+    payload: &JwtPayload,
+    jws_header: &JwsHeader,
+    signer: &dyn JwsSigner,
+    jwe_header: &JweHeader,
+    encrypter: &dyn JweEncrypter,
+) -> Result<String, JoseError> {
+    DEFAULT_CONTEXT.encode_nested(payload, jws_header, signer, jwe_header, encrypter)
+}
+
+/// Return the JWT object decoded from the nested JWT that is signed and then encrypted.
+///
+/// # Arguments
+///
+/// * `input` - a nested JWT string representation.
+/// * `decrypter` - a decrypter of the outer JWE.
+/// * `verifier` - a verifier of the inner JWS.
+pub fn decode_nested(
+    input: impl AsRef<[u8]>,
+    decrypter: &dyn JweDecrypter,
+    verifier: &dyn JwsVerifier,
+) -> Result<(JwtPayload, JwsHeader, JweHeader), JoseError> {
+    DEFAULT_CONTEXT.decode_nested(input, decrypter, verifier)
+}
+
+/// Return a JWT that is verified by an old key and signed again by a new key.
+///
+/// # Arguments
+///
+/// * `input` - a JWT string representation.
+/// * `verifier` - a verifier of the old key.
+/// * `signer` - a signer of the new key.
+/// * `header` - The JWS header claims of the new JWT.
+/// * `issued_at` - a new time for the issued at payload claim (iat).
+pub fn resign(
+    input: impl AsRef<[u8]>,
+    verifier: &dyn JwsVerifier,
+    signer: &dyn JwsSigner,
+    header: &JwsHeader,
+    issued_at: Option<&SystemTime>,
+) -> Result<String, JoseError> {
+    DEFAULT_CONTEXT.resign(input, verifier, signer, header, issued_at)
+}
+
+/// Return a DPoP proof JWT (RFC 9449) for a HTTP request.
+///
+/// # Arguments
+///
+/// * `method` - The HTTP method of the request (e.g. "POST").
+/// * `url` - The HTTP target URI of the request without query and fragment.
+/// * `signer` - a signer object of a asymmetric algorithm.
+/// * `jwk` - The key of the signer. Only the public key parameters are embedded.
+pub fn encode_dpop_proof(
+    method: &str,
+    url: &str,
+    signer: &dyn JwsSigner,
+    jwk: &Jwk,
+) -> Result<String, JoseError> {
+    DEFAULT_CONTEXT.encode_dpop_proof(method, url, signer, jwk)
+}
+
+/// Return the JWT object of a DPoP proof (RFC 9449) verified for a HTTP request.
+///
+/// # Arguments
+///
+/// * `input` - a DPoP proof JWT string representation.
+/// * `method` - The HTTP method of the request.
+/// * `url` - The HTTP target URI of the request.
+/// * `max_age` - The maximum difference between the iat claim and now.
+pub fn decode_dpop_proof(
+    input: impl AsRef<[u8]>,
+    method: &str,
+    url: &str,
+    max_age: Duration,
+) -> Result<(JwtPayload, JwsHeader), JoseError> {
+    DEFAULT_CONTEXT.decode_dpop_proof(input, method, url, max_age)
+}
+
+/// Return the JWT object verified by the public key of the jwk header claim
+/// that is accepted by a predicate.
+///
+/// # Arguments
+///
+/// * `input` - a JWT string representation.
+/// * `predicate` - a function that returns true if the embedded JWK is expected.
+pub fn decode_with_embedded_jwk<F>(
+    input: impl AsRef<[u8]>,
+    predicate: F,
+) -> Result<(JwtPayload, JwsHeader), JoseError>
+where
+    F: Fn(&Jwk) -> bool,
+{
+    DEFAULT_CONTEXT.decode_with_embedded_jwk(input, predicate)
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -181,19 +358,20 @@ mod tests {
     use anyhow::Result;
     use serde_json::json;
 
+    use crate::jwe::JweHeader;
     #[allow(deprecated)]
     use crate::jwe::{
         Dir, A128GCMKW, A128KW, A192GCMKW, A192KW, A256GCMKW, A256KW, ECDH_ES, ECDH_ES_A128KW,
         ECDH_ES_A192KW, ECDH_ES_A256KW, PBES2_HS256_A128KW, PBES2_HS384_A192KW, PBES2_HS512_A256KW,
         RSA1_5, RSA_OAEP, RSA_OAEP_256,
     };
-    use crate::jwk::Jwk;
+    use crate::jwk::{Jwk, JwkSet};
     use crate::jws::{
-        EdDSA, JwsHeader, ES256, ES256K, ES384, ES512, HS256, HS384, HS512, PS256, PS384, PS512,
-        RS256, RS384, RS512,
+        self, EdDSA, JwsHeader, JwsVerifier, ES256, ES256K, ES384, ES512, HS256, HS384, HS512,
+        PS256, PS384, PS512, RS256, RS384, RS512,
     };
-    use crate::jwt::{self, JwtPayload};
-    use crate::util;
+    use crate::jwt::{self, Clock, JwtContext, JwtPayload};
+    use crate::util::{self, HashAlgorithm};
     use crate::Value;
 
     #[test]
@@ -209,6 +387,29 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_decode_header_unverified() -> Result<()> {
+        let signer = ES256.signer_from_jwk(&ES256.generate_key_pair()?.to_jwk_key_pair())?;
+        let mut header = JwsHeader::new();
+        header.set_key_id("key-1");
+        let jwt_string = jwt::encode_with_signer(&JwtPayload::new(), &header, &signer)?;
+
+        let header = jwt::decode_header_unverified(&jwt_string)?;
+        assert_eq!(header.algorithm(), Some("ES256"));
+        assert_eq!(header.key_id(), Some("key-1"));
+
+        // The payload part is not decoded.
+        let parts: Vec<&str> = jwt_string.split('.').collect();
+        let jwt_string = format!("{}.!!!.{}", parts[0], parts[2]);
+        let header = jwt::decode_header_unverified(&jwt_string)?;
+        assert_eq!(header.key_id(), Some("key-1"));
+
+        assert!(jwt::decode_header_unverified(parts[0]).is_err());
+        assert!(jwt::decode_header_unverified(format!("!!!.{}.{}", parts[1], parts[2])).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_jwt_unsecured() -> Result<()> {
         let mut src_header = JwsHeader::new();
@@ -243,6 +444,328 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_jwt_acceptable_token_types() -> Result<()> {
+        let key = util::random_bytes(32);
+        let signer = HS256.signer_from_bytes(&key)?;
+        let verifier = HS256.verifier_from_bytes(&key)?;
+
+        let mut context = JwtContext::new();
+        context.add_acceptable_token_type("JWT");
+        context.add_acceptable_token_type("at+jwt");
+
+        let payload = JwtPayload::new();
+        for (token_type, acceptable) in &[
+            ("at+jwt", true),
+            ("application/at+jwt", true),
+            ("JWT", true),
+            ("dpop+jwt", false),
+        ] {
+            let mut header = JwsHeader::new();
+            header.set_token_type(*token_type);
+            let jwt_string = context.encode_with_signer(&payload, &header, &signer)?;
+
+            let result = context.decode_with_verifier(&jwt_string, &verifier);
+            assert_eq!(result.is_ok(), *acceptable, "{}", token_type);
+
+            // The default context doesn't restrict the typ header claim.
+            jwt::decode_with_verifier(&jwt_string, &verifier)?;
+        }
+
+        let mut header = JwsHeader::new();
+        header.set_token_type("dpop+jwt");
+        let jwt_string = context.encode_with_signer(&payload, &header, &signer)?;
+        context.add_acceptable_token_type("DPoP+JWT");
+        context.decode_with_verifier(&jwt_string, &verifier)?;
+        context.remove_acceptable_token_type("dpop+jwt");
+        assert!(context
+            .decode_with_verifier(&jwt_string, &verifier)
+            .is_err());
+
+        let mut header = JweHeader::new();
+        header.set_content_encryption("A128GCM");
+        header.set_token_type("dpop+jwt");
+        let encrypter = Dir.encrypter_from_bytes(&key[..16])?;
+        let decrypter = Dir.decrypter_from_bytes(&key[..16])?;
+        let jwt_string = context.encode_with_encrypter(&payload, &header, &encrypter)?;
+        assert!(context
+            .decode_with_decrypter(&jwt_string, &decrypter)
+            .is_err());
+        header.set_token_type("JWT");
+        let jwt_string = context.encode_with_encrypter(&payload, &header, &encrypter)?;
+        context.decode_with_decrypter(&jwt_string, &decrypter)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_resign() -> Result<()> {
+        let old_key = util::random_bytes(32);
+        let old_signer = HS256.signer_from_bytes(&old_key)?;
+        let old_verifier = HS256.verifier_from_bytes(&old_key)?;
+        let new_key_pair = ES256.generate_key_pair()?;
+        let new_signer = ES256.signer_from_der(new_key_pair.to_der_private_key())?;
+        let new_verifier = ES256.verifier_from_der(new_key_pair.to_der_public_key())?;
+
+        let mut payload = JwtPayload::new();
+        payload.set_subject("subject");
+        payload.set_audience(vec!["aud1", "aud2"]);
+        payload.set_issued_at(&(SystemTime::UNIX_EPOCH + Duration::from_secs(1000)))?;
+        payload.set_claim("custom", Some(json!({"nested": [1, 2]})))?;
+
+        let mut header = JwsHeader::new();
+        header.set_key_id("old-key");
+        let old_jwt = jwt::encode_with_signer(&payload, &header, &old_signer)?;
+
+        let mut new_header = JwsHeader::new();
+        new_header.set_key_id("new-key");
+        let new_jwt = jwt::resign(&old_jwt, &old_verifier, &new_signer, &new_header, None)?;
+        let (new_payload, decoded_header) = jwt::decode_with_verifier(&new_jwt, &new_verifier)?;
+        assert_eq!(new_payload, payload);
+        assert_eq!(decoded_header.key_id(), Some("new-key"));
+        assert_eq!(decoded_header.algorithm(), Some("ES256"));
+        assert!(jwt::decode_with_verifier(&new_jwt, &old_verifier).is_err());
+
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(2000);
+        let new_jwt = jwt::resign(
+            &old_jwt,
+            &old_verifier,
+            &new_signer,
+            &new_header,
+            Some(&now),
+        )?;
+        let (new_payload, _) = jwt::decode_with_verifier(&new_jwt, &new_verifier)?;
+        assert_eq!(new_payload.issued_at(), Some(now));
+        assert_eq!(new_payload.subject(), Some("subject"));
+        assert_eq!(new_payload.claim("custom"), payload.claim("custom"));
+
+        // A JWT whose signature is invalid is not signed again.
+        let mut tampered = old_jwt.clone();
+        tampered.pop();
+        tampered.push(if old_jwt.ends_with('A') { 'B' } else { 'A' });
+        assert!(jwt::resign(&tampered, &old_verifier, &new_signer, &new_header, None).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_dpop_proof() -> Result<()> {
+        let key_pair = ES256.generate_key_pair()?;
+        let jwk = key_pair.to_jwk_key_pair();
+        let signer = ES256.signer_from_jwk(&jwk)?;
+        let max_age = Duration::from_secs(60);
+
+        let proof = jwt::encode_dpop_proof("POST", "https://example/token", &signer, &jwk)?;
+
+        let (payload, header) =
+            jwt::decode_dpop_proof(&proof, "POST", "https://example/token?a=b", max_age)?;
+        assert_eq!(header.token_type(), Some("dpop+jwt"));
+        assert_eq!(header.jwk(), Some(jwk.to_public_key()?));
+        assert_eq!(payload.claim("htm"), Some(&json!("POST")));
+        assert_eq!(payload.claim("htu"), Some(&json!("https://example/token")));
+        assert!(payload.jwt_id().is_some());
+        assert!(payload.issued_at().is_some());
+
+        assert!(jwt::decode_dpop_proof(&proof, "GET", "https://example/token", max_age).is_err());
+        assert!(jwt::decode_dpop_proof(&proof, "POST", "https://example/other", max_age).is_err());
+
+        // The signature must be made by the key in the jwk header claim.
+        let other_jwk = ES256.generate_key_pair()?.to_jwk_key_pair();
+        let other_signer = ES256.signer_from_jwk(&other_jwk)?;
+        let mut header = JwsHeader::new();
+        header.set_token_type("dpop+jwt");
+        header.set_jwk(jwk.to_public_key()?);
+        let (payload, _) = jwt::decode_with_verifier(&proof, &ES256.verifier_from_jwk(&jwk)?)?;
+        let forged = jwt::encode_with_signer(&payload, &header, &other_signer)?;
+        assert!(jwt::decode_dpop_proof(&forged, "POST", "https://example/token", max_age).is_err());
+
+        // A max age that overflows the time range is an error, not a panic.
+        assert!(
+            jwt::decode_dpop_proof(&proof, "POST", "https://example/token", Duration::MAX).is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_dpop_proof_with_clock() -> Result<()> {
+        #[derive(Debug)]
+        struct FixedClock(SystemTime);
+
+        impl Clock for FixedClock {
+            fn now(&self) -> SystemTime {
+                self.0
+            }
+        }
+
+        let key_pair = ES256.generate_key_pair()?;
+        let jwk = key_pair.to_jwk_key_pair();
+        let signer = ES256.signer_from_jwk(&jwk)?;
+        let max_age = Duration::from_secs(60);
+        let issued_time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+
+        let mut context = JwtContext::new();
+        context.set_clock(FixedClock(issued_time));
+        let proof = context.encode_dpop_proof("POST", "https://example/token", &signer, &jwk)?;
+        let (payload, _) =
+            context.decode_dpop_proof(&proof, "POST", "https://example/token", max_age)?;
+        assert_eq!(payload.issued_at(), Some(issued_time));
+
+        context.set_clock(FixedClock(issued_time + Duration::from_secs(30)));
+        context.decode_dpop_proof(&proof, "POST", "https://example/token", max_age)?;
+
+        context.set_clock(FixedClock(issued_time + Duration::from_secs(120)));
+        assert!(context
+            .decode_dpop_proof(&proof, "POST", "https://example/token", max_age)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_decode_with_embedded_jwk() -> Result<()> {
+        let bound_jwk = ES256.generate_key_pair()?.to_jwk_key_pair();
+        let expected = bound_jwk.thumbprint(HashAlgorithm::Sha256)?;
+        let is_expected =
+            |jwk: &Jwk| jwk.thumbprint(HashAlgorithm::Sha256).ok().as_deref() == Some(&expected);
+
+        let mut payload = JwtPayload::new();
+        payload.set_subject("subject");
+
+        let mut header = JwsHeader::new();
+        header.set_jwk(bound_jwk.to_public_key()?);
+        let signer = ES256.signer_from_jwk(&bound_jwk)?;
+        let jwt = jwt::encode_with_signer(&payload, &header, &signer)?;
+        let (decoded, _) = jwt::decode_with_embedded_jwk(&jwt, is_expected)?;
+        assert_eq!(decoded.subject(), Some("subject"));
+
+        // The signature is valid under the embedded key, but the key is not the bound one.
+        let other_jwk = ES256.generate_key_pair()?.to_jwk_key_pair();
+        let mut header = JwsHeader::new();
+        header.set_jwk(other_jwk.to_public_key()?);
+        let signer = ES256.signer_from_jwk(&other_jwk)?;
+        let jwt = jwt::encode_with_signer(&payload, &header, &signer)?;
+        jwt::decode_with_embedded_jwk(&jwt, |_| true)?;
+        let err = jwt::decode_with_embedded_jwk(&jwt, is_expected).unwrap_err();
+        assert!(
+            matches!(err, crate::JoseError::InvalidSignature(_)),
+            "{}",
+            err
+        );
+
+        // The embedded key must verify the signature even if it is the bound one.
+        let mut header = JwsHeader::new();
+        header.set_jwk(bound_jwk.to_public_key()?);
+        let jwt = jwt::encode_with_signer(&payload, &header, &signer)?;
+        assert!(jwt::decode_with_embedded_jwk(&jwt, is_expected).is_err());
+
+        let jwt = jwt::encode_with_signer(&payload, &JwsHeader::new(), &signer)?;
+        assert!(jwt::decode_with_embedded_jwk(&jwt, |_| true).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_decode_with_verifier_in_jwk_set_matched() -> Result<()> {
+        let mut jwks = Vec::new();
+        for kid in &["old", "new", "new"] {
+            let mut jwk = Jwk::generate_oct_key(32)?;
+            jwk.set_key_id(*kid);
+            jwks.push(jwk);
+        }
+        let jwk_set: JwkSet = jwks.iter().cloned().collect();
+
+        let mut verifiers = Vec::new();
+        for jwk in &jwks {
+            verifiers.push((jwk.clone(), HS256.verifier_from_jwk(jwk)?));
+        }
+        let select = |jwk: &Jwk| {
+            verifiers
+                .iter()
+                .find(|(key, _)| key == jwk)
+                .map(|(_, verifier)| verifier as &dyn JwsVerifier)
+        };
+
+        let mut payload = JwtPayload::new();
+        payload.set_subject("subject");
+
+        // The second key of the shared kid is tried after the first one fails.
+        for expected in &jwks {
+            let signer = HS256.signer_from_jwk(expected)?;
+            let jwt = jwt::encode_with_signer(&payload, &JwsHeader::new(), &signer)?;
+            let (decoded, _, jwk) =
+                jwt::decode_with_verifier_in_jwk_set_matched(&jwt, &jwk_set, |jwk| {
+                    Ok(select(jwk))
+                })?;
+            assert_eq!(decoded.subject(), Some("subject"));
+            assert_eq!(jwk, expected);
+            assert_eq!(jwk.key_id(), expected.key_id());
+        }
+
+        let mut unknown = Jwk::generate_oct_key(32)?;
+        unknown.set_key_id("new");
+        let signer = HS256.signer_from_jwk(&unknown)?;
+        let jwt = jwt::encode_with_signer(&payload, &JwsHeader::new(), &signer)?;
+        assert!(
+            jwt::decode_with_verifier_in_jwk_set_matched(&jwt, &jwk_set, |jwk| Ok(select(jwk)))
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_decode_with_jwk_set_url() -> Result<()> {
+        let mut jwks = Vec::new();
+        for kid in &["key-1", "key-2"] {
+            let mut jwk = ES256.generate_key_pair()?.to_jwk_key_pair();
+            jwk.set_key_id(*kid);
+            jwks.push(jwk);
+        }
+        let jwk_set: JwkSet = jwks.iter().map(|jwk| jwk.to_public()).collect();
+
+        let fetcher = |url: &str| match url {
+            "https://example.com/jwks.json" => Ok(jwk_set.clone()),
+            _ => Err(crate::JoseError::InvalidJwtFormat(anyhow::anyhow!(
+                "The URL is not allowed: {}",
+                url
+            ))),
+        };
+
+        let mut payload = JwtPayload::new();
+        payload.set_subject("subject");
+        let signer = ES256.signer_from_jwk(&jwks[1])?;
+
+        let mut header = JwsHeader::new();
+        header.set_jwk_set_url("https://example.com/jwks.json");
+        header.set_key_id("key-2");
+        let jwt = jwt::encode_with_signer(&payload, &header, &signer)?;
+        let (decoded, _) = jwt::decode_with_jwk_set_url(&jwt, fetcher)?;
+        assert_eq!(decoded.subject(), Some("subject"));
+
+        // Every key is tried if the kid header claim is missing.
+        let mut header = JwsHeader::new();
+        header.set_jwk_set_url("https://example.com/jwks.json");
+        let jwt = jwt::encode_with_signer(&payload, &header, &signer)?;
+        let (decoded, _) = jwt::decode_with_jwk_set_url(&jwt, fetcher)?;
+        assert_eq!(decoded.subject(), Some("subject"));
+
+        header.set_key_id("key-1");
+        let jwt = jwt::encode_with_signer(&payload, &header, &signer)?;
+        assert!(jwt::decode_with_jwk_set_url(&jwt, fetcher).is_err());
+
+        header.set_jwk_set_url("https://attacker.example/jwks.json");
+        header.set_key_id("key-2");
+        let jwt = jwt::encode_with_signer(&payload, &header, &signer)?;
+        let err = jwt::decode_with_jwk_set_url(&jwt, fetcher).unwrap_err();
+        assert!(format!("{}", err).contains("not allowed"), "{}", err);
+
+        let jwt = jwt::encode_with_signer(&payload, &JwsHeader::new(), &signer)?;
+        assert!(jwt::decode_with_jwk_set_url(&jwt, fetcher).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_jwt_with_hmac() -> Result<()> {
         for alg in &[HS256, HS384, HS512] {
@@ -265,6 +788,117 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_jwt_decode_from_authorization_header() -> Result<()> {
+        let key = util::random_bytes(32);
+        let mut src_payload = JwtPayload::new();
+        src_payload.set_subject("user");
+        let signer = HS256.signer_from_bytes(&key)?;
+        let jwt_string = jwt::encode_with_signer(&src_payload, &JwsHeader::new(), &signer)?;
+        let verifier = HS256.verifier_from_bytes(&key)?;
+
+        for value in &[
+            format!("Bearer {}", jwt_string),
+            format!("bearer {}", jwt_string),
+            format!("  BEARER \t {}\r\n", jwt_string),
+            jwt_string.clone(),
+        ] {
+            let (dst_payload, _) = jwt::decode_from_authorization_header(value, &verifier)?;
+            assert_eq!(src_payload, dst_payload);
+        }
+
+        for value in &[
+            format!("Basic {}", jwt_string),
+            format!("Bearer{}", jwt_string),
+            "Bearer ".to_string(),
+        ] {
+            assert!(jwt::decode_from_authorization_header(value, &verifier).is_err());
+        }
+        assert!(matches!(
+            jwt::decode_from_authorization_header(&format!("DPoP {}", jwt_string), &verifier),
+            Err(crate::JoseError::InvalidJwtFormat(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_issue_and_read_rs256() -> Result<()> {
+        let private_key = load_file("pem/RSA_2048bit_private.pem")?;
+        let public_key = load_file("pem/RSA_2048bit_public.pem")?;
+
+        let mut header = JwsHeader::new();
+        header.set_key_id("key-1");
+        let mut payload = JwtPayload::new();
+        payload.set_issuer("https://issuer.example.com");
+        payload.set_subject("user-1");
+
+        let signer = RS256.signer_from_pem(&private_key)?;
+        let jwt_string = jwt::encode_with_signer(&payload, &header, &signer)?;
+
+        let verifier = RS256.verifier_from_pem(&public_key)?;
+        let (dst_payload, dst_header) = jwt::decode_with_verifier(&jwt_string, &verifier)?;
+        assert_eq!(dst_header.algorithm(), Some("RS256"));
+        assert_eq!(dst_header.token_type(), Some("JWT"));
+        assert_eq!(dst_header.key_id(), Some("key-1"));
+        assert_eq!(dst_payload, payload);
+
+        header.set_token_type("at+jwt");
+        let jwt_string = jwt::encode_with_signer(&payload, &header, &signer)?;
+        let (_, dst_header) = jwt::decode_with_verifier(&jwt_string, &verifier)?;
+        assert_eq!(dst_header.token_type(), Some("at+jwt"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_jwt() -> Result<()> {
+        let signing_private_key = load_file("pem/EC_P-256_private.pem")?;
+        let signing_public_key = load_file("pem/EC_P-256_public.pem")?;
+        let encryption_private_key = load_file("pem/RSA_2048bit_private.pem")?;
+        let encryption_public_key = load_file("pem/RSA_2048bit_public.pem")?;
+
+        let mut jws_header = JwsHeader::new();
+        jws_header.set_key_id("signing-key");
+        let mut jwe_header = JweHeader::new();
+        jwe_header.set_content_encryption("A256GCM");
+        let mut payload = JwtPayload::new();
+        payload.set_subject("user-1");
+
+        let signer = ES256.signer_from_pem(&signing_private_key)?;
+        let encrypter = RSA_OAEP.encrypter_from_pem(&encryption_public_key)?;
+        let jwt_string =
+            jwt::encode_nested(&payload, &jws_header, &signer, &jwe_header, &encrypter)?;
+        assert_eq!(jwt_string.split('.').count(), 5);
+
+        let verifier = ES256.verifier_from_pem(&signing_public_key)?;
+        let decrypter = RSA_OAEP.decrypter_from_pem(&encryption_private_key)?;
+        let (dst_payload, dst_jws_header, dst_jwe_header) =
+            jwt::decode_nested(&jwt_string, &decrypter, &verifier)?;
+        assert_eq!(dst_payload, payload);
+        assert_eq!(dst_jws_header.algorithm(), Some("ES256"));
+        assert_eq!(dst_jws_header.key_id(), Some("signing-key"));
+        assert_eq!(dst_jwe_header.algorithm(), Some("RSA-OAEP"));
+        assert_eq!(dst_jwe_header.content_encryption(), Some("A256GCM"));
+        assert_eq!(dst_jwe_header.content_type(), Some("JWT"));
+
+        let other_verifier =
+            ES256.verifier_from_pem(ES256.generate_key_pair()?.to_pem_public_key())?;
+        let err = jwt::decode_nested(&jwt_string, &decrypter, &other_verifier).unwrap_err();
+        assert!(format!("{}", err).contains("inner JWS"));
+
+        let other_decrypter =
+            RSA_OAEP.decrypter_from_der(RSA_OAEP.generate_key_pair(2048)?.to_der_private_key())?;
+        let err = jwt::decode_nested(&jwt_string, &other_decrypter, &verifier).unwrap_err();
+        assert!(format!("{}", err).contains("outer JWE"));
+
+        let jwt_string = jwt::encode_with_encrypter(&payload, &jwe_header, &encrypter)?;
+        let err = jwt::decode_nested(&jwt_string, &decrypter, &verifier).unwrap_err();
+        assert!(format!("{}", err).contains("outer JWE"));
+
+        Ok(())
+    }
+
     #[test]
     fn test_jwt_with_rsa_pem() -> Result<()> {
         for alg in &[RS256, RS384, RS512] {
@@ -458,6 +1092,42 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "legacy-algorithms")]
+    #[test]
+    fn test_external_jwt_verify_with_rs1() -> Result<()> {
+        use crate::jws::RS1;
+
+        let jwk = Jwk::from_bytes(&load_file("jwk/RSA_public.jwk")?)?;
+        let verifier = RS1.verifier_from_jwk(&jwk)?;
+        let jwt_string = String::from_utf8(load_file("jwt/RS1.jwt")?)?;
+        let (payload, header) = jwt::decode_with_verifier(&jwt_string, &verifier)?;
+        assert_eq!(header.algorithm(), Some("RS1"));
+        assert_eq!(payload.issuer(), Some("joe"));
+
+        let verifier = RS256.verifier_from_jwk(&jwk)?;
+        assert!(jwt::decode_with_verifier(&jwt_string, &verifier).is_err());
+
+        let private_jwk = Jwk::from_bytes(&load_file("jwk/RSA_private.jwk")?)?;
+        assert!(RS1.signer_from_jwk(&private_jwk).is_err());
+        assert!(RS1
+            .signer_from_der(&load_file("der/RSA_2048bit_pkcs8_private.der")?)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_external_jwt_rs1_unsupported() -> Result<()> {
+        // RS1 is never selected by name, even with the legacy-algorithms feature.
+        let jwk = Jwk::from_bytes(&load_file("jwk/RSA_public.jwk")?)?;
+        assert!(matches!(
+            jws::verifier_from_jwk_with_algorithm(&jwk, "RS1"),
+            Err(crate::JoseError::UnsupportedSignatureAlgorithm(_))
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn test_external_jwt_verify_with_rsapss() -> Result<()> {
         let jwk = Jwk::from_bytes(&load_file("jwk/RSA_public.jwk")?)?;
@@ -0,0 +1,49 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Represents a source of the current time that is used for time related
+/// claims (exp, nbf, iat) validation.
+///
+/// A fixed or manually advanced clock makes the validation deterministic in tests.
+pub trait Clock: Debug + Send + Sync {
+    /// Return the current time.
+    fn now(&self) -> SystemTime;
+}
+
+/// Represents the default clock that returns the system time.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A custom clock that is compared by identity. None is the system clock.
+#[derive(Debug, Clone)]
+pub(crate) struct ClockRef(pub(crate) Option<Arc<dyn Clock>>);
+
+impl ClockRef {
+    pub(crate) fn get(&self) -> &dyn Clock {
+        match &self.0 {
+            Some(val) => val.as_ref(),
+            None => &SystemClock,
+        }
+    }
+}
+
+impl PartialEq for ClockRef {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (Some(val1), Some(val2)) => {
+                Arc::as_ptr(val1) as *const () == Arc::as_ptr(val2) as *const ()
+            }
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ClockRef {}
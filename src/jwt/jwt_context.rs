@@ -1,15 +1,23 @@
-use anyhow::bail;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, bail};
 
 use crate::jwe::{JweContext, JweDecrypter, JweEncrypter, JweHeader};
 use crate::jwk::{Jwk, JwkSet};
-use crate::jws::{JwsContext, JwsHeader, JwsSigner, JwsVerifier};
-use crate::jwt::{self, JwtPayload};
-use crate::{JoseError, JoseHeader, Map, Value};
+use crate::jws::{self, JwsContext, JwsHeader, JwsSigner, JwsVerifier};
+use crate::jwt::clock::ClockRef;
+use crate::jwt::{self, Clock, JwtPayload, JwtPayloadValidator};
+use crate::util;
+use crate::{JoseError, JoseHeader, Value};
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct JwtContext {
     jws_context: JwsContext,
     jwe_context: JweContext,
+    acceptable_token_types: BTreeSet<String>,
+    clock: ClockRef,
 }
 
 impl JwtContext {
@@ -17,7 +25,86 @@ impl JwtContext {
         Self {
             jws_context: JwsContext::new(),
             jwe_context: JweContext::new(),
+            acceptable_token_types: BTreeSet::new(),
+            clock: ClockRef(None),
+        }
+    }
+
+    /// Set a clock that returns the current time for DPoP proofs.
+    ///
+    /// The default clock is the system clock.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - a clock
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = ClockRef(Some(Arc::new(clock)));
+    }
+
+    /// Return the clock for DPoP proofs.
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.get()
+    }
+
+    /// Test a typ header claim value is acceptable when decoding.
+    ///
+    /// Any value (including none) is acceptable if no token type is added.
+    /// The value is compared case-insensitively, and the "application/" prefix is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_type` - a typ header claim value
+    pub fn is_acceptable_token_type(&self, token_type: Option<&str>) -> bool {
+        if self.acceptable_token_types.is_empty() {
+            return true;
+        }
+
+        match token_type {
+            Some(val) => self
+                .acceptable_token_types
+                .contains(&Self::normalize_token_type(val)),
+            None => false,
+        }
+    }
+
+    /// Add a acceptable typ header claim value.
+    ///
+    /// Once a value is added, a JWT whose typ header claim is missing or not added is rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_type` - a acceptable typ header claim value (e.g. "JWT", "at+jwt")
+    pub fn add_acceptable_token_type(&mut self, token_type: &str) {
+        self.acceptable_token_types
+            .insert(Self::normalize_token_type(token_type));
+    }
+
+    /// Remove a acceptable typ header claim value.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_type` - a acceptable typ header claim value
+    pub fn remove_acceptable_token_type(&mut self, token_type: &str) {
+        self.acceptable_token_types
+            .remove(&Self::normalize_token_type(token_type));
+    }
+
+    fn normalize_token_type(token_type: &str) -> String {
+        let token_type = token_type.to_ascii_lowercase();
+        match token_type.strip_prefix("application/") {
+            Some(val) => val.to_string(),
+            None => token_type,
+        }
+    }
+
+    fn check_token_type(&self, token_type: Option<&str>) -> anyhow::Result<()> {
+        if !self.is_acceptable_token_type(token_type) {
+            match token_type {
+                Some(val) => bail!("The typ header claim is not acceptable: {}", val),
+                None => bail!("The typ header claim is required."),
+            }
         }
+        Ok(())
     }
 
     /// Test a critical header claim name is acceptable.
@@ -49,6 +136,55 @@ impl JwtContext {
         self.jwe_context.remove_acceptable_critical(name);
     }
 
+    /// Return the maximum length of a JWT for decoding.
+    /// The default value is 8 MiB.
+    pub fn max_input_len(&self) -> Option<usize> {
+        self.jws_context.max_input_len()
+    }
+
+    /// Set the maximum length of a JWT for decoding.
+    /// If it is none, the length is not limited.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a maximum length in bytes
+    pub fn set_max_input_len(&mut self, value: Option<usize>) {
+        self.jws_context.set_max_input_len(value);
+        self.jwe_context.set_max_input_len(value);
+    }
+
+    /// Return the maximum length of a decoded payload (or ciphertext of JWE) for decoding.
+    /// The default value is 8 MiB.
+    pub fn max_payload_len(&self) -> Option<usize> {
+        self.jws_context.max_payload_len()
+    }
+
+    /// Set the maximum length of a decoded payload (or ciphertext of JWE) for decoding.
+    /// If it is none, the length is not limited.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a maximum length in bytes
+    pub fn set_max_payload_len(&mut self, value: Option<usize>) {
+        self.jws_context.set_max_payload_len(value);
+        self.jwe_context.set_max_payload_len(value);
+    }
+
+    /// Test the public key of a signer is embedded as the jwk header claim.
+    pub fn is_public_jwk_embedding(&self) -> bool {
+        self.jws_context.is_public_jwk_embedding()
+    }
+
+    /// Set the public key of a signer is embedded as the jwk header claim
+    /// when encoding a JWT with a signer.
+    ///
+    /// # Arguments
+    ///
+    /// * `embed` - true if the public key is embedded
+    pub fn set_public_jwk_embedding(&mut self, embed: bool) {
+        self.jws_context.set_public_jwk_embedding(embed);
+    }
+
     /// Return the string repsentation of the JWT with a "none" algorithm.
     ///
     /// # Arguments
@@ -64,6 +200,7 @@ impl JwtContext {
     }
 
     /// Return the string repsentation of the JWT with the siginig algorithm.
+    /// The typ header claim is set to "JWT" if it is absent.
     ///
     /// # Arguments
     ///
@@ -83,6 +220,17 @@ impl JwtContext {
                 }
             }
 
+            let typed_header;
+            let header = match header.token_type() {
+                Some(_) => header,
+                None => {
+                    let mut header = header.clone();
+                    header.set_token_type("JWT");
+                    typed_header = header;
+                    &typed_header
+                }
+            };
+
             let payload_bytes = serde_json::to_vec(payload.claims_set()).unwrap();
             let jwt = self
                 .jws_context
@@ -123,17 +271,24 @@ impl JwtContext {
     pub fn decode_header(&self, input: impl AsRef<[u8]>) -> Result<Box<dyn JoseHeader>, JoseError> {
         (|| -> anyhow::Result<Box<dyn JoseHeader>> {
             let input = input.as_ref();
+            match self.max_input_len() {
+                Some(max) if input.len() > max => {
+                    bail!("The input is too long: {} > {}", input.len(), max)
+                }
+                _ => {}
+            }
+
             let parts: Vec<&[u8]> = input.split(|b| *b == '.' as u8).collect();
             if parts.len() == 3 {
                 // JWS
-                let header = base64::decode_config(parts[0], base64::URL_SAFE_NO_PAD)?;
-                let header: Map<String, Value> = serde_json::from_slice(&header)?;
+                let header = util::decode_base64url_segment(parts[0])?;
+                let header = util::parse_json_object(&header)?;
                 let header = JwsHeader::from_map(header)?;
                 Ok(Box::new(header))
             } else if parts.len() == 5 {
                 // JWE
-                let header = base64::decode_config(parts[0], base64::URL_SAFE_NO_PAD)?;
-                let header: Map<String, Value> = serde_json::from_slice(&header)?;
+                let header = util::decode_base64url_segment(parts[0])?;
+                let header = util::parse_json_object(&header)?;
                 let header = JweHeader::from_map(header)?;
                 Ok(Box::new(header))
             } else {
@@ -146,6 +301,27 @@ impl JwtContext {
         })
     }
 
+    /// Return the JWS header of a signed JWT without verifying the signature.
+    ///
+    /// The returned header is untrusted data. It may only be used to select a key
+    /// or a verifier (e.g. by the alg and kid header claims), and the JWT must
+    /// be verified before any claim is relied on. The payload is not decoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - a JWT string representation.
+    pub fn decode_header_unverified(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<JwsHeader, JoseError> {
+        self.jws_context
+            .decode_header_unverified(input)
+            .map_err(|err| match err {
+                JoseError::InvalidJwsFormat(err) => JoseError::InvalidJwtFormat(err),
+                err => err,
+            })
+    }
+
     /// Return the JWT object decoded with the "none" algorithm.
     ///
     /// # Arguments
@@ -172,6 +348,37 @@ impl JwtContext {
         self.decode_with_verifier_selector(input, |_header| Ok(Some(verifier)))
     }
 
+    /// Return the JWT object decoded from the value of an HTTP Authorization header.
+    ///
+    /// A case-insensitive "Bearer" scheme and surrounding whitespace are stripped
+    /// before decoding. A bare token is accepted, but any other scheme is rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a value of an Authorization header.
+    /// * `verifier` - a verifier of the signing algorithm.
+    pub fn decode_from_authorization_header(
+        &self,
+        value: &str,
+        verifier: &dyn JwsVerifier,
+    ) -> Result<(JwtPayload, JwsHeader), JoseError> {
+        let value = value.trim();
+        let token = match value.find(|c: char| c.is_ascii_whitespace()) {
+            Some(index) => {
+                let (scheme, token) = value.split_at(index);
+                if !scheme.eq_ignore_ascii_case("Bearer") {
+                    return Err(JoseError::InvalidJwtFormat(anyhow!(
+                        "The authorization scheme must be Bearer: {}",
+                        scheme
+                    )));
+                }
+                token.trim_start()
+            }
+            None => value,
+        };
+        self.decode_with_verifier(token, verifier)
+    }
+
     /// Return the JWT object decoded with a selected verifying algorithm.
     ///
     /// # Arguments
@@ -200,6 +407,8 @@ impl JwtContext {
                                 bail!("JWT is not supported b64 header claim.");
                             }
 
+                            self.check_token_type(header.token_type())?;
+
                             Ok(Some(verifier))
                         })()
                         .map_err(|err| {
@@ -210,7 +419,7 @@ impl JwtContext {
                         })
                     })?;
 
-            let payload: Map<String, Value> = serde_json::from_slice(&payload)?;
+            let payload = util::parse_json_object(&payload)?;
             let payload = JwtPayload::from_map(payload)?;
 
             Ok((payload, header))
@@ -252,6 +461,128 @@ impl JwtContext {
         })
     }
 
+    /// Return the JWT object decoded by using a JWK set with the JWK that verified it.
+    ///
+    /// Every JWK that has the kid header claim of the input is tried in order,
+    /// so a key ID shared by keys during a rotation is supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - a JWT string representation.
+    /// * `jwk_set` - a JWK set.
+    /// * `selector` - a function for selecting the verifying algorithm.
+    pub fn decode_with_verifier_in_jwk_set_matched<'a, 'b, F>(
+        &self,
+        input: impl AsRef<[u8]>,
+        jwk_set: &'b JwkSet,
+        selector: F,
+    ) -> Result<(JwtPayload, JwsHeader, &'b Jwk), JoseError>
+    where
+        F: Fn(&Jwk) -> Result<Option<&'a dyn JwsVerifier>, JoseError>,
+    {
+        (|| -> anyhow::Result<(JwtPayload, JwsHeader, &'b Jwk)> {
+            let input = input.as_ref();
+            let header = self.decode_header(input)?;
+            let key_id = match header.key_id() {
+                Some(val) => val,
+                None => bail!("The JWT kid header claim is required."),
+            };
+
+            let mut last_error = None;
+            for jwk in jwk_set.get(key_id) {
+                let verifier = match selector(jwk)? {
+                    Some(val) => val,
+                    None => continue,
+                };
+
+                match self.decode_with_verifier(input, verifier) {
+                    Ok((payload, header)) => return Ok((payload, header, jwk)),
+                    Err(err) => last_error = Some(err),
+                }
+            }
+
+            match last_error {
+                Some(err) => Err(err.into()),
+                None => bail!("A verifier is not found."),
+            }
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwtFormat(err),
+        })
+    }
+
+    /// Return the JWT object decoded by using a JWK set that is fetched
+    /// from the JWK set URL header claim (jku).
+    ///
+    /// This library doesn't perform HTTP requests. The fetcher is called with the jku
+    /// header claim value and must return the JWK set of the URL. The fetcher is
+    /// responsible for allowing only trusted URLs, because the header is not verified
+    /// until a key in the returned set verifies the signature.
+    ///
+    /// If the input has a kid header claim, the JWKs that have the same key ID are tried.
+    /// Otherwise every JWK in the set is tried. JWKs for encryption (use=enc) and JWKs
+    /// with a different alg parameter are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - a JWT string representation.
+    /// * `fetcher` - a function that returns the JWK set of a URL.
+    pub fn decode_with_jwk_set_url<F>(
+        &self,
+        input: impl AsRef<[u8]>,
+        fetcher: F,
+    ) -> Result<(JwtPayload, JwsHeader), JoseError>
+    where
+        F: Fn(&str) -> Result<JwkSet, JoseError>,
+    {
+        (|| -> anyhow::Result<(JwtPayload, JwsHeader)> {
+            let input = input.as_ref();
+            let header = self.decode_header_unverified(input)?;
+            let jwk_set_url = match header.jwk_set_url() {
+                Some(val) => val,
+                None => bail!("The JWT jku header claim is required."),
+            };
+            let algorithm = match header.algorithm() {
+                Some(val) => val,
+                None => bail!("The JWT alg header claim is required."),
+            };
+
+            let jwk_set = fetcher(jwk_set_url)?;
+            let candidates = match header.key_id() {
+                Some(val) => jwk_set.get(val),
+                None => jwk_set.keys(),
+            };
+
+            let mut last_error = None;
+            for jwk in candidates {
+                if jwk.key_use() == Some("enc") {
+                    continue;
+                }
+                match jwk.algorithm() {
+                    Some(val) if val != algorithm => continue,
+                    _ => {}
+                }
+
+                let result = jws::verifier_from_jwk_with_algorithm(jwk, algorithm)
+                    .and_then(|verifier| self.decode_with_verifier(input, &*verifier));
+                match result {
+                    Ok(val) => return Ok(val),
+                    Err(err) => last_error = Some(err),
+                }
+            }
+
+            match last_error {
+                Some(err) => Err(err.into()),
+                None => bail!("A verifier is not found in the JWK set: {}", jwk_set_url),
+            }
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwtFormat(err),
+        })
+    }
+
     /// Return the JWT object decoded by the selected decrypter.
     ///
     /// # Arguments
@@ -289,10 +620,13 @@ impl JwtContext {
                             None => return Ok(None),
                         };
 
+                        self.check_token_type(header.token_type())
+                            .map_err(JoseError::InvalidJwtFormat)?;
+
                         Ok(Some(decrypter))
                     })?;
 
-            let payload: Map<String, Value> = serde_json::from_slice(&payload)?;
+            let payload = util::parse_json_object(&payload)?;
             let payload = JwtPayload::from_map(payload)?;
 
             Ok((payload, header))
@@ -333,4 +667,279 @@ impl JwtContext {
             Ok(None)
         })
     }
+
+    /// Return the string repsentation of the nested JWT that is signed and then encrypted.
+    /// The cty header claim of the JWE is set to "JWT".
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload data.
+    /// * `jws_header` - The JWS heaser claims of the inner JWT.
+    /// * `signer` - a signer object.
+    /// * `jwe_header` - The JWE heaser claims of the outer JWE.
+    /// * `encrypter` - a encrypter object.
+    pub fn encode_nested(
+        &self,
+        payload: &JwtPayload,
+        jws_header: &JwsHeader,
+        signer: &dyn JwsSigner,
+        jwe_header: &JweHeader,
+        encrypter: &dyn JweEncrypter,
+    ) -> Result<String, JoseError> {
+        let jws = self
+            .encode_with_signer(payload, jws_header, signer)
+            .map_err(|err| err.with_context("Failed to sign the inner JWS of the nested JWT."))?;
+
+        let mut jwe_header = jwe_header.clone();
+        jwe_header.set_content_type("JWT");
+        self.jwe_context
+            .serialize_compact(jws.as_bytes(), &jwe_header, encrypter)
+            .map_err(|err| err.with_context("Failed to encrypt the outer JWE of the nested JWT."))
+    }
+
+    /// Return the JWT object decoded from the nested JWT that is signed and then encrypted.
+    ///
+    /// The outer JWE is decrypted and its cty header claim must be "JWT".
+    /// Then the inner JWS is verified. An error has a context of the failed layer.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - a nested JWT string representation.
+    /// * `decrypter` - a decrypter of the outer JWE.
+    /// * `verifier` - a verifier of the inner JWS.
+    pub fn decode_nested(
+        &self,
+        input: impl AsRef<[u8]>,
+        decrypter: &dyn JweDecrypter,
+        verifier: &dyn JwsVerifier,
+    ) -> Result<(JwtPayload, JwsHeader, JweHeader), JoseError> {
+        let (jws, jwe_header) = (|| -> anyhow::Result<(Vec<u8>, JweHeader)> {
+            let (jws, jwe_header) = self.jwe_context.deserialize_compact(input, decrypter)?;
+
+            match jwe_header.content_type() {
+                Some(val) if val.eq_ignore_ascii_case("JWT") => {}
+                Some(val) => bail!("The cty header claim must be JWT: {}", val),
+                None => bail!("The cty header claim is required."),
+            }
+
+            Ok((jws, jwe_header))
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwtFormat(err),
+        })
+        .map_err(|err| err.with_context("Failed to decrypt the outer JWE of the nested JWT."))?;
+
+        let (payload, jws_header) = self
+            .decode_with_verifier(&jws, verifier)
+            .map_err(|err| err.with_context("Failed to verify the inner JWS of the nested JWT."))?;
+
+        Ok((payload, jws_header, jwe_header))
+    }
+
+    /// Return a JWT that is verified by an old key and signed again by a new key.
+    ///
+    /// The payload claims are kept as they are, except that the issued at claim (iat)
+    /// is replaced if a time is specified. The header of the new JWT is the
+    /// specified header, so copy the header claims to keep from the decoded header.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - a JWT string representation.
+    /// * `verifier` - a verifier of the old key.
+    /// * `signer` - a signer of the new key.
+    /// * `header` - The JWS header claims of the new JWT.
+    /// * `issued_at` - a new time for the issued at payload claim (iat).
+    pub fn resign(
+        &self,
+        input: impl AsRef<[u8]>,
+        verifier: &dyn JwsVerifier,
+        signer: &dyn JwsSigner,
+        header: &JwsHeader,
+        issued_at: Option<&SystemTime>,
+    ) -> Result<String, JoseError> {
+        let (mut payload, _) = self.decode_with_verifier(input, verifier)?;
+        if let Some(val) = issued_at {
+            payload.set_issued_at(val)?;
+        }
+        self.encode_with_signer(&payload, header, signer)
+    }
+
+    /// Return a DPoP proof JWT (RFC 9449) for a HTTP request.
+    ///
+    /// The typ header claim is "dpop+jwt" and the jwk header claim is the public key of the JWK.
+    /// The payload has the htm, htu, jti and iat claims.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The HTTP method of the request (e.g. "POST").
+    /// * `url` - The HTTP target URI of the request without query and fragment.
+    /// * `signer` - a signer object of a asymmetric algorithm.
+    /// * `jwk` - The key of the signer. Only the public key parameters are embedded.
+    pub fn encode_dpop_proof(
+        &self,
+        method: &str,
+        url: &str,
+        signer: &dyn JwsSigner,
+        jwk: &Jwk,
+    ) -> Result<String, JoseError> {
+        (|| -> anyhow::Result<String> {
+            let mut public_jwk = jwk.to_public_key()?;
+            public_jwk.set_parameter("kid", None)?;
+
+            let mut header = JwsHeader::new();
+            header.set_token_type("dpop+jwt");
+            header.set_jwk(public_jwk);
+
+            let mut payload = JwtPayload::new();
+            payload.set_jwt_id(util::encode_base64url(util::try_random_bytes(16)?));
+            payload.set_claim("htm", Some(Value::String(method.to_string())))?;
+            payload.set_claim("htu", Some(Value::String(url.to_string())))?;
+            payload.set_issued_at(&self.clock().now())?;
+
+            let jwt = self.encode_with_signer(&payload, &header, signer)?;
+            Ok(jwt)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwtFormat(err),
+        })
+    }
+
+    /// Return the JWT object of a DPoP proof (RFC 9449) verified for a HTTP request.
+    ///
+    /// The signature is verified by the public key of the jwk header claim, and
+    /// the htm and htu claims must match the request. The query and fragment of
+    /// the URL are ignored. The iat claim must be within max_age from the current
+    /// time of the clock.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - a DPoP proof JWT string representation.
+    /// * `method` - The HTTP method of the request.
+    /// * `url` - The HTTP target URI of the request.
+    /// * `max_age` - The maximum difference between the iat claim and now.
+    pub fn decode_dpop_proof(
+        &self,
+        input: impl AsRef<[u8]>,
+        method: &str,
+        url: &str,
+        max_age: Duration,
+    ) -> Result<(JwtPayload, JwsHeader), JoseError> {
+        let input = input.as_ref();
+        let verifier = (|| -> anyhow::Result<Box<dyn JwsVerifier>> {
+            let header = self.decode_header(input)?;
+            match header.claim("typ") {
+                Some(Value::String(val)) if val.eq_ignore_ascii_case("dpop+jwt") => {}
+                Some(Value::String(val)) => bail!("The typ header claim must be dpop+jwt: {}", val),
+                _ => bail!("The typ header claim is required."),
+            }
+            let (_, verifier) = self.embedded_jwk_verifier(input)?;
+            Ok(verifier)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwtFormat(err),
+        })?;
+
+        let (payload, header) = self.decode_with_verifier(input, &*verifier)?;
+
+        let htu = match url.find(&['?', '#'][..]) {
+            Some(pos) => &url[..pos],
+            None => url,
+        };
+        let now = self.clock().now();
+        let min_issued_time = now.checked_sub(max_age).unwrap_or(SystemTime::UNIX_EPOCH);
+        let max_issued_time = match now.checked_add(max_age) {
+            Some(val) => val,
+            None => {
+                return Err(JoseError::InvalidClaim(anyhow!(
+                    "The max age is too large: {} seconds",
+                    max_age.as_secs()
+                )))
+            }
+        };
+
+        let mut validator = JwtPayloadValidator::new();
+        validator.set_base_time(now);
+        validator.set_min_issued_time(min_issued_time);
+        validator.set_max_issued_time(max_issued_time);
+        validator.set_jwt_id_required(true);
+        validator.set_claim("htm", Value::String(method.to_string()));
+        validator.set_claim("htu", Value::String(htu.to_string()));
+        if payload.issued_at().is_none() {
+            return Err(JoseError::InvalidClaim(anyhow::anyhow!(
+                "The iat payload claim is required."
+            )));
+        }
+        validator.validate(&payload)?;
+
+        Ok((payload, header))
+    }
+
+    /// Return the JWT object verified by the public key of the jwk header claim
+    /// that is accepted by a predicate.
+    ///
+    /// A valid signature only proves that the token is signed by the embedded key.
+    /// The predicate must check the key against an expectation that is obtained
+    /// out of band, e.g. a JWK thumbprint bound in a prior handshake. It is called
+    /// after the signature is verified.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - a JWT string representation.
+    /// * `predicate` - a function that returns true if the embedded JWK is expected.
+    pub fn decode_with_embedded_jwk<F>(
+        &self,
+        input: impl AsRef<[u8]>,
+        predicate: F,
+    ) -> Result<(JwtPayload, JwsHeader), JoseError>
+    where
+        F: Fn(&Jwk) -> bool,
+    {
+        let input = input.as_ref();
+        let (jwk, verifier) = self.embedded_jwk_verifier(input)?;
+        let (payload, header) = self.decode_with_verifier(input, &*verifier)?;
+        if !predicate(&jwk) {
+            return Err(JoseError::InvalidSignature(anyhow::anyhow!(
+                "The jwk header claim is not the expected key."
+            )));
+        }
+        Ok((payload, header))
+    }
+
+    fn embedded_jwk_verifier(
+        &self,
+        input: &[u8],
+    ) -> Result<(Jwk, Box<dyn JwsVerifier>), JoseError> {
+        (|| -> anyhow::Result<(Jwk, Box<dyn JwsVerifier>)> {
+            let header = self.decode_header(input)?;
+            let algorithm = match header.claim("alg") {
+                Some(Value::String(val)) => val,
+                _ => bail!("The alg header claim is required."),
+            };
+            if algorithm == "none" || algorithm.starts_with("HS") {
+                bail!(
+                    "The alg header claim must be a asymmetric algorithm: {}",
+                    algorithm
+                );
+            }
+            let jwk = match header.claim("jwk") {
+                Some(Value::Object(val)) => Jwk::from_map(val.clone())?,
+                _ => bail!("The jwk header claim is required."),
+            };
+            if jwk.parameter("d").is_some() {
+                bail!("The jwk header claim must not contain a private key.");
+            }
+
+            let mut verifier_jwk = jwk.clone();
+            verifier_jwk.set_parameter("kid", None)?;
+            let verifier = jws::verifier_from_jwk_with_algorithm(&verifier_jwk, algorithm)?;
+            Ok((jwk, verifier))
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwtFormat(err),
+        })
+    }
 }
@@ -4,6 +4,8 @@ use std::time::{Duration, SystemTime};
 
 use crate::{JoseError, Map, Number, Value};
 use anyhow::bail;
+use serde::de::{self, DeserializeOwned};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Debug, Eq, PartialEq, Clone, Default)]
 pub struct JwtPayload {
@@ -112,26 +114,20 @@ impl JwtPayload {
     }
 
     /// Set a system time for expires at payload claim (exp).
+    /// The time must not be before the Unix epoch.
     ///
     /// # Arguments
     ///
     /// * `value` - A expiration time on or after which the JWT must not be accepted for processing.
-    pub fn set_expires_at(&mut self, value: &SystemTime) {
-        let key = "exp".to_string();
-        let val = Number::from(
-            value
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        );
-        self.claims.insert(key.clone(), Value::Number(val));
+    pub fn set_expires_at(&mut self, value: &SystemTime) -> Result<(), JoseError> {
+        self.set_time_claim("exp", value)
     }
 
     /// Return the system time for expires at payload claim (exp).
     pub fn expires_at(&self) -> Option<SystemTime> {
         match self.claims.get("exp") {
             Some(Value::Number(val)) => match val.as_u64() {
-                Some(val) => Some(SystemTime::UNIX_EPOCH + Duration::from_secs(val)),
+                Some(val) => SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(val)),
                 None => None,
             },
             _ => None,
@@ -139,26 +135,20 @@ impl JwtPayload {
     }
 
     /// Set a system time for not before payload claim (nbf).
+    /// The time must not be before the Unix epoch.
     ///
     /// # Arguments
     ///
     /// * `value` - A time before which the JWT must not be accepted for processing.
-    pub fn set_not_before(&mut self, value: &SystemTime) {
-        let key = "nbf".to_string();
-        let val = Number::from(
-            value
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        );
-        self.claims.insert(key.clone(), Value::Number(val));
+    pub fn set_not_before(&mut self, value: &SystemTime) -> Result<(), JoseError> {
+        self.set_time_claim("nbf", value)
     }
 
     /// Return the system time for not before payload claim (nbf).
     pub fn not_before(&self) -> Option<SystemTime> {
         match self.claims.get("nbf") {
             Some(Value::Number(val)) => match val.as_u64() {
-                Some(val) => Some(SystemTime::UNIX_EPOCH + Duration::from_secs(val)),
+                Some(val) => SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(val)),
                 None => None,
             },
             _ => None,
@@ -166,26 +156,20 @@ impl JwtPayload {
     }
 
     /// Set a time for issued at payload claim (iat).
+    /// The time must not be before the Unix epoch.
     ///
     /// # Arguments
     ///
     /// * `value` - a time at which the JWT was issued.
-    pub fn set_issued_at(&mut self, value: &SystemTime) {
-        let key = "iat".to_string();
-        let val = Number::from(
-            value
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        );
-        self.claims.insert(key.clone(), Value::Number(val));
+    pub fn set_issued_at(&mut self, value: &SystemTime) -> Result<(), JoseError> {
+        self.set_time_claim("iat", value)
     }
 
     /// Return the time for a issued at payload claim (iat).
     pub fn issued_at(&self) -> Option<SystemTime> {
         match self.claims.get("iat") {
             Some(Value::Number(val)) => match val.as_u64() {
-                Some(val) => Some(SystemTime::UNIX_EPOCH + Duration::from_secs(val)),
+                Some(val) => SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(val)),
                 None => None,
             },
             _ => None,
@@ -242,6 +226,82 @@ impl JwtPayload {
         self.claims.get(key)
     }
 
+    /// Return a string value for payload claim of a specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - a key name of payload claim
+    pub fn claim_as_str(&self, key: &str) -> Result<Option<&str>, JoseError> {
+        (|| -> anyhow::Result<Option<&str>> {
+            match self.claims.get(key) {
+                Some(Value::String(val)) => Ok(Some(val)),
+                Some(_) => bail!("The JWT {} payload claim must be a string.", key),
+                None => Ok(None),
+            }
+        })()
+        .map_err(JoseError::InvalidClaim)
+    }
+
+    /// Return a integer value for payload claim of a specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - a key name of payload claim
+    pub fn claim_as_i64(&self, key: &str) -> Result<Option<i64>, JoseError> {
+        (|| -> anyhow::Result<Option<i64>> {
+            match self.claims.get(key) {
+                Some(Value::Number(val)) => match val.as_i64() {
+                    Some(val) => Ok(Some(val)),
+                    None => bail!(
+                        "The JWT {} payload claim must be a integer within 64bit.",
+                        key
+                    ),
+                },
+                Some(_) => bail!("The JWT {} payload claim must be a integer.", key),
+                None => Ok(None),
+            }
+        })()
+        .map_err(JoseError::InvalidClaim)
+    }
+
+    /// Return a boolean value for payload claim of a specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - a key name of payload claim
+    pub fn claim_as_bool(&self, key: &str) -> Result<Option<bool>, JoseError> {
+        (|| -> anyhow::Result<Option<bool>> {
+            match self.claims.get(key) {
+                Some(Value::Bool(val)) => Ok(Some(*val)),
+                Some(_) => bail!("The JWT {} payload claim must be a boolean.", key),
+                None => Ok(None),
+            }
+        })()
+        .map_err(JoseError::InvalidClaim)
+    }
+
+    /// Return a value deserialized from payload claim of a specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - a key name of payload claim
+    pub fn claim_as<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, JoseError> {
+        (|| -> anyhow::Result<Option<T>> {
+            match self.claims.get(key) {
+                Some(val) => match serde_json::from_value(val.clone()) {
+                    Ok(val) => Ok(Some(val)),
+                    Err(err) => bail!(
+                        "The JWT {} payload claim cannot be deserialized: {}",
+                        key,
+                        err
+                    ),
+                },
+                None => Ok(None),
+            }
+        })()
+        .map_err(JoseError::InvalidClaim)
+    }
+
     /// Return values for payload claims set
     pub fn claims_set(&self) -> &Map<String, Value> {
         &self.claims
@@ -286,6 +346,21 @@ impl JwtPayload {
         })()
         .map_err(|err| JoseError::InvalidJwtFormat(err))
     }
+
+    fn set_time_claim(&mut self, key: &str, value: &SystemTime) -> Result<(), JoseError> {
+        let val = match value.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(val) => val.as_secs(),
+            Err(_) => {
+                return Err(JoseError::InvalidClaim(anyhow::anyhow!(
+                    "The {} payload claim must not be before the Unix epoch.",
+                    key
+                )))
+            }
+        };
+        self.claims
+            .insert(key.to_string(), Value::Number(Number::from(val)));
+        Ok(())
+    }
 }
 
 impl AsRef<Map<String, Value>> for JwtPayload {
@@ -307,24 +382,61 @@ impl Display for JwtPayload {
     }
 }
 
+impl Serialize for JwtPayload {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.claims.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for JwtPayload {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let map = Map::deserialize(deserializer)?;
+        Self::from_map(map).map_err(de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::time::SystemTime;
+    use std::time::{Duration, SystemTime};
 
     use anyhow::Result;
+    use serde::Deserialize;
     use serde_json::json;
 
     use super::JwtPayload;
 
+    #[test]
+    fn test_payload_serde() -> Result<()> {
+        let json = r#"{"iss":"joe","exp":1300819380,"aud":["a","b"],"admin":true}"#;
+        let payload: JwtPayload = serde_json::from_str(json)?;
+        assert_eq!(payload.issuer(), Some("joe"));
+
+        let cloned = payload.clone();
+        assert_eq!(cloned, payload);
+        assert_eq!(serde_json::to_string(&cloned)?, json);
+        assert_eq!(cloned.to_string(), json);
+
+        assert!(serde_json::from_str::<JwtPayload>(r#"{"exp":"tomorrow"}"#).is_err());
+        assert!(serde_json::from_str::<JwtPayload>(r#"["iss"]"#).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_new_payload() -> Result<()> {
         let mut payload = JwtPayload::new();
         payload.set_issuer("iss");
         payload.set_subject("sub");
         payload.set_audience(vec!["aud0", "aud1"]);
-        payload.set_expires_at(&SystemTime::UNIX_EPOCH);
-        payload.set_not_before(&SystemTime::UNIX_EPOCH);
-        payload.set_issued_at(&SystemTime::UNIX_EPOCH);
+        payload.set_expires_at(&SystemTime::UNIX_EPOCH)?;
+        payload.set_not_before(&SystemTime::UNIX_EPOCH)?;
+        payload.set_issued_at(&SystemTime::UNIX_EPOCH)?;
         payload.set_jwt_id("jti");
         payload.set_claim("payload_claim", Some(json!("payload_claim")))?;
 
@@ -343,4 +455,64 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_time_claims() -> Result<()> {
+        let time = SystemTime::UNIX_EPOCH + Duration::new(1_600_000_000, 999_999_999);
+        let expected = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+
+        let mut payload = JwtPayload::new();
+        payload.set_expires_at(&time)?;
+        payload.set_not_before(&time)?;
+        payload.set_issued_at(&time)?;
+        assert_eq!(payload.expires_at(), Some(expected));
+        assert_eq!(payload.not_before(), Some(expected));
+        assert_eq!(payload.issued_at(), Some(expected));
+        assert_eq!(payload.claim("exp"), Some(&json!(1_600_000_000)));
+
+        let before_epoch = SystemTime::UNIX_EPOCH - Duration::from_secs(1);
+        assert!(payload.set_expires_at(&before_epoch).is_err());
+        assert!(payload.set_not_before(&before_epoch).is_err());
+        assert!(payload.set_issued_at(&before_epoch).is_err());
+        assert_eq!(payload.expires_at(), Some(expected));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_typed_claims() -> Result<()> {
+        #[derive(Debug, Eq, PartialEq, Deserialize)]
+        struct Address {
+            street: String,
+            zip: u32,
+        }
+
+        let mut payload = JwtPayload::new();
+        payload.set_claim("name", Some(json!("alice")))?;
+        payload.set_claim("age", Some(json!(42)))?;
+        payload.set_claim("admin", Some(json!(true)))?;
+        payload.set_claim(
+            "address",
+            Some(json!({ "street": "Main St", "zip": 12345 })),
+        )?;
+
+        assert_eq!(payload.claim_as_str("name")?, Some("alice"));
+        assert_eq!(payload.claim_as_i64("age")?, Some(42));
+        assert_eq!(payload.claim_as_bool("admin")?, Some(true));
+        assert_eq!(
+            payload.claim_as::<Address>("address")?,
+            Some(Address {
+                street: "Main St".to_string(),
+                zip: 12345,
+            })
+        );
+        assert_eq!(payload.claim_as_str("missing")?, None);
+
+        assert!(payload.claim_as_str("age").is_err());
+        assert!(payload.claim_as_i64("name").is_err());
+        assert!(payload.claim_as_bool("name").is_err());
+        assert!(payload.claim_as::<Address>("name").is_err());
+
+        Ok(())
+    }
 }
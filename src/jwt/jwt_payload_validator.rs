@@ -1,19 +1,27 @@
-use std::convert::Into;
-use std::time::SystemTime;
+use std::convert::{Into, TryFrom};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 use anyhow::bail;
 use chrono::{DateTime, Utc};
 
-use crate::jwt::JwtPayload;
+use crate::jwt::clock::ClockRef;
+use crate::jwt::{Clock, JwtPayload};
 use crate::{JoseError, Map, Value};
 
 /// Represents JWT payload validator.
 #[derive(Debug, Eq, PartialEq)]
 pub struct JwtPayloadValidator {
+    clock: ClockRef,
     base_time: Option<SystemTime>,
     min_issued_time: Option<SystemTime>,
     max_issued_time: Option<SystemTime>,
+    max_age: Option<Duration>,
+    leeway: Duration,
+    issuers: Option<Vec<String>>,
+    subject: Option<String>,
     audience: Option<String>,
+    jwt_id_required: bool,
     claims: Map<String, Value>,
 }
 
@@ -21,14 +29,37 @@ impl JwtPayloadValidator {
     /// Return a new JwtPayloadValidator.
     pub fn new() -> Self {
         Self {
+            clock: ClockRef(None),
             base_time: None,
             min_issued_time: None,
             max_issued_time: None,
+            max_age: None,
+            leeway: Duration::from_secs(0),
+            issuers: None,
+            subject: None,
             audience: None,
+            jwt_id_required: false,
             claims: Map::new(),
         }
     }
 
+    /// Set a clock that returns the current time for time related claims (exp, nbf, iat)
+    /// validation. A base time, if set, takes precedence over the clock.
+    ///
+    /// The default clock is the system clock.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - a clock
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = ClockRef(Some(Arc::new(clock)));
+    }
+
+    /// Return the clock for time related claims (exp, nbf, iat) validation.
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.get()
+    }
+
     /// Set a base time for time related claims (exp, nbf) validation.
     ///
     /// # Arguments
@@ -71,6 +102,41 @@ impl JwtPayloadValidator {
         self.max_issued_time.as_ref()
     }
 
+    /// Set a maximum age of the JWT for issued at payload claim (iat) validation.
+    ///
+    /// The JWT is rejected when the issued time is older than the max age relative
+    /// to the current time, or when the issued time is missing. This is independent
+    /// of the expiration time (exp) validation.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_age` - a maximum age of the JWT.
+    pub fn set_max_age(&mut self, max_age: Duration) {
+        self.max_age = Some(max_age);
+    }
+
+    /// Return the maximum age of the JWT for issued at payload claim (iat).
+    pub fn max_age(&self) -> Option<&Duration> {
+        self.max_age.as_ref()
+    }
+
+    /// Set a leeway for time related claims (exp, nbf, iat) validation to allow
+    /// for clock skew between the issuer and the validator.
+    ///
+    /// The default leeway is zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `leeway` - a leeway
+    pub fn set_leeway(&mut self, leeway: Duration) {
+        self.leeway = leeway;
+    }
+
+    /// Return the leeway for time related claims (exp, nbf, iat) validation.
+    pub fn leeway(&self) -> &Duration {
+        &self.leeway
+    }
+
     /// Set a value for issuer payload claim (iss) validation.
     ///
     /// # Arguments
@@ -78,17 +144,38 @@ impl JwtPayloadValidator {
     /// * `value` - a issuer
     pub fn set_issuer(&mut self, value: impl Into<String>) {
         let value: String = value.into();
-        self.claims.insert("iss".to_string(), Value::String(value));
+        self.issuers = Some(vec![value]);
     }
 
     /// Return the value for issuer payload claim (iss) validation.
+    ///
+    /// If multiple acceptable issuers are set, this method returns None.
     pub fn issuer(&self) -> Option<&str> {
-        match self.claims.get("iss") {
-            Some(Value::String(val)) => Some(val),
+        match self.issuers {
+            Some(ref vals) if vals.len() == 1 => Some(&vals[0]),
             _ => None,
         }
     }
 
+    /// Set values for acceptable issuers of payload claim (iss) validation.
+    ///
+    /// The validation succeeds if the issuer is one of the specified values.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - a list of acceptable issuers
+    pub fn set_issuers(&mut self, values: Vec<impl Into<String>>) {
+        let values: Vec<String> = values.into_iter().map(|val| val.into()).collect();
+        self.issuers = Some(values);
+    }
+
+    /// Return values for acceptable issuers of payload claim (iss) validation.
+    pub fn issuers(&self) -> Option<Vec<&str>> {
+        self.issuers
+            .as_ref()
+            .map(|vals| vals.iter().map(|val| val.as_str()).collect())
+    }
+
     /// Set a value for subject payload claim (sub) validation.
     ///
     /// # Arguments
@@ -96,15 +183,12 @@ impl JwtPayloadValidator {
     /// * `value` - a subject
     pub fn set_subject(&mut self, value: impl Into<String>) {
         let value: String = value.into();
-        self.claims.insert("sub".to_string(), Value::String(value));
+        self.subject = Some(value);
     }
 
     /// Return the value for subject payload claim (sub) validation.
     pub fn subject(&self) -> Option<&str> {
-        match self.claims.get("sub") {
-            Some(Value::String(val)) => Some(val),
-            _ => None,
-        }
+        self.subject.as_deref()
     }
 
     /// Set a value for audience payload claim (aud) validation.
@@ -143,6 +227,20 @@ impl JwtPayloadValidator {
         }
     }
 
+    /// Set whether JWT ID payload claim (jti) is required.
+    ///
+    /// # Arguments
+    ///
+    /// * `required` - true if a JWT ID must be present
+    pub fn set_jwt_id_required(&mut self, required: bool) {
+        self.jwt_id_required = required;
+    }
+
+    /// Return whether JWT ID payload claim (jti) is required.
+    pub fn is_jwt_id_required(&self) -> bool {
+        self.jwt_id_required
+    }
+
     /// Set a value for payload claim of a specified key.
     ///
     /// # Arguments
@@ -168,43 +266,106 @@ impl JwtPayloadValidator {
     ///
     /// * `payload` - a decoded JWT payload.
     pub fn validate(&self, payload: &JwtPayload) -> Result<(), JoseError> {
+        self.validate_with_jwt_id_checker(payload, |_jwt_id| true)
+    }
+
+    /// Validate a decoded JWT payload and check the JWT ID payload claim (jti) by a callback.
+    ///
+    /// The checker is called with the JWT ID only when it is present and every other
+    /// validation has passed. It should record the JWT ID and return false if the JWT ID
+    /// has already been seen.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - a decoded JWT payload.
+    /// * `checker` - a function for checking and recording the JWT ID.
+    pub fn validate_with_jwt_id_checker<F>(
+        &self,
+        payload: &JwtPayload,
+        mut checker: F,
+    ) -> Result<(), JoseError>
+    where
+        F: FnMut(&str) -> bool,
+    {
         (|| -> anyhow::Result<()> {
-            let now = SystemTime::now();
+            let now = self.clock().now();
             let current_time = self.base_time().unwrap_or(&now);
             let min_issued_time = self.min_issued_time().unwrap_or(&SystemTime::UNIX_EPOCH);
             let max_issued_time = self.max_issued_time().unwrap_or(&now);
 
+            // A time that overflows when the leeway is added is in the far future.
+            let latest_time = current_time.checked_add(self.leeway);
             if let Some(not_before) = payload.not_before() {
-                if &not_before > current_time {
-                    bail!(
-                        "The token is not yet valid: {}",
-                        DateTime::<Utc>::from(not_before)
-                    );
+                if matches!(latest_time, Some(val) if not_before > val) {
+                    bail!("The token is not yet valid: {}", to_display(&not_before));
                 }
+            } else if let Some(val) = out_of_range_time(payload, "nbf") {
+                bail!(
+                    "The token is not yet valid: {} seconds since the epoch",
+                    val
+                );
             }
 
             if let Some(expires_at) = payload.expires_at() {
-                if &expires_at <= current_time {
-                    bail!(
-                        "The token has expired: {}",
-                        DateTime::<Utc>::from(expires_at)
-                    );
+                let deadline = expires_at.checked_add(self.leeway);
+                if matches!(deadline, Some(val) if val <= *current_time) {
+                    bail!("The token has expired: {}", to_display(&expires_at));
                 }
             }
 
             if let Some(issued_at) = payload.issued_at() {
-                if &issued_at < min_issued_time {
-                    bail!(
-                        "The issued time is too old: {}",
-                        DateTime::<Utc>::from(issued_at)
-                    );
+                let earliest_time = issued_at.checked_add(self.leeway);
+                if matches!(earliest_time, Some(val) if val < *min_issued_time) {
+                    bail!("The issued time is too old: {}", to_display(&issued_at));
                 }
 
-                if &issued_at > max_issued_time {
-                    bail!(
-                        "The issued time is too new: {}",
-                        DateTime::<Utc>::from(issued_at)
-                    );
+                let latest_issued_time = max_issued_time.checked_add(self.leeway);
+                if matches!(latest_issued_time, Some(val) if issued_at > val) {
+                    bail!("The issued time is too new: {}", to_display(&issued_at));
+                }
+            } else if let Some(val) = out_of_range_time(payload, "iat") {
+                bail!(
+                    "The issued time is too new: {} seconds since the epoch",
+                    val
+                );
+            }
+
+            if let Some(max_age) = self.max_age {
+                match payload.issued_at() {
+                    Some(issued_at) => {
+                        let deadline = issued_at
+                            .checked_add(max_age)
+                            .and_then(|val| val.checked_add(self.leeway));
+                        if matches!(deadline, Some(val) if val < *current_time) {
+                            bail!(
+                                "The token is older than the max age: {}",
+                                to_display(&issued_at)
+                            );
+                        }
+                    }
+                    None => bail!("The issued time is missing."),
+                }
+            }
+
+            if let Some(issuers) = &self.issuers {
+                match payload.issuer() {
+                    Some(issuer) => {
+                        if !issuers.iter().any(|val| val == issuer) {
+                            bail!("The issuer is invalid: {}", issuer);
+                        }
+                    }
+                    None => bail!("The issuer is missing."),
+                }
+            }
+
+            if let Some(subject) = &self.subject {
+                match payload.subject() {
+                    Some(val) => {
+                        if val != subject {
+                            bail!("The subject is invalid: {}", val);
+                        }
+                    }
+                    None => bail!("The subject is missing."),
                 }
             }
 
@@ -216,6 +377,10 @@ impl JwtPayloadValidator {
                 }
             }
 
+            if self.jwt_id_required && payload.jwt_id().is_none() {
+                bail!("The JWT ID is missing.");
+            }
+
             for (key, value1) in &self.claims {
                 if let Some(value2) = payload.claim(key) {
                     if value1 != value2 {
@@ -226,6 +391,13 @@ impl JwtPayloadValidator {
                 }
             }
 
+            // The checker records the JWT ID, so it runs after every other validation.
+            if let Some(jwt_id) = payload.jwt_id() {
+                if !checker(jwt_id) {
+                    bail!("The JWT ID has already been used: {}", jwt_id);
+                }
+            }
+
             Ok(())
         })()
         .map_err(|err| match err.downcast::<JoseError>() {
@@ -235,14 +407,47 @@ impl JwtPayloadValidator {
     }
 }
 
+/// Return the value of a time claim that is an integer but beyond the range of SystemTime.
+///
+/// The getters of JwtPayload return None for such a claim, but it is in the far future.
+fn out_of_range_time(payload: &JwtPayload, key: &str) -> Option<u64> {
+    match payload.claim(key) {
+        Some(Value::Number(val)) => val.as_u64().filter(|val| {
+            SystemTime::UNIX_EPOCH
+                .checked_add(Duration::from_secs(*val))
+                .is_none()
+        }),
+        _ => None,
+    }
+}
+
+/// Format a time for an error message. Unlike `DateTime::from`, this does not panic
+/// on a time out of the range of chrono.
+fn to_display(time: &SystemTime) -> String {
+    let datetime = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .ok()
+        .and_then(|val| {
+            let secs = i64::try_from(val.as_secs()).ok()?;
+            DateTime::<Utc>::from_timestamp(secs, val.subsec_nanos())
+        });
+    match datetime {
+        Some(val) => val.to_string(),
+        None => format!("{:?}", time),
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
     use std::time::{Duration, SystemTime};
 
     use anyhow::Result;
     use serde_json::json;
 
-    use crate::jwt::{JwtPayload, JwtPayloadValidator};
+    use crate::jwt::{Clock, JwtPayload, JwtPayloadValidator};
 
     #[test]
     fn test_jwt_payload_validate() -> Result<()> {
@@ -250,9 +455,9 @@ mod tests {
         payload.set_issuer("iss");
         payload.set_subject("sub");
         payload.set_audience(vec!["aud0", "aud1"]);
-        payload.set_expires_at(&(SystemTime::UNIX_EPOCH + Duration::from_secs(60)));
-        payload.set_not_before(&(SystemTime::UNIX_EPOCH + Duration::from_secs(10)));
-        payload.set_issued_at(&SystemTime::UNIX_EPOCH);
+        payload.set_expires_at(&(SystemTime::UNIX_EPOCH + Duration::from_secs(60)))?;
+        payload.set_not_before(&(SystemTime::UNIX_EPOCH + Duration::from_secs(10)))?;
+        payload.set_issued_at(&SystemTime::UNIX_EPOCH)?;
         payload.set_jwt_id("jti");
         payload.set_claim("payload_claim", Some(json!("payload_claim")))?;
 
@@ -265,4 +470,226 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_jwt_payload_validate_issuer_and_subject() -> Result<()> {
+        let mut payload = JwtPayload::new();
+        payload.set_issuer("iss0");
+        payload.set_subject("sub");
+
+        let mut validator = JwtPayloadValidator::new();
+        validator.set_issuer("iss0");
+        validator.set_subject("sub");
+        validator.validate(&payload)?;
+
+        validator.set_issuer("iss1");
+        let err = validator.validate(&payload).unwrap_err();
+        assert_eq!(
+            format!("{}", err),
+            "Invalid claim: The issuer is invalid: iss0"
+        );
+
+        validator.set_issuer("iss0");
+        validator.set_subject("other");
+        let err = validator.validate(&payload).unwrap_err();
+        assert_eq!(
+            format!("{}", err),
+            "Invalid claim: The subject is invalid: sub"
+        );
+
+        let mut validator = JwtPayloadValidator::new();
+        validator.set_issuer("iss0");
+        validator.set_subject("sub");
+        let err = validator.validate(&JwtPayload::new()).unwrap_err();
+        assert_eq!(format!("{}", err), "Invalid claim: The issuer is missing.");
+
+        let mut payload = JwtPayload::new();
+        payload.set_issuer("iss0");
+        let err = validator.validate(&payload).unwrap_err();
+        assert_eq!(format!("{}", err), "Invalid claim: The subject is missing.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validate_with_clock() -> Result<()> {
+        #[derive(Debug)]
+        struct FakeClock(Arc<AtomicU64>);
+
+        impl Clock for FakeClock {
+            fn now(&self) -> SystemTime {
+                SystemTime::UNIX_EPOCH + Duration::from_secs(self.0.load(Ordering::SeqCst))
+            }
+        }
+
+        let mut payload = JwtPayload::new();
+        payload.set_not_before(&(SystemTime::UNIX_EPOCH + Duration::from_secs(10)))?;
+        payload.set_expires_at(&(SystemTime::UNIX_EPOCH + Duration::from_secs(60)))?;
+
+        let seconds = Arc::new(AtomicU64::new(0));
+        let mut validator = JwtPayloadValidator::new();
+        validator.set_clock(FakeClock(Arc::clone(&seconds)));
+        assert_eq!(validator.clock().now(), SystemTime::UNIX_EPOCH);
+        assert!(validator.validate(&payload).is_err());
+
+        seconds.store(30, Ordering::SeqCst);
+        validator.validate(&payload)?;
+
+        seconds.store(60, Ordering::SeqCst);
+        let err = validator.validate(&payload).unwrap_err();
+        assert!(format!("{}", err).contains("The token has expired"));
+
+        // A base time takes precedence over the clock.
+        validator.set_base_time(SystemTime::UNIX_EPOCH + Duration::from_secs(30));
+        validator.validate(&payload)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validate_max_age() -> Result<()> {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(3600);
+        let mut payload = JwtPayload::new();
+        payload.set_issued_at(&(now - Duration::from_secs(10 * 60)))?;
+
+        let mut validator = JwtPayloadValidator::new();
+        validator.set_base_time(now);
+        validator.validate(&payload)?;
+
+        validator.set_max_age(Duration::from_secs(5 * 60));
+        let err = validator.validate(&payload).unwrap_err();
+        assert!(format!("{}", err).contains("The token is older than the max age"));
+
+        validator.set_max_age(Duration::from_secs(15 * 60));
+        validator.validate(&payload)?;
+
+        let err = validator.validate(&JwtPayload::new()).unwrap_err();
+        assert_eq!(
+            format!("{}", err),
+            "Invalid claim: The issued time is missing."
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validate_leeway() -> Result<()> {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(3600);
+        let mut payload = JwtPayload::new();
+        payload.set_issued_at(&(now - Duration::from_secs(10 * 60)))?;
+        payload.set_expires_at(&(now - Duration::from_secs(30)))?;
+
+        let mut validator = JwtPayloadValidator::new();
+        validator.set_base_time(now);
+        validator.set_max_age(Duration::from_secs(9 * 60));
+        assert!(validator.validate(&payload).is_err());
+
+        validator.set_leeway(Duration::from_secs(60));
+        assert_eq!(validator.leeway(), &Duration::from_secs(60));
+        validator.validate(&payload)?;
+
+        payload.set_expires_at(&(now - Duration::from_secs(90)))?;
+        let err = validator.validate(&payload).unwrap_err();
+        assert!(format!("{}", err).contains("The token has expired"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validate_far_future_times() -> Result<()> {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(3600);
+        let far_future = SystemTime::UNIX_EPOCH + Duration::from_secs(i64::MAX as u64);
+        let mut payload = JwtPayload::new();
+        payload.set_claim("iat", Some(json!(i64::MAX)))?;
+        payload.set_claim("exp", Some(json!(i64::MAX)))?;
+        assert_eq!(payload.issued_at(), Some(far_future));
+
+        let mut validator = JwtPayloadValidator::new();
+        validator.set_base_time(now);
+        validator.set_max_age(Duration::from_secs(5 * 60));
+        validator.set_leeway(Duration::from_secs(60));
+        let err = validator.validate(&payload).unwrap_err();
+        assert!(format!("{}", err).contains("The issued time is too new"));
+
+        validator.set_max_issued_time(far_future);
+        validator.validate(&payload)?;
+
+        payload.set_claim("nbf", Some(json!(i64::MAX)))?;
+        validator.set_base_time(far_future);
+        validator.validate(&payload)?;
+
+        // A time beyond the range of SystemTime is never valid yet.
+        payload.set_claim("nbf", Some(json!(u64::MAX)))?;
+        assert_eq!(payload.not_before(), None);
+        let err = validator.validate(&payload).unwrap_err();
+        assert!(format!("{}", err).contains("The token is not yet valid"));
+
+        payload.set_claim("nbf", None)?;
+        payload.set_claim("iat", Some(json!(u64::MAX)))?;
+        assert_eq!(payload.issued_at(), None);
+        let err = validator.validate(&payload).unwrap_err();
+        assert!(format!("{}", err).contains("The issued time is too new"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validate_acceptable_issuers() -> Result<()> {
+        let mut validator = JwtPayloadValidator::new();
+        validator.set_issuers(vec!["tenant0", "tenant1"]);
+        assert_eq!(validator.issuer(), None);
+        assert_eq!(validator.issuers(), Some(vec!["tenant0", "tenant1"]));
+
+        let mut payload = JwtPayload::new();
+        payload.set_issuer("tenant1");
+        validator.validate(&payload)?;
+
+        payload.set_issuer("tenant2");
+        assert!(validator.validate(&payload).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validate_jwt_id() -> Result<()> {
+        let mut validator = JwtPayloadValidator::new();
+        validator.set_jwt_id_required(true);
+
+        let err = validator.validate(&JwtPayload::new()).unwrap_err();
+        assert_eq!(format!("{}", err), "Invalid claim: The JWT ID is missing.");
+
+        let mut payload = JwtPayload::new();
+        payload.set_jwt_id("jti0");
+
+        let mut seen = HashSet::new();
+        validator
+            .validate_with_jwt_id_checker(&payload, |jwt_id| seen.insert(jwt_id.to_string()))?;
+
+        let err = validator
+            .validate_with_jwt_id_checker(&payload, |jwt_id| seen.insert(jwt_id.to_string()))
+            .unwrap_err();
+        assert_eq!(
+            format!("{}", err),
+            "Invalid claim: The JWT ID has already been used: jti0"
+        );
+
+        payload.set_jwt_id("jti1");
+        validator
+            .validate_with_jwt_id_checker(&payload, |jwt_id| seen.insert(jwt_id.to_string()))?;
+
+        // A token rejected by another validation does not consume its JWT ID.
+        validator.set_claim("scope", json!("read"));
+        payload.set_jwt_id("jti2");
+        payload.set_claim("scope", Some(json!("write")))?;
+        assert!(validator
+            .validate_with_jwt_id_checker(&payload, |jwt_id| seen.insert(jwt_id.to_string()))
+            .is_err());
+        assert!(!seen.contains("jti2"));
+
+        payload.set_claim("scope", Some(json!("read")))?;
+        validator
+            .validate_with_jwt_id_checker(&payload, |jwt_id| seen.insert(jwt_id.to_string()))?;
+
+        Ok(())
+    }
 }
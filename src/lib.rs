@@ -8,11 +8,17 @@ pub mod jws;
 pub mod jwt;
 pub mod util;
 
+mod algorithm;
+mod compact;
 mod jose_error;
 mod jose_header;
 
-pub use crate::jose_error::JoseError;
-pub use crate::jose_header::JoseHeader;
+pub use crate::algorithm::Algorithm;
+pub use crate::compact::{split_compact, CompactKind};
+pub use crate::jose_error::{
+    JoseError, JoseErrorContext, JwkParameterError, JwkParameterErrorKind,
+};
+pub use crate::jose_header::{decode_header, JoseHeader};
 
 pub use serde_json::{Map, Number, Value};
 
@@ -1,24 +1,113 @@
+pub(crate) mod cbor;
 pub mod der;
 pub mod hash_algorithm;
+mod json;
 pub mod oid;
+#[cfg(feature = "pool")]
+pub(crate) mod pool;
+pub mod random_source;
 
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 use once_cell::sync::Lazy;
 use openssl::bn::BigNumRef;
-use openssl::rand;
 use regex::{self, bytes};
 
+use crate::JoseError;
+
 pub use crate::util::hash_algorithm::HashAlgorithm;
+pub(crate) use crate::util::json::{parse_json_object, to_sorted_json_string};
+pub use crate::util::random_source::{random_source, OpenSslRandomSource, RandomSource};
+#[cfg(feature = "test-rng")]
+pub use crate::util::random_source::{set_random_source, SeededRandomSource};
 
 pub use HashAlgorithm::Sha1 as SHA_1;
 pub use HashAlgorithm::Sha256 as SHA_256;
 pub use HashAlgorithm::Sha384 as SHA_384;
 pub use HashAlgorithm::Sha512 as SHA_512;
 
+/// Return random bytes of a specified length generated by OpenSSL's CSPRNG.
+///
+/// This function always uses `OpenSslRandomSource`, even if the random source of this
+/// library has been replaced. Use `random_bytes_from` to handle errors or to choose
+/// another random source.
+///
+/// # Panics
+///
+/// Panics if OpenSSL fails to generate random bytes.
+///
+/// # Arguments
+///
+/// * `len` - a length of the random bytes
 pub fn random_bytes(len: usize) -> Vec<u8> {
+    random_bytes_from(&OpenSslRandomSource, len).unwrap()
+}
+
+/// Return random bytes of a specified length generated by the random source of this library.
+pub(crate) fn try_random_bytes(len: usize) -> Result<Vec<u8>, JoseError> {
+    random_bytes_from(&*random_source(), len)
+}
+
+/// Return random bytes of a specified length generated by a random source.
+///
+/// # Arguments
+///
+/// * `source` - a random source
+/// * `len` - a length of the random bytes
+pub fn random_bytes_from(source: &dyn RandomSource, len: usize) -> Result<Vec<u8>, JoseError> {
     let mut vec = vec![0; len];
-    rand::rand_bytes(&mut vec).unwrap();
-    vec
+    source.fill_bytes(&mut vec)?;
+    Ok(vec)
+}
+
+/// Return a base64url string without padding (RFC 4648 Section 5) of the input.
+///
+/// # Arguments
+///
+/// * `input` - The data to encode.
+pub fn encode_base64url(input: impl AsRef<[u8]>) -> String {
+    base64::encode_config(input, base64::URL_SAFE_NO_PAD)
+}
+
+/// Return the data decoded from a base64url string without padding (RFC 4648 Section 5).
+///
+/// A padded string or a string of the standard base64 alphabet is rejected.
+///
+/// # Arguments
+///
+/// * `input` - The base64url string to decode.
+pub fn decode_base64url(input: impl AsRef<str>) -> Result<Vec<u8>, JoseError> {
+    (|| -> anyhow::Result<Vec<u8>> {
+        let input = input.as_ref();
+        if input.ends_with('=') {
+            bail!("The base64url string must not be padded: {}", input);
+        }
+        let data = base64::decode_config(input, base64::URL_SAFE_NO_PAD)?;
+        Ok(data)
+    })()
+    .map_err(JoseError::InvalidJwkFormat)
+}
+
+/// Decode a base64url segment of a compact serialization.
+///
+/// If the segment cannot be decoded and it contains a character of the standard
+/// base64 alphabet (+, / or padding =), the error says that the token is likely
+/// mis-encoded instead of only reporting the invalid byte.
+pub(crate) fn decode_base64url_segment(input: impl AsRef<[u8]>) -> anyhow::Result<Vec<u8>> {
+    let input = input.as_ref();
+    match base64::decode_config(input, base64::URL_SAFE_NO_PAD) {
+        Ok(val) => Ok(val),
+        Err(err) => {
+            if let Some(pos) = input.iter().position(|b| matches!(b, b'+' | b'/' | b'=')) {
+                bail!(
+                    "The segment contains '{}' at offset {}, which is not allowed in base64url. \
+                    The token may be encoded with the standard base64 alphabet.",
+                    input[pos] as char,
+                    pos
+                );
+            }
+            Err(err.into())
+        }
+    }
 }
 
 pub(crate) fn ceiling(len: usize, div: usize) -> usize {
@@ -36,30 +125,40 @@ pub(crate) fn is_base64_url_safe_nopad(input: &str) -> bool {
     RE_BASE64.is_match(input)
 }
 
+pub(crate) fn decode_base64_url_lenient(
+    input: impl AsRef<[u8]>,
+) -> Result<Vec<u8>, base64::DecodeError> {
+    let input = input.as_ref();
+    let mut end = input.len();
+    while end > 0 && input[end - 1] == b'=' {
+        end -= 1;
+    }
+
+    let translated: Vec<u8> = input[..end]
+        .iter()
+        .map(|b| match b {
+            b'+' => b'-',
+            b'/' => b'_',
+            _ => *b,
+        })
+        .collect();
+    base64::decode_config(&translated, base64::URL_SAFE_NO_PAD)
+}
+
+/// A PEM block: the label, the base64 data and the label of the end boundary.
+const PEM_BLOCK: &str = concat!(
+    r"-----BEGIN ([A-Z0-9 -]+)-----[\t ]*(?:\r\n|[\r\n])",
+    r"([\t\r\n a-zA-Z0-9+/=]+)",
+    r"-----END ([A-Z0-9 -]+)-----",
+);
+
 pub(crate) fn parse_pem(input: &[u8]) -> anyhow::Result<(String, Vec<u8>)> {
     static RE_PEM: Lazy<bytes::Regex> = Lazy::new(|| {
-        bytes::Regex::new(concat!(
-            r"^",
-            r"-----BEGIN ([A-Z0-9 -]+)-----[\t ]*(?:\r\n|[\r\n])",
-            r"([\t\r\n a-zA-Z0-9+/=]+)",
-            r"-----END ([A-Z0-9 -]+)-----[\t ]*(?:\r\n|[\r\n])?",
-            r"$"
-        ))
-        .unwrap()
+        bytes::Regex::new(&format!(r"^{}[\t ]*(?:\r\n|[\r\n])?$", PEM_BLOCK)).unwrap()
     });
 
-    static RE_FILTER: Lazy<bytes::Regex> = Lazy::new(|| bytes::Regex::new("[\t\r\n ]").unwrap());
-
     let result = if let Some(caps) = RE_PEM.captures(input) {
-        match (caps.get(1), caps.get(2), caps.get(3)) {
-            (Some(ref m1), Some(ref m2), Some(ref m3)) if m1.as_bytes() == m3.as_bytes() => {
-                let alg = String::from_utf8(m1.as_bytes().to_vec())?;
-                let base64_data = RE_FILTER.replace_all(m2.as_bytes(), bytes::NoExpand(b""));
-                let data = base64::decode_config(&base64_data, base64::STANDARD)?;
-                (alg, data)
-            }
-            _ => bail!("Mismatched the begging and ending label."),
-        }
+        decode_pem_block(&caps)?
     } else {
         bail!("Invalid PEM format.");
     };
@@ -67,6 +166,66 @@ pub(crate) fn parse_pem(input: &[u8]) -> anyhow::Result<(String, Vec<u8>)> {
     Ok(result)
 }
 
+/// Return every PEM block of the input with its label, in order of appearance.
+///
+/// This is useful for a bundle that concatenates a certificate and its private
+/// key. Text outside the blocks, such as comments, is ignored.
+///
+/// # Arguments
+///
+/// * `input` - The data that contains PEM blocks.
+pub fn parse_pem_all(input: impl AsRef<[u8]>) -> Result<Vec<(String, Vec<u8>)>, JoseError> {
+    parse_pem_blocks(input.as_ref())
+        .into_iter()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(JoseError::InvalidKeyFormat)
+}
+
+/// Return the result of decoding each PEM block of the input, in order of appearance.
+///
+/// A block that cannot be decoded results in an error at its position, and the
+/// following blocks are still decoded.
+pub(crate) fn parse_pem_blocks(input: &[u8]) -> Vec<anyhow::Result<(String, Vec<u8>)>> {
+    static RE_PEM_BLOCK: Lazy<bytes::Regex> =
+        Lazy::new(|| bytes::Regex::new(&format!("^{}", PEM_BLOCK)).unwrap());
+
+    const BEGIN: &[u8] = b"-----BEGIN ";
+
+    let mut blocks = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = input[pos..]
+        .windows(BEGIN.len())
+        .position(|window| window == BEGIN)
+    {
+        let start = pos + offset;
+        match RE_PEM_BLOCK.captures(&input[start..]) {
+            Some(caps) => {
+                blocks.push(decode_pem_block(&caps));
+                pos = start + caps[0].len();
+            }
+            None => {
+                blocks.push(Err(anyhow!("Invalid PEM format.")));
+                pos = start + BEGIN.len();
+            }
+        }
+    }
+    blocks
+}
+
+fn decode_pem_block(caps: &bytes::Captures) -> anyhow::Result<(String, Vec<u8>)> {
+    static RE_FILTER: Lazy<bytes::Regex> = Lazy::new(|| bytes::Regex::new("[\t\r\n ]").unwrap());
+
+    match (caps.get(1), caps.get(2), caps.get(3)) {
+        (Some(m1), Some(m2), Some(m3)) if m1.as_bytes() == m3.as_bytes() => {
+            let label = String::from_utf8(m1.as_bytes().to_vec())?;
+            let base64_data = RE_FILTER.replace_all(m2.as_bytes(), bytes::NoExpand(b""));
+            let data = base64::decode_config(&base64_data, base64::STANDARD)?;
+            Ok((label, data))
+        }
+        _ => bail!("Mismatched the beginning and ending label."),
+    }
+}
+
 pub(crate) fn num_to_vec(num: &BigNumRef, len: usize) -> Vec<u8> {
     let vec = num.to_vec();
     if vec.len() < len {
@@ -81,9 +240,47 @@ pub(crate) fn num_to_vec(num: &BigNumRef, len: usize) -> Vec<u8> {
     }
 }
 
+/// Return the bytes of a hexadecimal string for test vectors.
+#[cfg(test)]
+pub(crate) fn decode_hex(input: &str) -> Vec<u8> {
+    (0..input.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&input[i..(i + 2)], 16).unwrap())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::is_base64_url_safe_nopad;
+    use anyhow::Result;
+    use openssl::asn1::Asn1Time;
+    use openssl::hash::MessageDigest;
+    use openssl::pkey::PKey;
+    use openssl::x509::{X509Builder, X509NameBuilder};
+
+    use super::{decode_base64url, encode_base64url, is_base64_url_safe_nopad, parse_pem_all};
+    use crate::jwk::KeyPair;
+    use crate::jws::ES256;
+
+    #[test]
+    fn test_base64url() -> Result<()> {
+        assert_eq!(encode_base64url(b""), "");
+        assert_eq!(encode_base64url(b"0"), "MA");
+        assert_eq!(encode_base64url([0xfb, 0xff]), "-_8");
+
+        assert_eq!(decode_base64url("")?, b"");
+        assert_eq!(decode_base64url("MA")?, b"0");
+        assert_eq!(decode_base64url("-_8")?, vec![0xfb, 0xff]);
+        assert_eq!(decode_base64url("MDEyMzQ1Njc4OQ")?, b"0123456789");
+
+        assert!(decode_base64url("MA==").is_err());
+        assert!(decode_base64url("MDEyMzQ1Njc4OQ==").is_err());
+        assert!(decode_base64url("-_8=").is_err());
+        assert!(decode_base64url("+/8").is_err());
+        assert!(decode_base64url("M").is_err());
+        assert!(decode_base64url("AB<>").is_err());
+
+        Ok(())
+    }
 
     #[test]
     fn test_is_base64_url_safe_nopad() {
@@ -94,4 +291,47 @@ mod tests {
         assert!(!is_base64_url_safe_nopad("MDEyMzQ1Njc4OQ="));
         assert!(!is_base64_url_safe_nopad("MDEyMzQ1Njc4O"));
     }
+
+    #[test]
+    fn test_parse_pem_all() -> Result<()> {
+        let key_pair = ES256.generate_key_pair()?;
+        let private_key = PKey::private_key_from_der(&key_pair.to_der_private_key())?;
+
+        let mut name = X509NameBuilder::new()?;
+        name.append_entry_by_text("CN", "example.com")?;
+        let name = name.build();
+        let mut builder = X509Builder::new()?;
+        builder.set_version(2)?;
+        builder.set_subject_name(&name)?;
+        builder.set_issuer_name(&name)?;
+        builder.set_pubkey(&private_key)?;
+        builder.set_not_before(Asn1Time::days_from_now(0)?.as_ref())?;
+        builder.set_not_after(Asn1Time::days_from_now(1)?.as_ref())?;
+        builder.sign(&private_key, MessageDigest::sha256())?;
+        let cert = builder.build();
+
+        let mut bundle = b"# example.com\n".to_vec();
+        bundle.extend_from_slice(&cert.to_pem()?);
+        bundle.extend_from_slice(b"\n");
+        bundle.extend_from_slice(&key_pair.to_pem_private_key());
+
+        let blocks = parse_pem_all(&bundle)?;
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].0, "CERTIFICATE");
+        assert_eq!(blocks[0].1, cert.to_der()?);
+        assert_eq!(blocks[1].0, "PRIVATE KEY");
+        assert_eq!(blocks[1].1, private_key.private_key_to_pkcs8()?);
+
+        let loaded = ES256.key_pair_from_der(&blocks[1].1)?;
+        assert_eq!(loaded.to_der_private_key(), key_pair.to_der_private_key());
+
+        assert!(parse_pem_all(b"")?.is_empty());
+        bundle.extend_from_slice(b"-----BEGIN CERTIFICATE-----\nMA==\n");
+        assert!(parse_pem_all(&bundle).is_err());
+        let err = parse_pem_all(b"-----BEGIN CERTIFICATE-----\nMA==\n-----END PRIVATE KEY-----")
+            .unwrap_err();
+        assert!(format!("{}", err).contains("Mismatched the beginning and ending label."));
+
+        Ok(())
+    }
 }
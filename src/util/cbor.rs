@@ -0,0 +1,255 @@
+//! Concise Binary Object Representation (CBOR) utilities.
+//!
+//! Only the subset of RFC 8949 needed for COSE keys is supported:
+//! integers, byte and text strings, arrays, maps and the simple values
+//! true, false and null, all with definite lengths.
+
+use anyhow::bail;
+
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum CborValue {
+    Integer(i64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<CborValue>),
+    Map(Vec<(CborValue, CborValue)>),
+    Bool(bool),
+    Null,
+}
+
+impl CborValue {
+    /// Decode a single CBOR data item.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - A CBOR encoded data item without trailing bytes.
+    pub fn decode(input: &[u8]) -> anyhow::Result<Self> {
+        let mut reader = CborReader { input, pos: 0 };
+        let value = reader.read_value(0)?;
+        if reader.pos != input.len() {
+            bail!("The CBOR data item is followed by trailing bytes.");
+        }
+        Ok(value)
+    }
+
+    /// Encode this value in the deterministic encoding (RFC 8949 Section 4.2.1).
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write(&mut out);
+        out
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::Integer(val) if *val >= 0 => write_head(out, 0, *val as u64),
+            Self::Integer(val) => write_head(out, 1, (-1 - *val) as u64),
+            Self::Bytes(val) => {
+                write_head(out, 2, val.len() as u64);
+                out.extend_from_slice(val);
+            }
+            Self::Text(val) => {
+                write_head(out, 3, val.len() as u64);
+                out.extend_from_slice(val.as_bytes());
+            }
+            Self::Array(vals) => {
+                write_head(out, 4, vals.len() as u64);
+                for val in vals {
+                    val.write(out);
+                }
+            }
+            Self::Map(entries) => {
+                let mut entries: Vec<(Vec<u8>, Vec<u8>)> = entries
+                    .iter()
+                    .map(|(key, val)| (key.encode(), val.encode()))
+                    .collect();
+                entries.sort();
+
+                write_head(out, 5, entries.len() as u64);
+                for (key, val) in entries {
+                    out.extend_from_slice(&key);
+                    out.extend_from_slice(&val);
+                }
+            }
+            Self::Bool(false) => out.push(0xF4),
+            Self::Bool(true) => out.push(0xF5),
+            Self::Null => out.push(0xF6),
+        }
+    }
+}
+
+fn write_head(out: &mut Vec<u8>, major: u8, arg: u64) {
+    let major = major << 5;
+    if arg < 24 {
+        out.push(major | arg as u8);
+    } else if arg <= u8::MAX as u64 {
+        out.push(major | 24);
+        out.push(arg as u8);
+    } else if arg <= u16::MAX as u64 {
+        out.push(major | 25);
+        out.extend_from_slice(&(arg as u16).to_be_bytes());
+    } else if arg <= u32::MAX as u64 {
+        out.push(major | 26);
+        out.extend_from_slice(&(arg as u32).to_be_bytes());
+    } else {
+        out.push(major | 27);
+        out.extend_from_slice(&arg.to_be_bytes());
+    }
+}
+
+struct CborReader<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> CborReader<'a> {
+    fn read_value(&mut self, depth: usize) -> anyhow::Result<CborValue> {
+        if depth > MAX_DEPTH {
+            bail!("The CBOR data item is nested too deeply.");
+        }
+
+        let (major, arg) = self.read_head()?;
+        let value = match major {
+            0 => CborValue::Integer(to_i64(arg)?),
+            1 => CborValue::Integer(-1 - to_i64(arg)?),
+            2 => CborValue::Bytes(self.read_bytes(arg)?.to_vec()),
+            3 => match std::str::from_utf8(self.read_bytes(arg)?) {
+                Ok(val) => CborValue::Text(val.to_string()),
+                Err(_) => bail!("A CBOR text string must be valid UTF-8."),
+            },
+            4 => {
+                let len = self.check_count(arg)?;
+                let mut vals = Vec::with_capacity(len);
+                for _ in 0..len {
+                    vals.push(self.read_value(depth + 1)?);
+                }
+                CborValue::Array(vals)
+            }
+            5 => {
+                let len = self.check_count(arg)?;
+                let mut entries: Vec<(CborValue, CborValue)> = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let key = self.read_value(depth + 1)?;
+                    if entries.iter().any(|(val, _)| val == &key) {
+                        bail!("A CBOR map must not contain duplicate keys.");
+                    }
+                    let val = self.read_value(depth + 1)?;
+                    entries.push((key, val));
+                }
+                CborValue::Map(entries)
+            }
+            6 => bail!("CBOR tags are not supported."),
+            _ => match arg {
+                20 => CborValue::Bool(false),
+                21 => CborValue::Bool(true),
+                22 => CborValue::Null,
+                _ => bail!("The CBOR simple value or float is not supported."),
+            },
+        };
+        Ok(value)
+    }
+
+    fn read_head(&mut self) -> anyhow::Result<(u8, u64)> {
+        let initial = self.read_bytes(1)?[0];
+        let major = initial >> 5;
+        let info = initial & 0x1F;
+        let arg = match info {
+            0..=23 => info as u64,
+            24..=27 => {
+                let len = 1 << (info - 24);
+                self.read_bytes(len)?
+                    .iter()
+                    .fold(0u64, |acc, b| (acc << 8) | *b as u64)
+            }
+            31 => bail!("CBOR indefinite lengths are not supported."),
+            _ => bail!("The CBOR additional information is reserved: {}", info),
+        };
+        if major == 7 && info >= 24 {
+            bail!("The CBOR simple value or float is not supported.");
+        }
+        Ok((major, arg))
+    }
+
+    fn read_bytes(&mut self, len: u64) -> anyhow::Result<&'a [u8]> {
+        let remaining = self.input.len() - self.pos;
+        if len > remaining as u64 {
+            bail!("The CBOR data item is truncated.");
+        }
+        let start = self.pos;
+        self.pos += len as usize;
+        Ok(&self.input[start..self.pos])
+    }
+
+    fn check_count(&self, count: u64) -> anyhow::Result<usize> {
+        // Every element takes at least one byte.
+        if count > (self.input.len() - self.pos) as u64 {
+            bail!("The CBOR data item is truncated.");
+        }
+        Ok(count as usize)
+    }
+}
+
+fn to_i64(arg: u64) -> anyhow::Result<i64> {
+    if arg > i64::MAX as u64 {
+        bail!("The CBOR integer is out of range.");
+    }
+    Ok(arg as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::CborValue;
+
+    #[test]
+    fn test_cbor_round_trip() -> Result<()> {
+        let value = CborValue::Map(vec![
+            (CborValue::Integer(-1), CborValue::Integer(1)),
+            (CborValue::Integer(1), CborValue::Bytes(vec![0xAB; 300])),
+            (
+                CborValue::Text("ops".to_string()),
+                CborValue::Array(vec![CborValue::Bool(true), CborValue::Null]),
+            ),
+        ]);
+        let encoded = value.encode();
+        assert_eq!(&encoded[..3], &[0xA3, 0x01, 0x59]);
+
+        let decoded = CborValue::decode(&encoded)?;
+        assert_eq!(decoded.encode(), encoded);
+
+        assert_eq!(
+            CborValue::decode(&[0x3B, 0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF])?,
+            CborValue::Integer(i64::MIN)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cbor_reject_malformed() -> Result<()> {
+        // trailing bytes
+        assert!(CborValue::decode(&[0x01, 0x02]).is_err());
+        // truncated byte string
+        assert!(CborValue::decode(&[0x43, 0x01, 0x02]).is_err());
+        // indefinite length array
+        assert!(CborValue::decode(&[0x9F, 0x01, 0xFF]).is_err());
+        // duplicate map keys
+        assert!(CborValue::decode(&[0xA2, 0x01, 0x01, 0x01, 0x02]).is_err());
+        // huge declared length
+        assert!(
+            CborValue::decode(&[0x9B, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]).is_err()
+        );
+        // tag
+        assert!(CborValue::decode(&[0xC1, 0x01]).is_err());
+        // float
+        assert!(CborValue::decode(&[0xF9, 0x00, 0x00]).is_err());
+        // nested too deeply
+        let mut nested = vec![0x81; 32];
+        nested.push(0x01);
+        assert!(CborValue::decode(&nested).is_err());
+
+        Ok(())
+    }
+}
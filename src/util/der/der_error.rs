@@ -14,9 +14,15 @@ pub enum DerError {
     #[error("Invalid contents: {0}")]
     InvalidContents(String),
 
+    #[error("Non-canonical encoding: {0}")]
+    NonCanonicalEncoding(String),
+
     #[error("Overflow length.")]
     Overflow,
 
+    #[error("Nesting is deeper than {0} levels.")]
+    NestingTooDeep(usize),
+
     #[error("Failed to read: {0}")]
     ReadFailure(#[source] std::io::Error),
 }
@@ -5,6 +5,14 @@ use std::io::{Bytes, Read};
 use crate::util::der::{DerClass, DerError, DerType};
 use crate::util::oid::ObjectIdentifier;
 
+/// The maximum nesting depth of constructed types.
+const MAX_DEPTH: usize = 64;
+
+/// The maximum capacity that is reserved in advance for primitive contents.
+/// Larger contents grow as they are actually read, so a forged length
+/// cannot force a huge allocation.
+const MAX_PREALLOCATED_LEN: usize = 4096;
+
 struct DerStackItem {
     len: Option<usize>,
     parsed_len: usize,
@@ -17,6 +25,7 @@ pub struct DerReader<R: Read> {
     constructed: bool,
     contents: Option<Vec<u8>>,
     read_count: usize,
+    strict: bool,
 }
 
 impl<'a> DerReader<&'a [u8]> {
@@ -34,9 +43,26 @@ impl<R: Read> DerReader<R> {
             constructed: false,
             contents: None,
             read_count: 0,
+            strict: false,
         }
     }
 
+    /// Return whether this reader rejects encodings that are not canonical DER.
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Set whether this reader rejects encodings that are not canonical DER:
+    /// indefinite lengths, non-minimal length octets and integers with
+    /// redundant leading octets.
+    ///
+    /// # Arguments
+    ///
+    /// * `strict` - true if the reader is strict.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
     pub fn next(&mut self) -> Result<Option<DerType>, DerError> {
         let mut depth = self.stack.len();
         let mut is_indefinite_parent = false;
@@ -63,6 +89,7 @@ impl<R: Read> DerReader<R> {
         let start_read_count = self.read_count;
 
         match self.get_tag()? {
+            None if depth > 0 => return Err(DerError::UnexpectedEndOfInput),
             None => return Ok(None),
             Some((DerType::EndOfContents, constructed)) => {
                 if !is_indefinite_parent {
@@ -106,10 +133,21 @@ impl<R: Read> DerReader<R> {
                     )));
                 }
 
+                if depth >= MAX_DEPTH {
+                    return Err(DerError::NestingTooDeep(MAX_DEPTH));
+                }
+
                 let olength = self.get_length()?;
                 let offset = self.read_count - start_read_count;
+                let len = match olength {
+                    Some(val) => {
+                        self.check_remaining_len(val, offset)?;
+                        Some(val.checked_add(offset).ok_or(DerError::Overflow)?)
+                    }
+                    None => None,
+                };
                 self.stack.push(DerStackItem {
-                    len: olength.map(|val| val + offset),
+                    len,
                     parsed_len: offset,
                 });
 
@@ -134,7 +172,9 @@ impl<R: Read> DerReader<R> {
                     }
                 };
 
-                let mut contents = Vec::with_capacity(length);
+                self.check_remaining_len(length, self.read_count - start_read_count)?;
+
+                let mut contents = Vec::with_capacity(length.min(MAX_PREALLOCATED_LEN));
                 for _ in 0..length {
                     match self.get()? {
                         Some(val) => contents.push(val),
@@ -142,6 +182,20 @@ impl<R: Read> DerReader<R> {
                     }
                 }
 
+                if self.strict {
+                    if let DerType::Integer | DerType::Enumerated = der_type {
+                        if contents.len() > 1
+                            && ((contents[0] == 0x00 && contents[1] & 0x80 == 0)
+                                || (contents[0] == 0xFF && contents[1] & 0x80 != 0))
+                        {
+                            return Err(DerError::NonCanonicalEncoding(format!(
+                                "{} contents must not have redundant leading octets.",
+                                der_type
+                            )));
+                        }
+                    }
+                }
+
                 if depth > 0 {
                     let offset = self.read_count - start_read_count;
                     self.stack[depth - 1].parsed_len += offset;
@@ -292,6 +346,58 @@ impl<R: Read> DerReader<R> {
         }
     }
 
+    pub fn to_u32(&self) -> Result<u32, DerError> {
+        let value = self.to_unsigned(4)?;
+        Ok(value as u32)
+    }
+
+    pub fn to_usize(&self) -> Result<usize, DerError> {
+        let value = self.to_unsigned(std::mem::size_of::<usize>())?;
+        Ok(value as usize)
+    }
+
+    fn to_unsigned(&self, max_len: usize) -> Result<u64, DerError> {
+        if let DerType::Integer | DerType::Enumerated = self.der_type {
+            if let Some(contents) = &self.contents {
+                if contents.is_empty() {
+                    return Err(DerError::InvalidLength(format!(
+                        "{} content length must be 1 or more.",
+                        self.der_type
+                    )));
+                }
+
+                if contents[0] & 0x80 != 0 {
+                    return Err(DerError::InvalidContents(format!(
+                        "{} contents must not be negative.",
+                        self.der_type
+                    )));
+                }
+
+                let mut contents = &contents[..];
+                while contents.len() > 1 && contents[0] == 0 {
+                    contents = &contents[1..];
+                }
+
+                if contents.len() > max_len {
+                    return Err(DerError::Overflow);
+                }
+
+                let mut value = 0u64;
+                for b in contents {
+                    value = (value << 8) | *b as u64;
+                }
+                Ok(value)
+            } else {
+                unreachable!();
+            }
+        } else {
+            panic!(
+                "{} type is not supported to convert to unsigned integer.",
+                self.der_type
+            );
+        }
+    }
+
     pub fn to_be_bytes(&self, sign: bool, min_len: usize) -> Vec<u8> {
         if let DerType::Integer = self.der_type {
             if let Some(contents) = &self.contents {
@@ -513,6 +619,21 @@ impl<R: Read> DerReader<R> {
         }
     }
 
+    fn check_remaining_len(&self, length: usize, offset: usize) -> Result<(), DerError> {
+        if let Some(parent) = self.stack.last() {
+            if let Some(parent_len) = parent.len {
+                let remaining = parent_len.saturating_sub(parent.parsed_len + offset);
+                if length > remaining {
+                    return Err(DerError::InvalidLength(format!(
+                        "Content length {} exceeds the remaining length {} of the parent.",
+                        length, remaining
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn get_length(&mut self) -> Result<Option<usize>, DerError> {
         let result = match self.get()? {
             Some(val) if val == 0xFF => {
@@ -521,7 +642,14 @@ impl<R: Read> DerReader<R> {
                     val
                 )));
             }
-            Some(val) if val == 0x80 => None,
+            Some(val) if val == 0x80 => {
+                if self.strict {
+                    return Err(DerError::NonCanonicalEncoding(
+                        "Indefinite length is not allowed.".to_string(),
+                    ));
+                }
+                None
+            }
             Some(val) if val < 0x80 => Some(val as usize),
             Some(val) => {
                 let len_size = (val & 0x7F) as usize;
@@ -529,14 +657,25 @@ impl<R: Read> DerReader<R> {
                     return Err(DerError::Overflow);
                 }
                 let mut num = 0usize;
-                for _ in 0..len_size {
+                for i in 0..len_size {
                     match self.get()? {
                         Some(val) => {
+                            if self.strict && i == 0 && val == 0 {
+                                return Err(DerError::NonCanonicalEncoding(
+                                    "Length octets must not have leading zeros.".to_string(),
+                                ));
+                            }
                             num = num << 8 | val as usize;
                         }
                         None => return Err(DerError::UnexpectedEndOfInput),
                     }
                 }
+                if self.strict && num < 0x80 {
+                    return Err(DerError::NonCanonicalEncoding(format!(
+                        "Length {} must be encoded in the short form.",
+                        num
+                    )));
+                }
                 Some(num)
             }
             None => return Err(DerError::UnexpectedEndOfInput),
@@ -616,6 +755,146 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn parse_non_canonical_der() -> Result<()> {
+        // A sequence with a non-minimal length of 0x81 0x03.
+        let input = vec![0x30, 0x81, 0x03, 0x02, 0x01, 0x01];
+        let mut parser = DerReader::from_bytes(&input);
+        assert!(matches!(parser.next()?, Some(DerType::Sequence)));
+        assert!(matches!(parser.next()?, Some(DerType::Integer)));
+        assert_eq!(parser.to_u8()?, 1);
+        assert!(matches!(parser.next()?, Some(DerType::EndOfContents)));
+
+        let mut parser = DerReader::from_bytes(&input);
+        parser.set_strict(true);
+        assert!(matches!(
+            parser.next(),
+            Err(DerError::NonCanonicalEncoding(_))
+        ));
+
+        // A sequence with an indefinite length.
+        let input = vec![0x30, 0x80, 0x02, 0x01, 0x01, 0x00, 0x00];
+        let mut parser = DerReader::from_bytes(&input);
+        assert!(matches!(parser.next()?, Some(DerType::Sequence)));
+        assert!(matches!(parser.next()?, Some(DerType::Integer)));
+        assert!(matches!(parser.next()?, Some(DerType::EndOfContents)));
+
+        let mut parser = DerReader::from_bytes(&input);
+        parser.set_strict(true);
+        assert!(matches!(
+            parser.next(),
+            Err(DerError::NonCanonicalEncoding(_))
+        ));
+
+        // An integer with a redundant leading zero.
+        let input = vec![0x02, 0x02, 0x00, 0x01];
+        let mut parser = DerReader::from_bytes(&input);
+        assert!(matches!(parser.next()?, Some(DerType::Integer)));
+
+        let mut parser = DerReader::from_bytes(&input);
+        parser.set_strict(true);
+        assert!(matches!(
+            parser.next(),
+            Err(DerError::NonCanonicalEncoding(_))
+        ));
+
+        // A positive integer with a necessary leading zero.
+        let input = vec![0x02, 0x02, 0x00, 0x80];
+        let mut parser = DerReader::from_bytes(&input);
+        parser.set_strict(true);
+        assert!(matches!(parser.next()?, Some(DerType::Integer)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_der_unsigned_integer() -> Result<()> {
+        // A two-byte INTEGER 0x0120 (288).
+        let input = vec![0x02, 0x02, 0x01, 0x20];
+        let mut parser = DerReader::from_bytes(&input);
+        assert!(matches!(parser.next()?, Some(DerType::Integer)));
+        assert_eq!(parser.to_u32()?, 288);
+        assert_eq!(parser.to_usize()?, 288);
+        assert!(matches!(parser.to_u8(), Err(DerError::Overflow)));
+
+        // A positive INTEGER 128 with a leading zero.
+        let input = vec![0x02, 0x02, 0x00, 0x80];
+        let mut parser = DerReader::from_bytes(&input);
+        assert!(matches!(parser.next()?, Some(DerType::Integer)));
+        assert_eq!(parser.to_u32()?, 128);
+
+        // The largest u32 INTEGER.
+        let input = vec![0x02, 0x05, 0x00, 0xFF, 0xFF, 0xFF, 0xFF];
+        let mut parser = DerReader::from_bytes(&input);
+        assert!(matches!(parser.next()?, Some(DerType::Integer)));
+        assert_eq!(parser.to_u32()?, u32::MAX);
+
+        // A negative INTEGER.
+        let input = vec![0x02, 0x01, 0x80];
+        let mut parser = DerReader::from_bytes(&input);
+        assert!(matches!(parser.next()?, Some(DerType::Integer)));
+        assert!(matches!(parser.to_u32(), Err(DerError::InvalidContents(_))));
+
+        // An INTEGER that exceeds u32.
+        let input = vec![0x02, 0x05, 0x01, 0x00, 0x00, 0x00, 0x00];
+        let mut parser = DerReader::from_bytes(&input);
+        assert!(matches!(parser.next()?, Some(DerType::Integer)));
+        assert!(matches!(parser.to_u32(), Err(DerError::Overflow)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_malformed_der() -> Result<()> {
+        fn read_all(input: &[u8]) -> std::result::Result<(), DerError> {
+            let mut parser = DerReader::from_bytes(&input);
+            while parser.next()?.is_some() {}
+            Ok(())
+        }
+
+        // Truncated inputs of a valid sequence.
+        let input = vec![0x30, 0x06, 0x02, 0x01, 0x01, 0x04, 0x01, 0xFF];
+        assert!(read_all(&input).is_ok());
+        for i in 1..input.len() {
+            assert!(read_all(&input[..i]).is_err(), "truncated at {}", i);
+        }
+
+        // An OCTET STRING whose length field claims about 4 gigabytes.
+        let input = vec![0x04, 0x84, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+        assert!(matches!(
+            read_all(&input),
+            Err(DerError::UnexpectedEndOfInput)
+        ));
+
+        // A length that does not fit in usize.
+        let input = vec![0x04, 0x89, 0x01, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert!(matches!(read_all(&input), Err(DerError::Overflow)));
+
+        // A child that is longer than the remaining length of the parent.
+        let input = vec![0x30, 0x03, 0x04, 0x84, 0x7F, 0xFF, 0xFF, 0xFF];
+        assert!(matches!(read_all(&input), Err(DerError::InvalidLength(_))));
+        let input = vec![0x30, 0x04, 0x30, 0x03, 0x02, 0x01, 0x01];
+        assert!(matches!(read_all(&input), Err(DerError::InvalidLength(_))));
+
+        // Deeply nested sequences.
+        let mut input = Vec::new();
+        for _ in 0..1000 {
+            input.extend_from_slice(&[0x30, 0x80]);
+        }
+        assert!(matches!(read_all(&input), Err(DerError::NestingTooDeep(_))));
+
+        let mut input = vec![0x05, 0x00];
+        for _ in 0..1000 {
+            let mut outer = vec![0x30, 0x84];
+            outer.extend_from_slice(&(input.len() as u32).to_be_bytes());
+            outer.extend_from_slice(&input);
+            input = outer;
+        }
+        assert!(matches!(read_all(&input), Err(DerError::NestingTooDeep(_))));
+
+        Ok(())
+    }
+
     fn load_file(path: &str) -> Result<File> {
         let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         pb.push("data");
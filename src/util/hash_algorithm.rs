@@ -1,4 +1,6 @@
 use openssl::hash::MessageDigest;
+#[cfg(feature = "pool")]
+use openssl::md::{Md, MdRef};
 use std::fmt::Display;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -10,7 +12,7 @@ pub enum HashAlgorithm {
 }
 
 impl HashAlgorithm {
-    pub fn name(&self) -> &str {
+    pub fn name(&self) -> &'static str {
         match self {
             Self::Sha1 => "SHA-1",
             Self::Sha256 => "SHA-256",
@@ -36,6 +38,16 @@ impl HashAlgorithm {
             Self::Sha512 => MessageDigest::sha512(),
         }
     }
+
+    #[cfg(feature = "pool")]
+    pub(crate) fn md(&self) -> &'static MdRef {
+        match self {
+            Self::Sha1 => Md::sha1(),
+            Self::Sha256 => Md::sha256(),
+            Self::Sha384 => Md::sha384(),
+            Self::Sha512 => Md::sha512(),
+        }
+    }
 }
 
 impl Display for HashAlgorithm {
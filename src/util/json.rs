@@ -0,0 +1,179 @@
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+
+use crate::{Map, Number, Value};
+
+/// Parse a JSON object and reject duplicate member names at any depth.
+///
+/// serde_json keeps the last value of a duplicate name, so a header like
+/// {"alg":"none","alg":"RS256"} could be read differently by another parser.
+///
+/// # Arguments
+///
+/// * `input` - a JSON text
+pub(crate) fn parse_json_object(input: &[u8]) -> Result<Map<String, Value>, serde_json::Error> {
+    let StrictValue(value) = serde_json::from_slice(input)?;
+    match value {
+        Value::Object(val) => Ok(val),
+        _ => Err(de::Error::custom("The JSON text must be a object.")),
+    }
+}
+
+/// Return a JSON text of a JSON object whose members are sorted by name at any depth.
+///
+/// The output is the same for equal objects regardless of the insertion order.
+///
+/// # Arguments
+///
+/// * `map` - a JSON object
+pub(crate) fn to_sorted_json_string(map: &Map<String, Value>) -> Result<String, serde_json::Error> {
+    fn sort_map(map: &Map<String, Value>) -> Map<String, Value> {
+        let mut keys: Vec<&String> = map.keys().collect();
+        keys.sort();
+
+        let mut sorted = Map::new();
+        for key in keys {
+            sorted.insert(key.clone(), sort_value(&map[key]));
+        }
+        sorted
+    }
+
+    fn sort_value(value: &Value) -> Value {
+        match value {
+            Value::Object(val) => Value::Object(sort_map(val)),
+            Value::Array(vals) => Value::Array(vals.iter().map(sort_value).collect()),
+            val => val.clone(),
+        }
+    }
+
+    serde_json::to_string(&sort_map(map))
+}
+
+struct StrictValue(Value);
+
+impl<'de> Deserialize<'de> for StrictValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(StrictValueVisitor)
+    }
+}
+
+struct StrictValueVisitor;
+
+impl<'de> Visitor<'de> for StrictValueVisitor {
+    type Value = StrictValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<StrictValue, E> {
+        Ok(StrictValue(Value::Bool(value)))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<StrictValue, E> {
+        Ok(StrictValue(Value::Number(value.into())))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<StrictValue, E> {
+        Ok(StrictValue(Value::Number(value.into())))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<StrictValue, E> {
+        Ok(StrictValue(
+            Number::from_f64(value).map_or(Value::Null, Value::Number),
+        ))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<StrictValue, E> {
+        Ok(StrictValue(Value::String(value.to_string())))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<StrictValue, E> {
+        Ok(StrictValue(Value::String(value)))
+    }
+
+    fn visit_none<E>(self) -> Result<StrictValue, E> {
+        Ok(StrictValue(Value::Null))
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<StrictValue, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        StrictValue::deserialize(deserializer)
+    }
+
+    fn visit_unit<E>(self) -> Result<StrictValue, E> {
+        Ok(StrictValue(Value::Null))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<StrictValue, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut vec = Vec::new();
+        while let Some(StrictValue(val)) = seq.next_element()? {
+            vec.push(val);
+        }
+        Ok(StrictValue(Value::Array(vec)))
+    }
+
+    fn visit_map<A>(self, mut access: A) -> Result<StrictValue, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut map = Map::new();
+        while let Some(key) = access.next_key::<String>()? {
+            if map.contains_key(&key) {
+                return Err(de::Error::custom(format!(
+                    "A duplicate key exists: {}",
+                    key
+                )));
+            }
+            let StrictValue(val) = access.next_value()?;
+            map.insert(key, val);
+        }
+        Ok(StrictValue(Value::Object(map)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use serde_json::json;
+
+    use super::{parse_json_object, to_sorted_json_string};
+
+    #[test]
+    fn test_parse_json_object() -> Result<()> {
+        let map = parse_json_object(br#"{"a":1,"b":[true,null,"c",-1.5],"d":{"e":"f"}}"#)?;
+        assert_eq!(
+            serde_json::Value::Object(map),
+            json!({"a":1,"b":[true,null,"c",-1.5],"d":{"e":"f"}})
+        );
+
+        assert!(parse_json_object(br#"{"alg":"none","alg":"RS256"}"#).is_err());
+        assert!(parse_json_object(br#"{"jwk":{"kty":"EC","kty":"RSA"}}"#).is_err());
+        assert!(parse_json_object(br#"[{"a":1}]"#).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_sorted_json_string() -> Result<()> {
+        let map1 = parse_json_object(br#"{"b":[{"d":1,"c":2}],"a":{"f":true,"e":null}}"#)?;
+        let map2 = parse_json_object(br#"{"a":{"e":null,"f":true},"b":[{"c":2,"d":1}]}"#)?;
+        assert_ne!(serde_json::to_string(&map1)?, serde_json::to_string(&map2)?);
+        assert_eq!(
+            to_sorted_json_string(&map1)?,
+            r#"{"a":{"e":null,"f":true},"b":[{"c":2,"d":1}]}"#
+        );
+        assert_eq!(to_sorted_json_string(&map1)?, to_sorted_json_string(&map2)?);
+
+        Ok(())
+    }
+}
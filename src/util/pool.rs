@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use openssl::error::ErrorStack;
+use openssl::md_ctx::MdCtx;
+
+/// A maximum number of idle contexts that are kept for each key.
+const MAX_IDLE_CONTEXTS: usize = 64;
+
+pub(crate) static MD_CTX_POOL: Lazy<MdCtxPool> = Lazy::new(MdCtxPool::new);
+
+/// Represents a thread-safe pool of OpenSSL message digest contexts.
+///
+/// The contexts are pooled by a key (e.g. an algorithm name) so that a signer
+/// can reuse them instead of allocating a new one for each signing.
+pub(crate) struct MdCtxPool {
+    contexts: Mutex<HashMap<String, Vec<MdCtx>>>,
+}
+
+impl MdCtxPool {
+    /// Return a new empty pool.
+    pub(crate) fn new() -> Self {
+        Self {
+            contexts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a context for a specified key from the pool or create a new context.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - a key of the pooled contexts
+    pub(crate) fn take(&self, key: &str) -> Result<MdCtx, ErrorStack> {
+        let ctx = match self.contexts.lock() {
+            Ok(mut contexts) => contexts.get_mut(key).and_then(|vec| vec.pop()),
+            Err(_) => None,
+        };
+
+        match ctx {
+            Some(val) => Ok(val),
+            None => MdCtx::new(),
+        }
+    }
+
+    /// Reset a context and give it back to the pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - a key of the pooled contexts
+    /// * `ctx` - a context that is taken from this pool
+    pub(crate) fn give_back(&self, key: &str, mut ctx: MdCtx) {
+        if ctx.reset().is_err() {
+            return;
+        }
+
+        if let Ok(mut contexts) = self.contexts.lock() {
+            let vec = contexts.entry(key.to_string()).or_insert_with(Vec::new);
+            if vec.len() < MAX_IDLE_CONTEXTS {
+                vec.push(ctx);
+            }
+        }
+    }
+
+    /// Return the number of idle contexts for a specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - a key of the pooled contexts
+    #[cfg(test)]
+    pub(crate) fn idle_len(&self, key: &str) -> usize {
+        match self.contexts.lock() {
+            Ok(contexts) => contexts.get(key).map_or(0, |vec| vec.len()),
+            Err(_) => 0,
+        }
+    }
+}
+
+impl Default for MdCtxPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::MdCtxPool;
+
+    #[test]
+    fn test_take_and_give_back() -> Result<()> {
+        let pool = MdCtxPool::new();
+        assert_eq!(pool.idle_len("PS256"), 0);
+
+        let ctx1 = pool.take("PS256")?;
+        let ctx2 = pool.take("PS256")?;
+        pool.give_back("PS256", ctx1);
+        pool.give_back("PS256", ctx2);
+        assert_eq!(pool.idle_len("PS256"), 2);
+        assert_eq!(pool.idle_len("PS512"), 0);
+
+        let _ctx = pool.take("PS256")?;
+        assert_eq!(pool.idle_len("PS256"), 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+use std::fmt::Debug;
+#[cfg(feature = "test-rng")]
+use std::sync::Mutex;
+use std::sync::{Arc, RwLock};
+
+use anyhow::anyhow;
+use once_cell::sync::Lazy;
+#[cfg(feature = "test-rng")]
+use openssl::hash::{hash, MessageDigest};
+use openssl::rand;
+
+use crate::JoseError;
+
+static RANDOM_SOURCE: Lazy<RwLock<Arc<dyn RandomSource>>> =
+    Lazy::new(|| RwLock::new(Arc::new(OpenSslRandomSource)));
+
+/// Represents a source of random bytes that is used to generate
+/// content encryption keys, initialization vectors, salts and oct keys.
+pub trait RandomSource: Debug + Send + Sync {
+    /// Fill a buffer with random bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - a buffer to be filled
+    fn fill_bytes(&self, buf: &mut [u8]) -> Result<(), JoseError>;
+}
+
+/// Represents the default random source that is backed by OpenSSL's CSPRNG.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct OpenSslRandomSource;
+
+impl RandomSource for OpenSslRandomSource {
+    fn fill_bytes(&self, buf: &mut [u8]) -> Result<(), JoseError> {
+        rand::rand_bytes(buf).map_err(|err| JoseError::RandomGenerationFailed(anyhow!(err)))
+    }
+}
+
+/// Represents a deterministic random source that derives bytes from a seed.
+///
+/// The output is SHA-256(seed || counter) for an incrementing counter.
+/// This source is only for reproducible test vectors and must never be used in production.
+#[cfg(feature = "test-rng")]
+#[derive(Debug)]
+pub struct SeededRandomSource {
+    seed: Vec<u8>,
+    state: Mutex<(u64, Vec<u8>)>,
+}
+
+#[cfg(feature = "test-rng")]
+impl SeededRandomSource {
+    /// Return a new random source that generates the same bytes for the same seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - a seed value
+    pub fn new(seed: impl AsRef<[u8]>) -> Self {
+        Self {
+            seed: seed.as_ref().to_vec(),
+            state: Mutex::new((0, Vec::new())),
+        }
+    }
+}
+
+#[cfg(feature = "test-rng")]
+impl RandomSource for SeededRandomSource {
+    fn fill_bytes(&self, buf: &mut [u8]) -> Result<(), JoseError> {
+        let mut state = self.state.lock().map_err(|_| {
+            JoseError::RandomGenerationFailed(anyhow!("The random source is poisoned."))
+        })?;
+        let (counter, pending) = &mut *state;
+        for byte in buf.iter_mut() {
+            if pending.is_empty() {
+                let mut input = self.seed.clone();
+                input.extend_from_slice(&counter.to_be_bytes());
+                let block = hash(MessageDigest::sha256(), &input)
+                    .map_err(|err| JoseError::RandomGenerationFailed(anyhow!(err)))?;
+                pending.extend(block.iter().rev());
+                *counter += 1;
+            }
+            *byte = pending.pop().unwrap();
+        }
+        Ok(())
+    }
+}
+
+/// Replace the random source of this library.
+///
+/// This function is only available with the `test-rng` feature, so that a
+/// production build cannot swap out the CSPRNG.
+///
+/// Key pairs of RSA, EC, Ed25519 and Ed448 are generated by OpenSSL and
+/// always use its own random number generator.
+///
+/// # Arguments
+///
+/// * `source` - a new random source
+#[cfg(feature = "test-rng")]
+pub fn set_random_source(source: impl RandomSource + 'static) {
+    let mut current = match RANDOM_SOURCE.write() {
+        Ok(val) => val,
+        Err(err) => err.into_inner(),
+    };
+    *current = Arc::new(source);
+}
+
+/// Return the current random source of this library.
+pub fn random_source() -> Arc<dyn RandomSource> {
+    match RANDOM_SOURCE.read() {
+        Ok(val) => Arc::clone(&val),
+        Err(err) => Arc::clone(&err.into_inner()),
+    }
+}
+
+#[cfg(all(test, feature = "test-rng"))]
+mod tests {
+    use anyhow::Result;
+
+    use super::{RandomSource, SeededRandomSource};
+    use crate::jwe::enc::aesgcm::AesgcmJweEncryption;
+    use crate::jwe::JweContentEncryption;
+    use crate::util;
+
+    #[test]
+    fn test_seeded_random_source() -> Result<()> {
+        let enc = AesgcmJweEncryption::A128gcm;
+
+        let source1 = SeededRandomSource::new(b"seed");
+        let iv1 = util::random_bytes_from(&source1, enc.iv_len())?;
+        let source2 = SeededRandomSource::new(b"seed");
+        let iv2 = util::random_bytes_from(&source2, enc.iv_len())?;
+        assert_eq!(iv1, iv2);
+
+        let mut long1 = vec![0; 100];
+        source1.fill_bytes(&mut long1)?;
+        let mut long2 = vec![0; 100];
+        source2.fill_bytes(&mut long2)?;
+        assert_eq!(long1, long2);
+
+        let source3 = SeededRandomSource::new(b"other seed");
+        let iv3 = util::random_bytes_from(&source3, enc.iv_len())?;
+        assert_ne!(iv1, iv3);
+
+        Ok(())
+    }
+}